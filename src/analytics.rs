@@ -5,13 +5,14 @@
  * including filtering, aggregation, and statistical analysis functions.
  */
 
-use std::collections::{HashMap, HashSet};
-use chrono::NaiveDate;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use chrono::{Datelike, NaiveDate};
 use std::sync::Arc;
 
 use crate::{
     Result, NppesError,
     data_types::*,
+    matching::{NameMatcher, SubstringMatcher, token_set_ratio},
 };
 
 /// Analytics engine for NPPES data analysis
@@ -105,18 +106,40 @@ impl<'a> NppesAnalytics<'a> {
         self.providers.iter().find(|p| &p.npi == npi)
     }
     
-    /// Find providers by name (partial match)
+    /// Find providers by name (partial match against any of their names)
     pub fn find_by_name(&self, name_query: &str) -> Vec<&NppesRecord> {
-        let query_lower = name_query.to_lowercase();
-        
         self.providers.iter()
-            .filter(|p| {
-                let display_name = p.display_name().to_lowercase();
-                display_name.contains(&query_lower)
-            })
+            .filter(|p| p.all_names().iter().any(|name| SubstringMatcher.matches(name_query, name)))
             .collect()
     }
     
+    /// Find providers by name, tolerating typos and word order, best matches first
+    /// 
+    /// Each provider is scored by the best [`token_set_ratio`] (0.0-1.0) between
+    /// the query and any of its names, so "Smith, John" finds JOHN SMITH. At most
+    /// `limit` providers are returned, by descending score; ties keep dataset
+    /// order. Every provider is scored, so this is a full scan.
+    pub fn search_by_name_fuzzy(&self, query: &str, limit: usize) -> Vec<(&NppesRecord, f64)> {
+        let score = |provider: &'a NppesRecord| {
+            let best = provider.all_names().iter()
+                .map(|name| token_set_ratio(query, name))
+                .fold(0.0, f64::max);
+            (best > 0.0).then_some((provider, best))
+        };
+        
+        #[cfg(feature = "parallel")]
+        let mut scored: Vec<(&NppesRecord, f64)> = {
+            use rayon::prelude::*;
+            self.providers.par_iter().filter_map(score).collect()
+        };
+        #[cfg(not(feature = "parallel"))]
+        let mut scored: Vec<(&NppesRecord, f64)> = self.providers.iter().filter_map(score).collect();
+        
+        scored.sort_by(|a, b| b.1.total_cmp(&a.1));
+        scored.truncate(limit);
+        scored
+    }
+    
     /// Find providers by state
     pub fn find_by_state(&self, state: &str) -> Vec<&NppesRecord> {
         let state_enum = StateCode::from_code(state);
@@ -147,41 +170,174 @@ impl<'a> NppesAnalytics<'a> {
     
     /// Get provider count by state
     pub fn provider_count_by_state(&self) -> HashMap<String, usize> {
-        let mut counts = HashMap::new();
-        
-        for provider in self.providers {
-            if let Some(state) = &provider.mailing_address.state {
-                *counts.entry(state.as_code().to_string()).or_insert(0) += 1;
-            }
-        }
-        
-        counts
+        self.count_by_keys(|provider| {
+            provider.mailing_address.state.as_ref().map(|s| s.as_code().to_string())
+        })
     }
     
     /// Get provider count by taxonomy code
     pub fn provider_count_by_taxonomy(&self) -> HashMap<String, usize> {
-        let mut counts = HashMap::new();
-        
-        for provider in self.providers {
-            for taxonomy in &provider.taxonomy_codes {
-                *counts.entry(taxonomy.code.clone()).or_insert(0) += 1;
+        self.count_by_keys(|provider| {
+            provider.taxonomy_codes.iter().map(|t| t.code.clone())
+        })
+    }
+    
+    /// Get provider count by entity type
+    pub fn provider_count_by_entity_type(&self) -> HashMap<EntityType, usize> {
+        self.count_by_keys(|provider| provider.entity_type.clone())
+    }
+    
+    /// Get the number of distinct providers with an endpoint of each type (requires endpoint data)
+    /// 
+    /// Types are keyed in upper case as NPPES codes them (`"DIRECT"`, `"FHIR"`,
+    /// ...), and a provider with several endpoints of one type is counted once
+    /// for it. Endpoints without a type are skipped.
+    pub fn endpoint_type_distribution(&self) -> HashMap<String, usize> {
+        let mut providers_by_type: HashMap<String, HashSet<&Npi>> = HashMap::new();
+        for endpoint in self.endpoints.unwrap_or_default() {
+            if let Some(endpoint_type) = endpoint.endpoint_type.as_deref().map(str::trim).filter(|t| !t.is_empty()) {
+                providers_by_type.entry(endpoint_type.to_ascii_uppercase()).or_default().insert(&endpoint.npi);
+            }
+        }
+        providers_by_type.into_iter()
+            .map(|(endpoint_type, npis)| (endpoint_type, npis.len()))
+            .collect()
+    }
+    
+    /// Get individual provider counts by taxonomy code as `(male, female, undisclosed)`
+    pub fn gender_by_taxonomy(&self) -> HashMap<String, (usize, usize, usize)> {
+        let mut counts: HashMap<String, (usize, usize, usize)> = HashMap::new();
+        for provider in self.providers.iter().filter(|p| p.entity_type == Some(EntityType::Individual)) {
+            let codes: HashSet<&str> = provider.taxonomy_codes.iter().map(|t| t.code.as_str()).collect();
+            for code in codes {
+                let entry = counts.entry(code.to_string()).or_default();
+                match provider.provider_gender {
+                    Some(SexCode::Male) => entry.0 += 1,
+                    Some(SexCode::Female) => entry.1 += 1,
+                    Some(SexCode::Undisclosed | SexCode::Unspecified) | None => entry.2 += 1,
+                }
             }
         }
-        
         counts
     }
     
-    /// Get provider count by entity type
-    pub fn provider_count_by_entity_type(&self) -> HashMap<EntityType, usize> {
-        let mut counts = HashMap::new();
+    /// Get provider counts by practice location city, keyed by `(state, city)`
+    /// 
+    /// City names are normalized with [`normalize_city`] so casing and common
+    /// abbreviations don't split buckets, and the state disambiguates cities like
+    /// Springfield. Buckets with fewer than `min_count` providers are dropped and
+    /// the rest are sorted by count, descending.
+    pub fn provider_count_by_city(&self, min_count: usize) -> Vec<(String, String, usize)> {
+        let counts = self.count_by_keys(|provider| {
+            let address = &provider.practice_address;
+            address.city.as_deref()
+                .map(normalize_city)
+                .filter(|city| !city.is_empty())
+                .map(|city| {
+                    let state = address.state.as_ref().map(|s| s.as_code().to_string()).unwrap_or_default();
+                    (state, city)
+                })
+        });
         
-        for provider in self.providers {
-            if let Some(ref entity_type) = provider.entity_type {
-                *counts.entry(entity_type.clone()).or_insert(0) += 1;
+        let mut cities: Vec<_> = counts.into_iter()
+            .filter(|(_, count)| *count >= min_count)
+            .map(|((state, city), count)| (state, city, count))
+            .collect();
+        cities.sort_by(|a, b| b.2.cmp(&a.2).then_with(|| (&a.0, &a.1).cmp(&(&b.0, &b.1))));
+        cities
+    }
+    
+    /// Count providers under every key yielded for them
+    /// 
+    /// With the `parallel` feature, each rayon worker folds into its own map
+    /// and the per-thread maps are merged at the end.
+    fn count_by_keys<K, I, F>(&self, keys: F) -> HashMap<K, usize>
+    where
+        K: Eq + std::hash::Hash + Send,
+        I: IntoIterator<Item = K>,
+        F: Fn(&'a NppesRecord) -> I + Sync + Send,
+    {
+        #[cfg(feature = "parallel")]
+        {
+            use rayon::prelude::*;
+            self.providers.par_iter()
+                .fold(HashMap::new, |mut counts, provider| {
+                    for key in keys(provider) {
+                        *counts.entry(key).or_insert(0) += 1;
+                    }
+                    counts
+                })
+                .reduce(HashMap::new, |mut merged, counts| {
+                    for (key, count) in counts {
+                        *merged.entry(key).or_insert(0) += count;
+                    }
+                    merged
+                })
+        }
+        
+        #[cfg(not(feature = "parallel"))]
+        {
+            let mut counts = HashMap::new();
+            
+            for provider in self.providers {
+                for key in keys(provider) {
+                    *counts.entry(key).or_insert(0) += 1;
+                }
             }
+            
+            counts
         }
+    }
+    
+    /// Estimate quantiles of a numeric value derived from each provider
+    /// 
+    /// Providers for which `extractor` returns `None` (or NaN) are skipped. `qs`
+    /// are fractions in `0.0..=1.0`; each result is NaN when no values were
+    /// extracted. Memory stays bounded regardless of dataset size (see
+    /// [`Quantiles`]).
+    /// 
+    /// # Example
+    /// ```no_run
+    /// # use nppes::prelude::*;
+    /// # let dataset = NppesDataset::load_standard("./data")?;
+    /// let taxonomy_counts = dataset.analytics().quantiles(
+    ///     |p| Some(p.taxonomy_codes.len() as f64),
+    ///     &[0.5, 0.9, 0.99],
+    /// );
+    /// # Ok::<(), nppes::NppesError>(())
+    /// ```
+    pub fn quantiles<F>(&self, extractor: F, qs: &[f64]) -> Vec<f64>
+    where
+        F: Fn(&NppesRecord) -> Option<f64> + Sync + Send,
+    {
+        #[cfg(feature = "parallel")]
+        let mut accumulator = {
+            use rayon::prelude::*;
+            self.providers.par_iter()
+                .fold(Quantiles::new, |mut acc, provider| {
+                    if let Some(value) = extractor(provider) {
+                        acc.add(value);
+                    }
+                    acc
+                })
+                .reduce(Quantiles::new, |mut merged, acc| {
+                    merged.merge(acc);
+                    merged
+                })
+        };
         
-        counts
+        #[cfg(not(feature = "parallel"))]
+        let mut accumulator = {
+            let mut acc = Quantiles::new();
+            for provider in self.providers {
+                if let Some(value) = extractor(provider) {
+                    acc.add(value);
+                }
+            }
+            acc
+        };
+        
+        qs.iter().map(|&q| accumulator.quantile(q)).collect()
     }
     
     /// Get top N states by provider count
@@ -226,6 +382,118 @@ impl<'a> NppesAnalytics<'a> {
             .collect()
     }
     
+    /// Count new enrollments by `(year, month)` of enumeration date, in chronological order
+    ///
+    /// Only months with at least one enrollment appear. Providers without an
+    /// enumeration date are skipped.
+    pub fn enrollments_by_month(&self) -> BTreeMap<(i32, u32), usize> {
+        let mut counts = BTreeMap::new();
+        for date in self.providers.iter().filter_map(|p| p.enumeration_date) {
+            *counts.entry((date.year(), date.month())).or_insert(0) += 1;
+        }
+        counts
+    }
+    
+    /// Count new enrollments by year of enumeration date, in chronological order
+    ///
+    /// See [`enrollments_by_month`](Self::enrollments_by_month).
+    pub fn enrollments_by_year(&self) -> BTreeMap<i32, usize> {
+        let mut counts = BTreeMap::new();
+        for date in self.providers.iter().filter_map(|p| p.enumeration_date) {
+            *counts.entry(date.year()).or_insert(0) += 1;
+        }
+        counts
+    }
+    
+    /// Monthly counts of providers added and deactivated in a year
+    ///
+    /// Keys are months `1..=12`; every month is present even when it had no
+    /// activity. Values are `(added, deactivated, net)` based on
+    /// `enumeration_date` and `deactivation_date`.
+    pub fn monthly_net_flow(&self, year: i32) -> BTreeMap<u32, (usize, usize, i64)> {
+        let mut flow: BTreeMap<u32, (usize, usize, i64)> = (1..=12).map(|month| (month, (0, 0, 0))).collect();
+        
+        for provider in self.providers {
+            let month = provider.enumeration_date.filter(|d| d.year() == year).map(|d| d.month());
+            if let Some(entry) = month.and_then(|m| flow.get_mut(&m)) {
+                entry.0 += 1;
+                entry.2 += 1;
+            }
+            let month = provider.deactivation_date.filter(|d| d.year() == year).map(|d| d.month());
+            if let Some(entry) = month.and_then(|m| flow.get_mut(&m)) {
+                entry.1 += 1;
+                entry.2 -= 1;
+            }
+        }
+        
+        flow
+    }
+    
+    /// Count providers by whole years enrolled as of a date
+    ///
+    /// Keys are completed years since `enumeration_date`, so a provider enumerated
+    /// 18 months before `as_of` counts under `1`. Providers without an enumeration
+    /// date, or enumerated after `as_of`, are left out.
+    pub fn tenure_distribution(&self, as_of: NaiveDate) -> HashMap<u32, usize> {
+        let mut distribution = HashMap::new();
+        for years in self.providers.iter().filter_map(|p| as_of.years_since(p.enumeration_date?)) {
+            *distribution.entry(years).or_insert(0) += 1;
+        }
+        distribution
+    }
+    
+    /// Average time enrolled as of a date, in fractional years
+    ///
+    /// Covers the same providers as [`tenure_distribution`](Self::tenure_distribution),
+    /// counting days and dividing by 365.25. `None` if no provider qualifies.
+    pub fn mean_tenure_years(&self, as_of: NaiveDate) -> Option<f64> {
+        let (count, total_days) = self.providers.iter()
+            .filter_map(|p| p.enumeration_date)
+            .map(|enumerated| (as_of - enumerated).num_days())
+            .filter(|&days| days >= 0)
+            .fold((0usize, 0i64), |(count, total), days| (count + 1, total + days));
+        (count > 0).then(|| total_days as f64 / count as f64 / 365.25)
+    }
+    
+    /// Flag providers with dates that can't be right
+    /// 
+    /// Reports enumeration dates before the NPI program began issuing numbers
+    /// ([`NPI_PROGRAM_START`]), last update dates before the enumeration date, and
+    /// any date after today. A provider appears once per issue found.
+    pub fn implausible_dates(&self) -> Vec<(&NppesRecord, DateIssue)> {
+        self.implausible_dates_as_of(chrono::Utc::now().date_naive())
+    }
+    
+    fn implausible_dates_as_of(&self, today: NaiveDate) -> Vec<(&NppesRecord, DateIssue)> {
+        let mut issues = Vec::new();
+        
+        for provider in self.providers {
+            if let Some(enumerated) = provider.enumeration_date {
+                if enumerated < NPI_PROGRAM_START {
+                    issues.push((provider, DateIssue::EnumeratedBeforeProgramStart(enumerated)));
+                }
+                if let Some(updated) = provider.last_update_date.filter(|&updated| updated < enumerated) {
+                    issues.push((provider, DateIssue::UpdatedBeforeEnumeration { enumerated, updated }));
+                }
+            }
+            
+            let dates = [
+                ("enumeration_date", provider.enumeration_date),
+                ("last_update_date", provider.last_update_date),
+                ("deactivation_date", provider.deactivation_date),
+                ("reactivation_date", provider.reactivation_date),
+                ("certification_date", provider.certification_date),
+            ];
+            for (field, date) in dates {
+                if let Some(date) = date.filter(|&date| date > today) {
+                    issues.push((provider, DateIssue::FutureDate { field, date }));
+                }
+            }
+        }
+        
+        issues
+    }
+    
     /// Get active providers only
     pub fn active_providers(&self) -> Vec<&NppesRecord> {
         self.providers.iter()
@@ -247,6 +515,13 @@ impl<'a> NppesAnalytics<'a> {
             .collect()
     }
     
+    /// Find providers with no primary taxonomy or more than one
+    pub fn providers_with_invalid_primary_taxonomy(&self) -> Vec<&NppesRecord> {
+        self.providers.iter()
+            .filter(|p| p.primary_taxonomy_count() != 1)
+            .collect()
+    }
+    
     /// Get taxonomy description for a code (requires taxonomy reference data)
     pub fn get_taxonomy_description(&self, taxonomy_code: &str) -> Option<&TaxonomyReference> {
         self.taxonomy_ref?
@@ -255,6 +530,10 @@ impl<'a> NppesAnalytics<'a> {
     }
     
     /// Enrich providers with taxonomy descriptions
+    /// 
+    /// Clones every provider record, which for the full NPPES file costs as
+    /// much memory as the dataset itself. Prefer [`enrich_iter`](Self::enrich_iter)
+    /// unless owned values are needed.
     pub fn enrich_with_taxonomy_descriptions(&self) -> Result<Vec<EnrichedProvider>> {
         if self.taxonomy_ref.is_none() {
             return Err(NppesError::DataValidation {
@@ -265,37 +544,28 @@ impl<'a> NppesAnalytics<'a> {
             });
         }
         
-        let taxonomy_map: HashMap<&str, &TaxonomyReference> = self.taxonomy_ref.unwrap()
+        Ok(self.enrich_iter().map(EnrichedProviderRef::into_owned).collect())
+    }
+    
+    /// Pair each provider with its taxonomy reference entries, without cloning
+    /// 
+    /// Lazily yields providers in order. Without taxonomy reference data every
+    /// taxonomy is yielded with no reference.
+    pub fn enrich_iter(&self) -> impl Iterator<Item = EnrichedProviderRef<'a>> + 'a {
+        let taxonomy_map: HashMap<&str, &TaxonomyReference> = self.taxonomy_ref.unwrap_or_default()
             .iter()
             .map(|t| (t.code.as_str(), t))
             .collect();
         
-        let mut enriched_providers = Vec::new();
-        
-        for provider in self.providers {
-            let enriched_taxonomies: Vec<_> = provider.taxonomy_codes.iter()
-                .map(|tc| {
-                    let taxonomy_ref = taxonomy_map.get(tc.code.as_str());
-                    EnrichedTaxonomyCode {
-                        code: tc.code.clone(),
-                        license_number: tc.license_number.clone(),
-                        license_state: tc.license_state.clone(),
-                        is_primary: tc.is_primary,
-                        taxonomy_group: tc.taxonomy_group.clone(),
-                        display_name: taxonomy_ref.and_then(|t| t.display_name.clone()),
-                        classification: taxonomy_ref.and_then(|t| t.classification.clone()),
-                        specialization: taxonomy_ref.and_then(|t| t.specialization.clone()),
-                    }
+        self.providers.iter().map(move |provider| EnrichedProviderRef {
+            provider,
+            enriched_taxonomies: provider.taxonomy_codes.iter()
+                .map(|taxonomy| EnrichedTaxonomyRef {
+                    taxonomy,
+                    reference: taxonomy_map.get(taxonomy.code.as_str()).copied(),
                 })
-                .collect();
-            
-            enriched_providers.push(EnrichedProvider {
-                provider: provider.clone(),
-                enriched_taxonomies,
-            });
-        }
-        
-        Ok(enriched_providers)
+                .collect(),
+        })
     }
     
     /// Create a provider lookup index by NPI for fast access
@@ -319,6 +589,133 @@ impl<'a> NppesAnalytics<'a> {
         
         index
     }
+    
+    /// Group providers that look like duplicates of each other
+    ///
+    /// Providers are compared only within the same entity type and practice
+    /// state (mailing state when the practice address is blank), so the cost is
+    /// quadratic in the size of the largest such group. Similarity is the mean
+    /// of the token Jaccard scores of the normalized display names and of the
+    /// addresses (first line, city and ZIP5). Pairs scoring at least
+    /// `threshold` (0.0-1.0) are linked, and linked providers form clusters.
+    /// Only clusters of two or more are returned, each in dataset order.
+    pub fn find_potential_duplicates(&self, threshold: f64) -> Vec<Vec<&NppesRecord>> {
+        let mut groups: HashMap<(EntityType, &str), Vec<usize>> = HashMap::new();
+        for (idx, provider) in self.providers.iter().enumerate() {
+            let state = duplicate_address(provider).state.as_ref().map(StateCode::as_code);
+            if let (Some(entity_type), Some(state)) = (provider.entity_type.clone(), state) {
+                groups.entry((entity_type, state)).or_default().push(idx);
+            }
+        }
+        let keys: Vec<DuplicateKey> = self.providers.iter().map(DuplicateKey::new).collect();
+        
+        let linked_pairs = |group: &Vec<usize>| -> Vec<(usize, usize)> {
+            let mut pairs = Vec::new();
+            for (n, &a) in group.iter().enumerate() {
+                for &b in &group[n + 1..] {
+                    if keys[a].similarity(&keys[b]) >= threshold {
+                        pairs.push((a, b));
+                    }
+                }
+            }
+            pairs
+        };
+        
+        #[cfg(feature = "parallel")]
+        let pairs: Vec<(usize, usize)> = {
+            use rayon::prelude::*;
+            groups.par_iter().flat_map_iter(|(_, group)| linked_pairs(group)).collect()
+        };
+        #[cfg(not(feature = "parallel"))]
+        let pairs: Vec<(usize, usize)> = groups.values().flat_map(linked_pairs).collect();
+        
+        // Union-find over provider positions
+        let mut parent: Vec<usize> = (0..self.providers.len()).collect();
+        fn root(parent: &mut [usize], mut idx: usize) -> usize {
+            while parent[idx] != idx {
+                parent[idx] = parent[parent[idx]];
+                idx = parent[idx];
+            }
+            idx
+        }
+        for (a, b) in pairs {
+            let (ra, rb) = (root(&mut parent, a), root(&mut parent, b));
+            if ra != rb {
+                parent[ra.max(rb)] = ra.min(rb);
+            }
+        }
+        
+        let mut clusters: BTreeMap<usize, Vec<&NppesRecord>> = BTreeMap::new();
+        for idx in 0..self.providers.len() {
+            let r = root(&mut parent, idx);
+            clusters.entry(r).or_default().push(&self.providers[idx]);
+        }
+        clusters.into_values().filter(|cluster| cluster.len() >= 2).collect()
+    }
+}
+
+/// Address used for duplicate detection: practice, or mailing when practice is blank
+fn duplicate_address(provider: &NppesRecord) -> &Address {
+    if provider.practice_address.is_empty() {
+        &provider.mailing_address
+    } else {
+        &provider.practice_address
+    }
+}
+
+/// Normalized name and address tokens compared by [`NppesAnalytics::find_potential_duplicates`]
+struct DuplicateKey {
+    name: HashSet<String>,
+    address: HashSet<String>,
+}
+
+impl DuplicateKey {
+    fn new(provider: &NppesRecord) -> Self {
+        let tokens = |text: &str| -> HashSet<String> {
+            normalize_address_text(text).split(' ').filter(|t| !t.is_empty()).map(str::to_string).collect()
+        };
+        let address = duplicate_address(provider);
+        let mut address_tokens = tokens(&[address.line_1.as_deref(), address.city.as_deref()]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>()
+            .join(" "));
+        address_tokens.extend(address.zip5());
+        Self {
+            name: tokens(&provider.display_name()),
+            address: address_tokens,
+        }
+    }
+    
+    fn similarity(&self, other: &DuplicateKey) -> f64 {
+        (jaccard(&self.name, &other.name) + jaccard(&self.address, &other.address)) / 2.0
+    }
+}
+
+/// Shared tokens over all tokens; 0.0 when both sets are empty
+fn jaccard(a: &HashSet<String>, b: &HashSet<String>) -> f64 {
+    let union = a.union(b).count();
+    if union == 0 {
+        return 0.0;
+    }
+    a.intersection(b).count() as f64 / union as f64
+}
+
+/// First day NPIs were issued (NPPES opened on May 23, 2005)
+pub const NPI_PROGRAM_START: NaiveDate = match NaiveDate::from_ymd_opt(2005, 5, 23) {
+    Some(date) => date,
+    None => panic!("invalid NPI program start date"),
+};
+
+/// A date problem found by [`NppesAnalytics::implausible_dates`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DateIssue {
+    /// Enumerated before the NPI program started
+    EnumeratedBeforeProgramStart(NaiveDate),
+    /// Last updated before being enumerated
+    UpdatedBeforeEnumeration { enumerated: NaiveDate, updated: NaiveDate },
+    /// A date later than today
+    FutureDate { field: &'static str, date: NaiveDate },
 }
 
 /// Statistics about the NPPES dataset
@@ -354,6 +751,156 @@ impl DatasetStats {
     }
 }
 
+/// Streaming quantile estimator with bounded memory
+/// 
+/// A merging t-digest: values are buffered, then folded into at most a few
+/// hundred weighted centroids that stay small near the tails, so extreme
+/// percentiles remain accurate. Small inputs that never needed merging give
+/// exact results with linear interpolation between order statistics.
+#[derive(Debug, Clone)]
+pub struct Quantiles {
+    /// `(mean, weight)` pairs sorted by mean
+    centroids: Vec<(f64, f64)>,
+    buffer: Vec<f64>,
+    compression: f64,
+    count: usize,
+    min: f64,
+    max: f64,
+}
+
+impl Default for Quantiles {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Quantiles {
+    /// Number of values buffered before they are merged into centroids
+    const BUFFER_SIZE: usize = 4096;
+    
+    /// Create an estimator with the default compression of 200
+    pub fn new() -> Self {
+        Self::with_compression(200.0)
+    }
+    
+    /// Create an estimator with a custom compression; higher values keep more
+    /// centroids and give more accurate estimates
+    pub fn with_compression(compression: f64) -> Self {
+        Self {
+            centroids: Vec::new(),
+            buffer: Vec::new(),
+            compression: compression.max(10.0),
+            count: 0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+        }
+    }
+    
+    /// Add a value; NaN is ignored
+    pub fn add(&mut self, value: f64) {
+        if value.is_nan() {
+            return;
+        }
+        self.count += 1;
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+        self.buffer.push(value);
+        if self.buffer.len() >= Self::BUFFER_SIZE {
+            self.compress();
+        }
+    }
+    
+    /// Combine another estimator's values into this one
+    pub fn merge(&mut self, other: Quantiles) {
+        self.count += other.count;
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+        self.buffer.extend(other.buffer);
+        self.centroids.extend(other.centroids);
+        self.compress();
+    }
+    
+    /// Number of values added
+    pub fn count(&self) -> usize {
+        self.count
+    }
+    
+    /// Estimate the value at fraction `q` (clamped to `0.0..=1.0`); NaN when empty
+    pub fn quantile(&mut self, q: f64) -> f64 {
+        self.compress();
+        if self.centroids.is_empty() {
+            return f64::NAN;
+        }
+        let q = q.clamp(0.0, 1.0);
+        
+        // Exact: every centroid is a single value
+        if self.centroids.iter().all(|&(_, weight)| weight == 1.0) {
+            let rank = q * (self.centroids.len() - 1) as f64;
+            let lower = rank.floor() as usize;
+            let upper = rank.ceil() as usize;
+            let (low, high) = (self.centroids[lower].0, self.centroids[upper].0);
+            return low + (high - low) * (rank - lower as f64);
+        }
+        
+        // Interpolate between centroid centers, anchored at the observed min/max
+        let total = self.count as f64;
+        let target = q * total;
+        let (first_mean, first_weight) = self.centroids[0];
+        if target <= first_weight / 2.0 {
+            return self.min + (first_mean - self.min) * target / (first_weight / 2.0);
+        }
+        let mut cumulative = 0.0;
+        for pair in self.centroids.windows(2) {
+            let (left_mean, left_weight) = pair[0];
+            let (right_mean, right_weight) = pair[1];
+            let left_center = cumulative + left_weight / 2.0;
+            let right_center = cumulative + left_weight + right_weight / 2.0;
+            if target <= right_center {
+                let fraction = (target - left_center) / (right_center - left_center);
+                return left_mean + (right_mean - left_mean) * fraction;
+            }
+            cumulative += left_weight;
+        }
+        let (last_mean, last_weight) = self.centroids[self.centroids.len() - 1];
+        let remaining = (total - target) / (last_weight / 2.0);
+        self.max - (self.max - last_mean) * remaining.min(1.0)
+    }
+    
+    /// Fold buffered values into the centroids, merging neighbours that fit
+    /// within one unit of the t-digest scale function
+    fn compress(&mut self) {
+        if self.buffer.is_empty() && self.centroids.len() <= 1 {
+            return;
+        }
+        let mut points = std::mem::take(&mut self.centroids);
+        points.extend(self.buffer.drain(..).map(|value| (value, 1.0)));
+        points.sort_by(|a, b| a.0.total_cmp(&b.0));
+        
+        let total: f64 = points.iter().map(|&(_, weight)| weight).sum();
+        let scale = |q: f64| self.compression / (2.0 * std::f64::consts::PI) * (2.0 * q - 1.0).asin();
+        
+        let mut merged: Vec<(f64, f64)> = Vec::with_capacity(points.len().min(self.compression as usize * 2));
+        let mut cumulative = 0.0;
+        let mut k_start = scale(0.0);
+        for (mean, weight) in points {
+            if let Some(last) = merged.last_mut() {
+                let k_end = scale(((cumulative + weight) / total).min(1.0));
+                if k_end - k_start <= 1.0 {
+                    let combined = last.1 + weight;
+                    last.0 += (mean - last.0) * weight / combined;
+                    last.1 = combined;
+                    cumulative += weight;
+                    continue;
+                }
+                k_start = scale(cumulative / total);
+            }
+            merged.push((mean, weight));
+            cumulative += weight;
+        }
+        self.centroids = merged;
+    }
+}
+
 /// Provider record enriched with taxonomy descriptions
 #[derive(Debug, Clone)]
 pub struct EnrichedProvider {
@@ -374,6 +921,61 @@ pub struct EnrichedTaxonomyCode {
     pub specialization: Option<String>,
 }
 
+/// Provider borrowed from the dataset with its taxonomy reference entries
+#[derive(Debug, Clone)]
+pub struct EnrichedProviderRef<'a> {
+    pub provider: &'a NppesRecord,
+    pub enriched_taxonomies: Vec<EnrichedTaxonomyRef<'a>>,
+}
+
+impl EnrichedProviderRef<'_> {
+    /// Copy into an owned [`EnrichedProvider`]
+    pub fn into_owned(self) -> EnrichedProvider {
+        EnrichedProvider {
+            provider: self.provider.clone(),
+            enriched_taxonomies: self.enriched_taxonomies.iter().map(EnrichedTaxonomyRef::to_enriched).collect(),
+        }
+    }
+}
+
+/// Taxonomy code borrowed from a provider, with its reference entry if known
+#[derive(Debug, Clone, Copy)]
+pub struct EnrichedTaxonomyRef<'a> {
+    pub taxonomy: &'a TaxonomyCode,
+    pub reference: Option<&'a TaxonomyReference>,
+}
+
+impl EnrichedTaxonomyRef<'_> {
+    /// Get the display name from the taxonomy reference
+    pub fn display_name(&self) -> Option<&str> {
+        self.reference?.display_name.as_deref()
+    }
+    
+    /// Get the classification from the taxonomy reference
+    pub fn classification(&self) -> Option<&str> {
+        self.reference?.classification.as_deref()
+    }
+    
+    /// Get the specialization from the taxonomy reference
+    pub fn specialization(&self) -> Option<&str> {
+        self.reference?.specialization.as_deref()
+    }
+    
+    /// Copy into an owned [`EnrichedTaxonomyCode`]
+    pub fn to_enriched(&self) -> EnrichedTaxonomyCode {
+        EnrichedTaxonomyCode {
+            code: self.taxonomy.code.clone(),
+            license_number: self.taxonomy.license_number.clone(),
+            license_state: self.taxonomy.license_state.clone(),
+            is_primary: self.taxonomy.is_primary,
+            taxonomy_group: self.taxonomy.taxonomy_group.clone(),
+            display_name: self.display_name().map(str::to_string),
+            classification: self.classification().map(str::to_string),
+            specialization: self.specialization().map(str::to_string),
+        }
+    }
+}
+
 /// Query builder for complex provider searches
 pub struct ProviderQuery<'a> {
     analytics: &'a NppesAnalytics<'a>,
@@ -453,4 +1055,293 @@ impl<'a> ProviderQuery<'a> {
     pub fn count(self) -> usize {
         self.execute().len()
     }
-} 
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_quantiles_exact_for_small_inputs() {
+        let mut quantiles = Quantiles::new();
+        for value in [5.0, 1.0, 3.0, 2.0, 4.0] {
+            quantiles.add(value);
+        }
+        assert_eq!(quantiles.quantile(0.0), 1.0);
+        assert_eq!(quantiles.quantile(0.5), 3.0);
+        assert_eq!(quantiles.quantile(0.875), 4.5);
+        assert_eq!(quantiles.quantile(1.0), 5.0);
+        assert!(Quantiles::new().quantile(0.5).is_nan());
+    }
+    
+    #[test]
+    fn test_quantiles_bounded_estimate_for_large_inputs() {
+        let mut quantiles = Quantiles::new();
+        for i in 0..200_000 {
+            quantiles.add((i % 10_000) as f64);
+        }
+        assert!(quantiles.centroids.len() < 1_000);
+        
+        for (q, expected) in [(0.01, 100.0), (0.5, 5_000.0), (0.99, 9_900.0)] {
+            let estimate = quantiles.quantile(q);
+            assert!((estimate - expected).abs() < 50.0, "q={} estimate={}", q, estimate);
+        }
+    }
+    
+    #[test]
+    fn test_implausible_dates() {
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        let providers = vec![
+            NppesRecord {
+                enumeration_date: Some(date(2010, 1, 1)),
+                last_update_date: Some(date(2020, 1, 1)),
+                ..Default::default()
+            },
+            NppesRecord {
+                enumeration_date: Some(date(1999, 1, 1)),
+                ..Default::default()
+            },
+            NppesRecord {
+                enumeration_date: Some(date(2010, 1, 1)),
+                last_update_date: Some(date(2009, 1, 1)),
+                deactivation_date: Some(date(2031, 1, 1)),
+                ..Default::default()
+            },
+        ];
+        
+        let issues: Vec<DateIssue> = NppesAnalytics::new(&providers)
+            .implausible_dates_as_of(date(2025, 6, 1))
+            .into_iter()
+            .map(|(_, issue)| issue)
+            .collect();
+        assert_eq!(issues, vec![
+            DateIssue::EnumeratedBeforeProgramStart(date(1999, 1, 1)),
+            DateIssue::UpdatedBeforeEnumeration { enumerated: date(2010, 1, 1), updated: date(2009, 1, 1) },
+            DateIssue::FutureDate { field: "deactivation_date", date: date(2031, 1, 1) },
+        ]);
+    }
+    
+    #[test]
+    fn test_gender_by_taxonomy() {
+        let individual = |gender: Option<SexCode>, codes: &[&str]| NppesRecord {
+            entity_type: Some(EntityType::Individual),
+            provider_gender: gender,
+            taxonomy_codes: codes.iter()
+                .map(|code| TaxonomyCode { code: code.to_string(), ..Default::default() })
+                .collect(),
+            ..Default::default()
+        };
+        let providers = vec![
+            // One entry per license state
+            individual(Some(SexCode::Male), &["207Q00000X", "207Q00000X"]),
+            individual(Some(SexCode::Female), &["207Q00000X", "208D00000X"]),
+            individual(Some(SexCode::Female), &["208D00000X"]),
+            individual(Some(SexCode::Undisclosed), &["208D00000X"]),
+            individual(Some(SexCode::Unspecified), &["208D00000X"]),
+            individual(None, &["207Q00000X"]),
+            NppesRecord {
+                entity_type: Some(EntityType::Organization),
+                ..individual(None, &["207Q00000X"])
+            },
+        ];
+        
+        let counts = NppesAnalytics::new(&providers).gender_by_taxonomy();
+        assert_eq!(counts.len(), 2);
+        assert_eq!(counts["207Q00000X"], (1, 1, 1));
+        assert_eq!(counts["208D00000X"], (0, 2, 2));
+    }
+    
+    #[test]
+    fn test_enrich_iter_borrows_providers() {
+        let taxonomy = |code: &str| TaxonomyCode { code: code.to_string(), ..Default::default() };
+        let providers = vec![
+            NppesRecord { taxonomy_codes: vec![taxonomy("207Q00000X"), taxonomy("999Z00000X")], ..Default::default() },
+            NppesRecord { taxonomy_codes: vec![taxonomy("208D00000X")], ..Default::default() },
+        ];
+        let references = vec![
+            TaxonomyReference {
+                code: "207Q00000X".to_string(),
+                classification: Some("Family Medicine".to_string()),
+                display_name: Some("Family Medicine Physician".to_string()),
+                ..Default::default()
+            },
+            TaxonomyReference {
+                code: "208D00000X".to_string(),
+                classification: Some("General Practice".to_string()),
+                ..Default::default()
+            },
+        ];
+        let analytics = NppesAnalytics::new(&providers).with_taxonomy_reference(&references);
+        
+        let borrowed: Vec<EnrichedProviderRef> = analytics.enrich_iter().collect();
+        for (enriched, provider) in borrowed.iter().zip(&providers) {
+            assert!(std::ptr::eq(enriched.provider, provider));
+        }
+        let first = &borrowed[0].enriched_taxonomies;
+        assert!(std::ptr::eq(first[0].reference.unwrap(), &references[0]));
+        assert_eq!(first[0].display_name(), Some("Family Medicine Physician"));
+        assert_eq!(first[1].classification(), None);
+        
+        let owned = analytics.enrich_with_taxonomy_descriptions().unwrap();
+        let owned_descriptions: Vec<_> = owned.iter()
+            .flat_map(|p| &p.enriched_taxonomies)
+            .map(|t| (t.display_name.as_deref(), t.classification.as_deref(), t.specialization.as_deref()))
+            .collect();
+        let borrowed_descriptions: Vec<_> = borrowed.iter()
+            .flat_map(|p| &p.enriched_taxonomies)
+            .map(|t| (t.display_name(), t.classification(), t.specialization()))
+            .collect();
+        assert_eq!(owned_descriptions, borrowed_descriptions);
+        assert_eq!(owned_descriptions.len(), 3);
+        
+        // Without reference data nothing is described, but providers still come through
+        let bare = NppesAnalytics::new(&providers);
+        assert!(bare.enrich_iter().all(|p| p.enriched_taxonomies.iter().all(|t| t.reference.is_none())));
+        assert!(bare.enrich_with_taxonomy_descriptions().is_err());
+    }
+    
+    #[test]
+    fn test_providers_with_invalid_primary_taxonomy() {
+        let provider = |npi: &str, primaries: &[bool]| NppesRecord {
+            npi: Npi::new(npi.to_string()).unwrap(),
+            taxonomy_codes: primaries.iter()
+                .map(|&is_primary| TaxonomyCode { code: "207Q00000X".to_string(), is_primary, ..Default::default() })
+                .collect(),
+            ..Default::default()
+        };
+        let providers = vec![
+            provider("1234567893", &[]),
+            provider("1245319599", &[false, true]),
+            provider("1003000126", &[true, false, true]),
+            provider("1538144910", &[false]),
+        ];
+        
+        let counts: Vec<usize> = providers.iter().map(NppesRecord::primary_taxonomy_count).collect();
+        assert_eq!(counts, [0, 1, 2, 0]);
+        let analytics = NppesAnalytics::new(&providers);
+        let invalid: Vec<&str> = analytics.providers_with_invalid_primary_taxonomy()
+            .iter()
+            .map(|p| p.npi.as_str())
+            .collect();
+        assert_eq!(invalid, ["1234567893", "1003000126", "1538144910"]);
+    }
+    
+    #[test]
+    fn test_enrollments_across_year_boundary() {
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        let providers: Vec<NppesRecord> = [
+            Some(date(2024, 1, 3)),
+            Some(date(2023, 12, 31)),
+            Some(date(2023, 12, 1)),
+            Some(date(2024, 1, 31)),
+            None,
+            Some(date(2024, 3, 15)),
+        ].into_iter()
+            .map(|enumeration_date| NppesRecord { enumeration_date, ..Default::default() })
+            .collect();
+        let analytics = NppesAnalytics::new(&providers);
+        
+        let monthly: Vec<((i32, u32), usize)> = analytics.enrollments_by_month().into_iter().collect();
+        assert_eq!(monthly, [((2023, 12), 2), ((2024, 1), 2), ((2024, 3), 1)]);
+        let yearly: Vec<(i32, usize)> = analytics.enrollments_by_year().into_iter().collect();
+        assert_eq!(yearly, [(2023, 2), (2024, 3)]);
+    }
+    
+    #[test]
+    fn test_tenure_distribution() {
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        let enumerated = |enumeration_date| NppesRecord { enumeration_date, ..Default::default() };
+        let providers = vec![
+            enumerated(Some(date(2010, 3, 1))),
+            enumerated(Some(date(2015, 1, 1))),
+            // A day short of ten years
+            enumerated(Some(date(2015, 6, 2))),
+            enumerated(Some(date(2024, 12, 1))),
+            enumerated(None),
+            enumerated(Some(date(2026, 1, 1))),
+        ];
+        let analytics = NppesAnalytics::new(&providers);
+        let as_of = date(2025, 6, 1);
+        
+        let distribution = analytics.tenure_distribution(as_of);
+        assert_eq!(distribution, HashMap::from([(15, 1), (10, 1), (9, 1), (0, 1)]));
+        
+        let two = &providers[1..3];
+        let mean = NppesAnalytics::new(two).mean_tenure_years(as_of).unwrap();
+        assert!((mean - 10.2).abs() < 0.05, "mean tenure {}", mean);
+        assert_eq!(NppesAnalytics::new(&providers[4..]).mean_tenure_years(as_of), None);
+    }
+    
+    #[test]
+    fn test_search_by_name_fuzzy_ranks_transposed_names() {
+        let person = |npi: &str, first: &str, last: &str| NppesRecord {
+            npi: Npi::new(npi.to_string()).unwrap(),
+            entity_type: Some(EntityType::Individual),
+            provider_name: ProviderName {
+                first: Some(first.to_string()),
+                last: Some(last.to_string()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let providers = vec![
+            person("1234567893", "JANE", "DOE"),
+            person("1245319599", "JOHN", "SMITH"),
+            person("1003000126", "JOHN", "SMALLS"),
+        ];
+        let analytics = NppesAnalytics::new(&providers);
+        
+        let results = analytics.search_by_name_fuzzy("Smith, John", 10);
+        assert_eq!(results[0].0.npi.as_str(), "1245319599");
+        assert_eq!(results[0].1, 1.0);
+        assert!(results.windows(2).all(|pair| pair[0].1 >= pair[1].1));
+        
+        // A typo in a transposed name still ranks the intended provider first
+        let results = analytics.search_by_name_fuzzy("smiht john", 2);
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].0.npi.as_str(), "1245319599");
+        assert!(results[0].1 > results[1].1);
+        
+        assert!(analytics.search_by_name_fuzzy("", 10).is_empty());
+        assert!(analytics.search_by_name_fuzzy("smith", 0).is_empty());
+    }
+    
+    #[test]
+    fn test_find_potential_duplicates() {
+        let organization = |npi: &str, name: &str, line_1: &str, state: StateCode| NppesRecord {
+            npi: Npi::new(npi.to_string()).unwrap(),
+            entity_type: Some(EntityType::Organization),
+            organization_name: OrganizationName {
+                legal_business_name: Some(name.to_string()),
+                ..Default::default()
+            },
+            practice_address: Address {
+                line_1: Some(line_1.to_string()),
+                city: Some("SPRINGFIELD".to_string()),
+                state: Some(state),
+                postal_code: Some("627011234".to_string()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let providers = vec![
+            organization("1234567893", "ACME HEALTH CLINIC LLC", "123 MAIN STREET", StateCode::IL),
+            organization("1245319599", "BETA DENTAL GROUP", "9 OAK AVE", StateCode::IL),
+            organization("1003000126", "Acme Health Clinic", "123 Main St.", StateCode::IL),
+            // Same organization in another state isn't compared
+            organization("1538144910", "ACME HEALTH CLINIC", "123 MAIN ST", StateCode::MO),
+            organization("1124033048", "ACME HEALTH CLINIC", "123 MAIN STREET", StateCode::IL),
+        ];
+        
+        let analytics = NppesAnalytics::new(&providers);
+        let clusters = analytics.find_potential_duplicates(0.7);
+        let npis: Vec<Vec<&str>> = clusters.iter()
+            .map(|cluster| cluster.iter().map(|p| p.npi.as_str()).collect())
+            .collect();
+        assert_eq!(npis, vec![vec!["1234567893", "1003000126", "1124033048"]]);
+        
+        // Only the out-of-state copy is token-for-token identical
+        assert!(analytics.find_potential_duplicates(1.0).is_empty());
+        assert!(NppesAnalytics::new(&providers[..2]).find_potential_duplicates(0.7).is_empty());
+    }
+}
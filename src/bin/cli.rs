@@ -87,6 +87,7 @@ enum ExportFormatOpt {
     Json,
     Csv,
     Sql,
+    Xml,
 }
 
 fn main() {
@@ -167,6 +168,7 @@ fn cmd_export(args: ExportArgs) {
                 ExportFormatOpt::Json => ExportFormat::Json,
                 ExportFormatOpt::Csv => ExportFormat::Csv,
                 ExportFormatOpt::Sql => ExportFormat::Sql,
+                ExportFormatOpt::Xml => ExportFormat::Xml,
             };
             match dataset.export_subset(&args.output, filter, format) {
                 Ok(_) => println!("Exported to {}", args.output.display()),
@@ -48,15 +48,22 @@ pub struct NppesConfig {
 }
 
 /// Validation level for data parsing
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+/// 
+/// Levels are ordered, each checking everything the one before it does. Readers
+/// take the level from the global configuration; override it with
+/// [`NppesReader::with_validation_level`](crate::reader::NppesReader::with_validation_level)
+/// or [`NppesDatasetBuilder::validation_level`](crate::dataset::NppesDatasetBuilder::validation_level).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum ValidationLevel {
-    /// No validation, fastest parsing
+    /// No validation, fastest parsing; malformed values are dropped rather than
+    /// rejected, and records with an invalid NPI are skipped
     None,
-    /// Basic validation (NPI format, required fields)
+    /// Basic validation (NPI format and entity type code)
     Basic,
-    /// Standard validation (recommended)
+    /// Standard validation (recommended): also dates and the replacement NPI
     Standard,
-    /// Strict validation (all fields, may reject valid data)
+    /// Strict validation: also US address state codes and at most one primary
+    /// taxonomy, which may reject data NPPES itself accepts
     Strict,
 }
 
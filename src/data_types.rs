@@ -33,6 +33,78 @@ impl std::fmt::Display for Npi {
     }
 }
 
+/// EIN (Employer Identification Number) - 9 digits, conventionally written `XX-XXXXXXX`
+/// 
+/// Valid values are stored as bare digits, so `"12-3456789"` and `"123456789"`
+/// compare and hash equal. Values that aren't a valid EIN are kept as-is (trimmed)
+/// rather than dropped; check [`is_valid`](Ein::is_valid) before relying on them.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub struct Ein(String);
+
+impl Ein {
+    /// Create a new EIN, validating format
+    /// 
+    /// Accepts nine digits with or without a single hyphen.
+    pub fn new(ein: &str) -> Result<Self, crate::NppesError> {
+        normalize_ein(ein).map(Ein).ok_or_else(|| crate::NppesError::DataValidation {
+            message: format!("EIN must be 9 digits, optionally written XX-XXXXXXX, found '{}'", ein),
+            field: Some("EIN".to_string()),
+            value: Some(ein.to_string()),
+            context: Default::default(),
+        })
+    }
+    
+    /// Create an EIN from a raw file value, keeping malformed values as-is
+    pub fn from_raw(raw: &str) -> Self {
+        Ein(normalize_ein(raw).unwrap_or_else(|| raw.trim().to_string()))
+    }
+    
+    /// Whether the value is a well-formed EIN
+    pub fn is_valid(&self) -> bool {
+        self.0.len() == 9 && self.0.bytes().all(|b| b.is_ascii_digit())
+    }
+    
+    /// Get the EIN digits, or the raw value if malformed
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+    
+    /// Format as `XX-XXXXXXX`, or `None` if malformed
+    pub fn formatted(&self) -> Option<String> {
+        self.is_valid().then(|| format!("{}-{}", &self.0[..2], &self.0[2..]))
+    }
+}
+
+impl From<String> for Ein {
+    fn from(raw: String) -> Self {
+        Ein::from_raw(&raw)
+    }
+}
+
+impl From<Ein> for String {
+    fn from(ein: Ein) -> Self {
+        ein.0
+    }
+}
+
+impl std::fmt::Display for Ein {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.formatted() {
+            Some(formatted) => write!(f, "{}", formatted),
+            None => write!(f, "{}", self.0),
+        }
+    }
+}
+
+/// Strip a single hyphen from an EIN and check that nine digits remain
+fn normalize_ein(ein: &str) -> Option<String> {
+    let ein = ein.trim();
+    let digits: String = ein.chars().filter(|&c| c != '-').collect();
+    let hyphens = ein.len() - digits.len();
+    (hyphens <= 1 && digits.len() == 9 && digits.bytes().all(|b| b.is_ascii_digit())).then_some(digits)
+}
+
 /// Entity Type Code (1 = Individual, 2 = Organization)
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum EntityType {
@@ -80,11 +152,14 @@ impl OptionDisplay for Option<EntityType> {
 }
 
 /// Healthcare Provider Taxonomy Code information
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
 pub struct TaxonomyCode {
     pub code: String,
     pub license_number: Option<String>,
+    /// Raw license state as it appears in the source file
     pub license_state: Option<String>,
+    /// Canonical 2025 license state code (None when the raw value is nonstandard)
+    pub license_state_code: Option<StateCode>,
     pub is_primary: bool,
     pub taxonomy_group: Option<String>,
     /// Canonical 2025 group taxonomy code (required)
@@ -94,7 +169,7 @@ pub struct TaxonomyCode {
 }
 
 /// Other Provider Identifier information
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
 pub struct OtherIdentifier {
     pub identifier: String,
     pub type_code: Option<String>,
@@ -148,10 +223,74 @@ impl Address {
         
         parts.join(", ")
     }
+
+    /// Build a normalized `(line_1, city, state, zip5)` key for address matching
+    ///
+    /// Returns `None` when the first address line is missing, since such
+    /// addresses cannot meaningfully be matched against each other.
+    pub fn normalized_key(&self) -> Option<AddressKey> {
+        let line_1 = normalize_address_text(self.line_1.as_deref()?);
+        if line_1.is_empty() {
+            return None;
+        }
+
+        let city = self.city.as_deref().map(normalize_address_text).unwrap_or_default();
+        let state = self.state.as_ref().map(|s| s.as_code().to_string()).unwrap_or_default();
+        let zip5 = self.zip5().unwrap_or_default();
+
+        Some((line_1, city, state, zip5))
+    }
+
+    /// Get the 5-digit ZIP code for this address (see [`normalize_zip5`])
+    pub fn zip5(&self) -> Option<String> {
+        self.postal_code.as_deref().and_then(normalize_zip5)
+    }
+}
+
+/// Normalize a postal code to its 5-digit ZIP
+///
+/// Accepts ZIP5 and ZIP+4 with or without a hyphen (`"94103"`, `"941031234"`,
+/// `"94103-1234"`) and returns the first five digits. Returns `None` when the
+/// value holds fewer than five digits, e.g. for most foreign postal codes.
+pub fn normalize_zip5(postal_code: &str) -> Option<String> {
+    let digits: String = postal_code.chars()
+        .filter(|c| c.is_ascii_digit())
+        .take(5)
+        .collect();
+    (digits.len() == 5).then_some(digits)
+}
+
+/// Normalized address key: `(line_1, city, state, zip5)`
+pub type AddressKey = (String, String, String, String);
+
+/// Normalize free-text address components for comparison
+///
+/// Uppercases, replaces punctuation with spaces and collapses whitespace,
+/// so `"123 Main St."` and `"123  MAIN ST"` compare equal.
+pub fn normalize_address_text(text: &str) -> String {
+    let cleaned: String = text.chars()
+        .map(|c| if c.is_alphanumeric() { c.to_ascii_uppercase() } else { ' ' })
+        .collect();
+    cleaned.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Normalize a city name for grouping and comparison
+///
+/// Applies [`normalize_address_text`] and expands the common leading
+/// abbreviations `ST`, `FT` and `MT`, so `"St. Louis"` and `"SAINT LOUIS"`
+/// compare equal.
+pub fn normalize_city(city: &str) -> String {
+    let normalized = normalize_address_text(city);
+    match normalized.split_once(' ') {
+        Some(("ST", rest)) => format!("SAINT {}", rest),
+        Some(("FT", rest)) => format!("FORT {}", rest),
+        Some(("MT", rest)) => format!("MOUNT {}", rest),
+        _ => normalized,
+    }
 }
 
 /// Provider name information
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
 pub struct ProviderName {
     pub prefix: Option<NamePrefixCode>,
     pub first: Option<String>,
@@ -190,7 +329,7 @@ impl ProviderName {
 }
 
 /// Organization name information
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
 pub struct OrganizationName {
     pub legal_business_name: Option<String>,
     pub other_name: Option<String>,
@@ -198,8 +337,11 @@ pub struct OrganizationName {
     pub other_name_type: Option<OtherProviderNameTypeCode>,
 }
 
-/// Authorized Official information (for organizations)
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+/// Authorized Official information
+/// 
+/// Normally reported by organizations, but some individual (sole proprietor)
+/// records also populate these columns.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
 pub struct AuthorizedOfficial {
     pub prefix: Option<NamePrefixCode>,
     pub first_name: Option<String>,
@@ -212,6 +354,18 @@ pub struct AuthorizedOfficial {
 }
 
 impl AuthorizedOfficial {
+    /// Check if no authorized official field is populated
+    pub fn is_empty(&self) -> bool {
+        self.prefix.is_none()
+            && self.first_name.is_none()
+            && self.middle_name.is_none()
+            && self.last_name.is_none()
+            && self.suffix.is_none()
+            && self.credential.is_none()
+            && self.title.is_none()
+            && self.telephone.is_none()
+    }
+    
     /// Format the full name
     pub fn full_name(&self) -> String {
         let mut parts = Vec::new();
@@ -239,6 +393,26 @@ impl AuthorizedOfficial {
     }
 }
 
+/// Small owned summary of a provider, for API responses
+/// 
+/// Build one with [`NppesRecord::summary_owned`], which resolves the specialty
+/// against a dataset's taxonomy reference.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProviderSummary {
+    pub npi: Npi,
+    /// Person's first and last name, or the organization's legal business name
+    pub name: String,
+    pub entity_type: Option<EntityType>,
+    /// Display name of the primary taxonomy, or its code if not in the reference
+    pub primary_specialty: Option<String>,
+    /// Mailing address city
+    pub city: Option<String>,
+    /// Mailing address state
+    pub state: Option<StateCode>,
+    /// Whether the provider is active (see [`NppesRecord::is_active`])
+    pub active: bool,
+}
+
 /// Main NPPES Provider Record
 /// 
 /// This struct represents the main provider data from the NPPES CSV file.
@@ -249,7 +423,7 @@ pub struct NppesRecord {
     pub npi: Npi,
     pub entity_type: Option<EntityType>,
     pub replacement_npi: Option<Npi>,
-    pub ein: Option<String>,
+    pub ein: Option<Ein>,
     
     // Provider names
     pub provider_name: ProviderName,
@@ -277,7 +451,7 @@ pub struct NppesRecord {
     /// Canonical 2025 sex code (required)
     pub provider_gender: Option<SexCode>,
     
-    // Authorized official (for organizations)
+    // Authorized official (mostly organizations; `None` when the columns are blank)
     pub authorized_official: Option<AuthorizedOfficial>,
     
     // Healthcare taxonomy codes (up to 15)
@@ -292,7 +466,41 @@ pub struct NppesRecord {
     /// Canonical 2025 subpart code (required)
     pub organization_subpart: Option<SubpartCode>,
     pub parent_organization_lbn: Option<String>,
-    pub parent_organization_tin: Option<String>,
+    pub parent_organization_tin: Option<Ein>,
+}
+
+impl Default for NppesRecord {
+    /// An empty record with a blank placeholder NPI
+    /// 
+    /// Handy as a base for struct update syntax; set `npi` before using the record.
+    fn default() -> Self {
+        Self {
+            npi: Npi(String::new()),
+            entity_type: None,
+            replacement_npi: None,
+            ein: None,
+            provider_name: ProviderName::default(),
+            provider_other_name: ProviderName::default(),
+            provider_other_name_type: None,
+            organization_name: OrganizationName::default(),
+            mailing_address: Address::default(),
+            practice_address: Address::default(),
+            enumeration_date: None,
+            last_update_date: None,
+            deactivation_date: None,
+            reactivation_date: None,
+            certification_date: None,
+            deactivation_reason: None,
+            provider_gender: None,
+            authorized_official: None,
+            taxonomy_codes: Vec::new(),
+            other_identifiers: Vec::new(),
+            sole_proprietor: None,
+            organization_subpart: None,
+            parent_organization_lbn: None,
+            parent_organization_tin: None,
+        }
+    }
 }
 
 impl NppesRecord {
@@ -301,14 +509,37 @@ impl NppesRecord {
         self.taxonomy_codes.iter().find(|t| t.is_primary)
     }
     
+    /// Count the taxonomy codes marked primary (exactly one in well-formed data)
+    pub fn primary_taxonomy_count(&self) -> usize {
+        self.taxonomy_codes.iter().filter(|t| t.is_primary).count()
+    }
+    
     /// Get all taxonomy codes
     pub fn all_taxonomy_codes(&self) -> &[TaxonomyCode] {
         &self.taxonomy_codes
     }
     
-    /// Check if provider is active (not deactivated)
+    /// Check if provider is active
+    /// 
+    /// A provider is active when it was never deactivated, or was reactivated on
+    /// or after its deactivation date. A reactivation dated before the
+    /// deactivation is treated as a data error and doesn't count.
     pub fn is_active(&self) -> bool {
-        self.deactivation_date.is_none()
+        self.deactivation_date.is_none_or(|deactivated| {
+            self.reactivation_date.is_some_and(|reactivated| reactivated >= deactivated)
+        })
+    }
+    
+    /// Check if provider is an individual who answered yes to sole proprietor
+    pub fn is_sole_proprietor_individual(&self) -> bool {
+        self.entity_type == Some(EntityType::Individual)
+            && self.sole_proprietor == Some(SoleProprietorCode::Yes)
+    }
+    
+    /// Check if provider is an organization that answered yes to being a subpart
+    pub fn is_subpart_organization(&self) -> bool {
+        self.entity_type == Some(EntityType::Organization)
+            && self.organization_subpart == Some(SubpartCode::Yes)
     }
     
     /// Get provider's primary name based on entity type
@@ -344,6 +575,75 @@ impl NppesRecord {
             None => "Unknown".to_string(),
         }
     }
+
+    /// Get every non-empty name the record carries
+    ///
+    /// Includes the individual name, the individual's other name, the legal
+    /// business name and the organization's other name, deduplicated
+    /// case-insensitively. Individual names are formatted as first, middle and
+    /// last name without prefixes or credentials.
+    pub fn all_names(&self) -> Vec<String> {
+        let person = |name: &ProviderName| {
+            [&name.first, &name.middle, &name.last]
+                .into_iter()
+                .filter_map(|part| part.as_deref().map(str::trim).filter(|p| !p.is_empty()))
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+        let candidates = [
+            person(&self.provider_name),
+            person(&self.provider_other_name),
+            self.organization_name.legal_business_name.clone().unwrap_or_default(),
+            self.organization_name.other_name.clone().unwrap_or_default(),
+        ];
+
+        let mut names: Vec<String> = Vec::new();
+        for name in candidates {
+            let name = name.trim();
+            if !name.is_empty() && !names.iter().any(|existing| existing.eq_ignore_ascii_case(name)) {
+                names.push(name.to_string());
+            }
+        }
+        names
+    }
+
+    /// Get an organization's alternate names with their type codes
+    ///
+    /// Combines the main record's organization other name with entries from the
+    /// other names reference file (pass `dataset.get_other_names(&npi)` contents, or
+    /// an empty slice). Names are deduplicated case-insensitively, keeping the first
+    /// known type code.
+    pub fn organization_other_names<'a>(
+        &'a self,
+        other_names: &'a [OtherNameRecord],
+    ) -> Vec<(&'a str, Option<OtherProviderNameTypeCode>)> {
+        let main = self.organization_name.other_name.as_deref()
+            .map(|name| (name, self.organization_name.other_name_type.clone()));
+        let reference = other_names.iter()
+            .filter(|r| r.npi == self.npi)
+            .map(|r| (
+                r.provider_other_organization_name.as_str(),
+                r.provider_other_organization_name_type_code.as_deref()
+                    .and_then(OtherProviderNameTypeCode::from_code),
+            ));
+
+        let mut names: Vec<(&str, Option<OtherProviderNameTypeCode>)> = Vec::new();
+        for (name, type_code) in main.into_iter().chain(reference) {
+            let name = name.trim();
+            if name.is_empty() {
+                continue;
+            }
+            match names.iter_mut().find(|(existing, _)| existing.eq_ignore_ascii_case(name)) {
+                Some((_, existing_type)) => {
+                    if existing_type.is_none() {
+                        *existing_type = type_code;
+                    }
+                }
+                None => names.push((name, type_code)),
+            }
+        }
+        names
+    }
 }
 
 /// Other Name Reference record
@@ -390,7 +690,7 @@ pub struct EndpointRecord {
 /// Healthcare Provider Taxonomy Reference
 /// 
 /// Reference data for taxonomy codes from NUCC
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
 pub struct TaxonomyReference {
     pub code: String,
     pub grouping: Option<String>,
@@ -455,11 +755,16 @@ impl SubpartCode {
 }
 
 /// Sex Code (M, F, U, X)
+/// 
+/// Each NPPES code has its own variant, so `U` and `X` survive a round trip.
+/// Exports that write codes (CSV, XML, SQL, Parquet, Avro) use [`as_code`](Self::as_code);
+/// JSON uses the variant name, like every other enum here.
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum SexCode {
     Male,        // M
     Female,      // F
-    Undisclosed, // U or X
+    Undisclosed, // U
+    Unspecified, // X
 }
 
 impl SexCode {
@@ -467,7 +772,8 @@ impl SexCode {
         match code {
             "M" => Some(SexCode::Male),
             "F" => Some(SexCode::Female),
-            "U" | "X" => Some(SexCode::Undisclosed),
+            "U" => Some(SexCode::Undisclosed),
+            "X" => Some(SexCode::Unspecified),
             _ => None,
         }
     }
@@ -475,7 +781,17 @@ impl SexCode {
         match self {
             SexCode::Male => "M",
             SexCode::Female => "F",
-            SexCode::Undisclosed => "U", // Prefer U for output
+            SexCode::Undisclosed => "U",
+            SexCode::Unspecified => "X",
+        }
+    }
+    /// Human-readable label
+    pub fn description(&self) -> &'static str {
+        match self {
+            SexCode::Male => "Male",
+            SexCode::Female => "Female",
+            SexCode::Undisclosed => "Undisclosed",
+            SexCode::Unspecified => "Unspecified",
         }
     }
 }
@@ -663,6 +979,24 @@ impl StateCode {
             AK => "AK", AL => "AL", AR => "AR", AS => "AS", AZ => "AZ", CA => "CA", CO => "CO", CT => "CT", DC => "DC", DE => "DE", FL => "FL", FM => "FM", GA => "GA", GU => "GU", HI => "HI", IA => "IA", ID => "ID", IL => "IL", IN => "IN", KS => "KS", KY => "KY", LA => "LA", MA => "MA", MD => "MD", ME => "ME", MH => "MH", MI => "MI", MN => "MN", MO => "MO", MP => "MP", MS => "MS", MT => "MT", NC => "NC", ND => "ND", NE => "NE", NH => "NH", NJ => "NJ", NM => "NM", NV => "NV", NY => "NY", OH => "OH", OK => "OK", OR => "OR", PA => "PA", PR => "PR", PW => "PW", RI => "RI", SC => "SC", SD => "SD", TN => "TN", TX => "TX", UT => "UT", VA => "VA", VI => "VI", VT => "VT", WA => "WA", WI => "WI", WV => "WV", WY => "WY", ZZ => "ZZ",
         }
     }
+    /// Full state or territory name
+    pub fn name(&self) -> &'static str {
+        use StateCode::*;
+        match self {
+            AK => "Alaska", AL => "Alabama", AR => "Arkansas", AS => "American Samoa", AZ => "Arizona",
+            CA => "California", CO => "Colorado", CT => "Connecticut", DC => "District of Columbia", DE => "Delaware",
+            FL => "Florida", FM => "Federated States of Micronesia", GA => "Georgia", GU => "Guam", HI => "Hawaii",
+            IA => "Iowa", ID => "Idaho", IL => "Illinois", IN => "Indiana", KS => "Kansas",
+            KY => "Kentucky", LA => "Louisiana", MA => "Massachusetts", MD => "Maryland", ME => "Maine",
+            MH => "Marshall Islands", MI => "Michigan", MN => "Minnesota", MO => "Missouri", MP => "Northern Mariana Islands",
+            MS => "Mississippi", MT => "Montana", NC => "North Carolina", ND => "North Dakota", NE => "Nebraska",
+            NH => "New Hampshire", NJ => "New Jersey", NM => "New Mexico", NV => "Nevada", NY => "New York",
+            OH => "Ohio", OK => "Oklahoma", OR => "Oregon", PA => "Pennsylvania", PR => "Puerto Rico",
+            PW => "Palau", RI => "Rhode Island", SC => "South Carolina", SD => "South Dakota", TN => "Tennessee",
+            TX => "Texas", UT => "Utah", VA => "Virginia", VI => "Virgin Islands", VT => "Vermont",
+            WA => "Washington", WI => "Wisconsin", WV => "West Virginia", WY => "Wyoming", ZZ => "Foreign Country",
+        }
+    }
 }
 
 /// Country Code (ISO 3166-1 alpha-2, plus US, ZZ, etc.)
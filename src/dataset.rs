@@ -6,8 +6,12 @@
  */
 
 use std::path::{Path, PathBuf};
-use std::collections::HashMap;
-use crate::{Result, NppesError};
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::collections::{HashMap, HashSet};
+use chrono::NaiveDate;
+use crate::{Result, NppesError, ExportFormat};
+use crate::config::ValidationLevel;
 use crate::data_types::*;
 use crate::reader::NppesReader;
 use crate::analytics::NppesAnalytics;
@@ -92,12 +96,16 @@ impl From<&Path> for DataSource {
 /// ```
 pub struct NppesDatasetBuilder {
     main_data_source: Option<DataSource>,
+    additional_main_data_sources: Vec<DataSource>,
     taxonomy_source: Option<DataSource>,
     other_names_source: Option<DataSource>,
     practice_locations_source: Option<DataSource>,
     endpoints_source: Option<DataSource>,
     skip_invalid_records: bool,
+    validation_level: Option<ValidationLevel>,
     build_indexes: bool,
+    strict_cross_file: bool,
+    capture_raw_records: bool,
     #[cfg(feature = "progress")]
     show_progress: bool,
     #[cfg(feature = "download")]
@@ -115,12 +123,16 @@ impl NppesDatasetBuilder {
     pub fn new() -> Self {
         Self {
             main_data_source: None,
+            additional_main_data_sources: Vec::new(),
             taxonomy_source: None,
             other_names_source: None,
             practice_locations_source: None,
             endpoints_source: None,
             skip_invalid_records: false,
+            validation_level: None,
             build_indexes: true,
+            strict_cross_file: false,
+            capture_raw_records: false,
             #[cfg(feature = "progress")]
             show_progress: true,
             #[cfg(feature = "download")]
@@ -134,7 +146,26 @@ impl NppesDatasetBuilder {
         self
     }
     
+    /// Add a main data file, for sharded inputs or combining several releases
+    /// 
+    /// May be called repeatedly; the first call acts like [`main_data`](Self::main_data)
+    /// if no main source was set. Additional sources must be local files. All files
+    /// are concatenated and de-duplicated by NPI, with records from later sources
+    /// replacing earlier ones.
+    pub fn add_main_data<S: Into<DataSource>>(mut self, source: S) -> Self {
+        if self.main_data_source.is_none() {
+            self.main_data_source = Some(source.into());
+        } else {
+            self.additional_main_data_sources.push(source.into());
+        }
+        self
+    }
+    
     /// Set the path or URL to the taxonomy reference file
+    /// 
+    /// With a local main file, URL sources for this and the other auxiliary
+    /// files are downloaded individually (`download` feature); without the
+    /// feature, building fails rather than leaving the data out.
     pub fn taxonomy_reference<S: Into<DataSource>>(mut self, source: S) -> Self {
         self.taxonomy_source = Some(source.into());
         self
@@ -178,12 +209,38 @@ impl NppesDatasetBuilder {
         self
     }
     
+    /// Set how thoroughly main records are validated
+    /// 
+    /// Defaults to the level in the global configuration; see
+    /// [`NppesReader::with_validation_level`].
+    pub fn validation_level(mut self, level: ValidationLevel) -> Self {
+        self.validation_level = Some(level);
+        self
+    }
+    
     /// Enable or disable automatic index building
     pub fn build_indexes(mut self, build: bool) -> Self {
         self.build_indexes = build;
         self
     }
     
+    /// Fail the build when reference files contain NPIs missing from the main file
+    /// 
+    /// Orphaned records usually mean the files come from different NPPES releases.
+    /// See [`NppesDataset::orphaned_side_records`] to check a loaded dataset instead.
+    pub fn strict_cross_file(mut self, strict: bool) -> Self {
+        self.strict_cross_file = strict;
+        self
+    }
+    
+    /// Keep each provider's original CSV row for [`NppesDataset::raw_field`]
+    /// 
+    /// Roughly doubles the memory used by the main file; meant for debugging.
+    pub fn capture_raw_records(mut self, capture: bool) -> Self {
+        self.capture_raw_records = capture;
+        self
+    }
+    
     #[cfg(feature = "progress")]
     /// Enable or disable progress bars
     pub fn show_progress(mut self, show: bool) -> Self {
@@ -198,6 +255,7 @@ impl NppesDatasetBuilder {
             let rt = tokio::runtime::Runtime::new().map_err(|e| NppesError::Custom {
                 message: format!("Failed to create async runtime: {}", e),
                 suggestion: Some("Use build_async() if you're already in an async context".to_string()),
+                source: Some(Box::new(e)),
             })?;
             
             rt.block_on(self.build_async())
@@ -211,6 +269,7 @@ impl NppesDatasetBuilder {
                 .ok_or_else(|| NppesError::Custom {
                     message: "Main data source not specified".to_string(),
                     suggestion: Some("Use .main_data() to specify the main NPPES data source".to_string()),
+                    source: None,
                 })?;
             
             // Only file sources are supported without download feature
@@ -218,28 +277,20 @@ impl NppesDatasetBuilder {
                 DataSource::File(path) => {
                     let resolved_sources = ResolvedSources {
                         main_data_path: path,
-                        taxonomy_path: self.taxonomy_source.as_ref().and_then(|s| match s {
-                            DataSource::File(p) => Some(p.clone()),
-                            DataSource::Url(_) => None,
-                        }),
-                        other_names_path: self.other_names_source.as_ref().and_then(|s| match s {
-                            DataSource::File(p) => Some(p.clone()),
-                            DataSource::Url(_) => None,
-                        }),
-                        practice_locations_path: self.practice_locations_source.as_ref().and_then(|s| match s {
-                            DataSource::File(p) => Some(p.clone()),
-                            DataSource::Url(_) => None,
-                        }),
-                        endpoints_path: self.endpoints_source.as_ref().and_then(|s| match s {
-                            DataSource::File(p) => Some(p.clone()),
-                            DataSource::Url(_) => None,
-                        }),
+                        additional_main_data_paths: additional_main_data_paths(self.additional_main_data_sources)?,
+                        taxonomy_path: local_auxiliary_path(self.taxonomy_source, "Taxonomy reference")?,
+                        other_names_path: local_auxiliary_path(self.other_names_source, "Other names")?,
+                        practice_locations_path: local_auxiliary_path(self.practice_locations_source, "Practice locations")?,
+                        endpoints_path: local_auxiliary_path(self.endpoints_source, "Endpoints")?,
                     };
                     
                     Self::build_from_resolved_sources_static(
                         resolved_sources,
                         self.skip_invalid_records,
+                        self.validation_level,
                         self.build_indexes,
+                        self.strict_cross_file,
+                        self.capture_raw_records,
                         #[cfg(feature = "progress")]
                         self.show_progress,
                     )
@@ -257,23 +308,28 @@ impl NppesDatasetBuilder {
             .ok_or_else(|| NppesError::Custom {
                 message: "Main data source not specified".to_string(),
                 suggestion: Some("Use .main_data() or .from_url() to specify the main NPPES data source".to_string()),
+                source: None,
             })?;
         
         println!("Loading NPPES dataset...");
         
         // Extract all fields we need before moving them
+        let additional_main_data_paths = additional_main_data_paths(self.additional_main_data_sources)?;
         let taxonomy_source = self.taxonomy_source;
         let other_names_source = self.other_names_source;
         let practice_locations_source = self.practice_locations_source;
         let endpoints_source = self.endpoints_source;
         let skip_invalid_records = self.skip_invalid_records;
+        let validation_level = self.validation_level;
         let build_indexes = self.build_indexes;
+        let strict_cross_file = self.strict_cross_file;
+        let capture_raw_records = self.capture_raw_records;
         #[cfg(feature = "progress")]
         let show_progress = self.show_progress;
         #[cfg(feature = "download")]
         let download_config = self.download_config;
         
-        let resolved_sources = Self::resolve_sources_static(
+        let mut resolved_sources = Self::resolve_sources_static(
             main_source,
             taxonomy_source,
             other_names_source,
@@ -284,11 +340,15 @@ impl NppesDatasetBuilder {
             #[cfg(not(feature = "download"))]
             None,
         ).await?;
+        resolved_sources.additional_main_data_paths = additional_main_data_paths;
         
         Self::build_from_resolved_sources_static(
             resolved_sources,
             skip_invalid_records,
+            validation_level,
             build_indexes,
+            strict_cross_file,
+            capture_raw_records,
             #[cfg(feature = "progress")]
             show_progress,
         )
@@ -298,13 +358,20 @@ impl NppesDatasetBuilder {
     fn build_from_resolved_sources_static(
         resolved_sources: ResolvedSources,
         skip_invalid_records: bool,
+        validation_level: Option<ValidationLevel>,
         build_indexes: bool,
+        strict_cross_file: bool,
+        capture_raw_records: bool,
         #[cfg(feature = "progress")]
         show_progress: bool,
     ) -> Result<NppesDataset> {
         // Create reader with progress support
         let mut reader = NppesReader::new()
-            .with_skip_invalid_records(skip_invalid_records);
+            .with_skip_invalid_records(skip_invalid_records)
+            .with_raw_capture(capture_raw_records);
+        if let Some(level) = validation_level {
+            reader = reader.with_validation_level(level);
+        }
         
         #[cfg(feature = "progress")]
         if show_progress {
@@ -322,69 +389,103 @@ impl NppesDatasetBuilder {
         #[cfg(not(feature = "progress"))]
         println!("Loading main provider data from: {}", resolved_sources.main_data_path.display());
         
-        let providers = reader.load_main_data(&resolved_sources.main_data_path)?;
+        let (mut providers, report) = reader.load_main_data_with_report(&resolved_sources.main_data_path)?;
+        let mut raw_records = report.raw_records;
         
-        // Load other data files
-        let taxonomy_map = if let Some(path) = resolved_sources.taxonomy_path {
-            #[cfg(feature = "progress")]
-            if !show_progress {
-                println!("Loading taxonomy reference from: {}", path.display());
-            }
-            
-            #[cfg(not(feature = "progress"))]
-            println!("Loading taxonomy reference from: {}", path.display());
+        if !resolved_sources.additional_main_data_paths.is_empty() {
+            let mut positions: HashMap<Npi, usize> = providers.iter()
+                .enumerate()
+                .map(|(idx, p)| (p.npi.clone(), idx))
+                .collect();
             
-            let taxonomies = reader.load_taxonomy_data(&path)?;
-            Some(create_taxonomy_map(taxonomies))
-        } else {
-            None
-        };
+            for path in &resolved_sources.additional_main_data_paths {
+                #[cfg(feature = "progress")]
+                if !show_progress {
+                    println!("Loading additional provider data from: {}", path.display());
+                }
+                
+                #[cfg(not(feature = "progress"))]
+                println!("Loading additional provider data from: {}", path.display());
+                
+                let (records, report) = reader.load_main_data_with_report(path)?;
+                // Later rows replace earlier ones, so their raw rows must win too
+                raw_records.extend(report.raw_records);
+                for record in records {
+                    match positions.get(&record.npi) {
+                        Some(&idx) => providers[idx] = record,
+                        None => {
+                            positions.insert(record.npi.clone(), providers.len());
+                            providers.push(record);
+                        }
+                    }
+                }
+            }
+        }
         
-        let other_names_map = if let Some(path) = resolved_sources.other_names_path {
+        // Load other data files
+        let announce = |label: &str, path: &Path| {
             #[cfg(feature = "progress")]
             if !show_progress {
-                println!("Loading other names from: {}", path.display());
+                println!("Loading {} from: {}", label, path.display());
             }
             
             #[cfg(not(feature = "progress"))]
-            println!("Loading other names from: {}", path.display());
-            
-            let other_names = reader.load_other_name_data(&path)?;
-            Some(create_other_names_map(other_names))
-        } else {
-            None
+            println!("Loading {} from: {}", label, path.display());
+        };
+        let load_taxonomy = || -> Result<_> {
+            resolved_sources.taxonomy_path.as_deref()
+                .map(|path| {
+                    announce("taxonomy reference", path);
+                    reader.load_taxonomy_data(path).map(create_taxonomy_map)
+                })
+                .transpose()
+        };
+        let load_other_names = || -> Result<_> {
+            resolved_sources.other_names_path.as_deref()
+                .map(|path| {
+                    announce("other names", path);
+                    reader.load_other_name_data(path).map(create_other_names_map)
+                })
+                .transpose()
+        };
+        let load_practice_locations = || -> Result<_> {
+            resolved_sources.practice_locations_path.as_deref()
+                .map(|path| {
+                    announce("practice locations", path);
+                    reader.load_practice_location_data(path).map(create_practice_locations_map)
+                })
+                .transpose()
+        };
+        let load_endpoints = || -> Result<_> {
+            resolved_sources.endpoints_path.as_deref()
+                .map(|path| {
+                    announce("endpoints", path);
+                    reader.load_endpoint_data(path).map(create_endpoints_map)
+                })
+                .transpose()
         };
         
-        let practice_locations_map = if let Some(path) = resolved_sources.practice_locations_path {
-            #[cfg(feature = "progress")]
-            if !show_progress {
-                println!("Loading practice locations from: {}", path.display());
-            }
-            
-            #[cfg(not(feature = "progress"))]
-            println!("Loading practice locations from: {}", path.display());
-            
-            let locations = reader.load_practice_location_data(&path)?;
-            Some(create_practice_locations_map(locations))
-        } else {
-            None
+        let load_sequentially = || -> Result<_> {
+            Ok((load_taxonomy()?, load_other_names()?, load_practice_locations()?, load_endpoints()?))
         };
         
-        let endpoints_map = if let Some(path) = resolved_sources.endpoints_path {
-            #[cfg(feature = "progress")]
-            if !show_progress {
-                println!("Loading endpoints from: {}", path.display());
-            }
-            
-            #[cfg(not(feature = "progress"))]
-            println!("Loading endpoints from: {}", path.display());
-            
-            let endpoints = reader.load_endpoint_data(&path)?;
-            Some(create_endpoints_map(endpoints))
+        // The auxiliary files are independent, so load them concurrently unless
+        // progress bars or callbacks would interleave. Errors are reported in file
+        // order, not in the order the loads fail.
+        #[cfg(feature = "parallel")]
+        let (taxonomy_map, other_names_map, practice_locations_map, endpoints_map) = if reader.reports_progress() {
+            load_sequentially()?
         } else {
-            None
+            let ((taxonomy, other_names), (practice_locations, endpoints)) = rayon::join(
+                || rayon::join(load_taxonomy, load_other_names),
+                || rayon::join(load_practice_locations, load_endpoints),
+            );
+            (taxonomy?, other_names?, practice_locations?, endpoints?)
         };
         
+        #[cfg(not(feature = "parallel"))]
+        let (taxonomy_map, other_names_map, practice_locations_map, endpoints_map) = load_sequentially()?;
+        
         // Build indexes if requested
         let mut dataset = NppesDataset {
             providers,
@@ -394,8 +495,31 @@ impl NppesDatasetBuilder {
             endpoints_map,
             npi_index: None,
             state_index: None,
+            country_index: None,
             taxonomy_index: None,
+            other_id_index: None,
+            address_index: None,
+            enumeration_date_sorted: None,
+            raw_records: None,
         };
+        if capture_raw_records {
+            dataset.set_raw_records(raw_records);
+        }
+        
+        if strict_cross_file {
+            let orphans = dataset.orphaned_side_records();
+            if orphans > 0 {
+                return Err(NppesError::DataValidation {
+                    message: format!(
+                        "{} reference records have NPIs missing from the main file; the files may come from different NPPES releases",
+                        orphans
+                    ),
+                    field: Some("NPI".to_string()),
+                    value: Some(orphans.to_string()),
+                    context: Default::default(),
+                });
+            }
+        }
         
         if build_indexes {
             #[cfg(feature = "progress")]
@@ -434,25 +558,32 @@ impl NppesDatasetBuilder {
     ) -> Result<ResolvedSources> {
         match main_source {
             DataSource::File(path) => {
-                // All local files - just return paths
+                // Local main file; auxiliary files may still be URLs to download
+                #[cfg(feature = "download")]
+                let (taxonomy_path, other_names_path, practice_locations_path, endpoints_path) = {
+                    let mut downloader = NppesDownloader::with_config(download_config.unwrap_or_default());
+                    (
+                        download_auxiliary_source(&mut downloader, taxonomy_source).await?,
+                        download_auxiliary_source(&mut downloader, other_names_source).await?,
+                        download_auxiliary_source(&mut downloader, practice_locations_source).await?,
+                        download_auxiliary_source(&mut downloader, endpoints_source).await?,
+                    )
+                };
+                #[cfg(not(feature = "download"))]
+                let (taxonomy_path, other_names_path, practice_locations_path, endpoints_path) = (
+                    local_auxiliary_path(taxonomy_source, "Taxonomy reference")?,
+                    local_auxiliary_path(other_names_source, "Other names")?,
+                    local_auxiliary_path(practice_locations_source, "Practice locations")?,
+                    local_auxiliary_path(endpoints_source, "Endpoints")?,
+                );
+                
                 Ok(ResolvedSources {
                     main_data_path: path,
-                    taxonomy_path: taxonomy_source.and_then(|s| match s {
-                        DataSource::File(p) => Some(p),
-                        DataSource::Url(_) => None, // Handle mixed sources separately if needed
-                    }),
-                    other_names_path: other_names_source.and_then(|s| match s {
-                        DataSource::File(p) => Some(p),
-                        DataSource::Url(_) => None,
-                    }),
-                    practice_locations_path: practice_locations_source.and_then(|s| match s {
-                        DataSource::File(p) => Some(p),
-                        DataSource::Url(_) => None,
-                    }),
-                    endpoints_path: endpoints_source.and_then(|s| match s {
-                        DataSource::File(p) => Some(p),
-                        DataSource::Url(_) => None,
-                    }),
+                    additional_main_data_paths: Vec::new(),
+                    taxonomy_path,
+                    other_names_path,
+                    practice_locations_path,
+                    endpoints_path,
                 })
             }
             DataSource::Url(url) => {
@@ -470,11 +601,13 @@ impl NppesDatasetBuilder {
                         return Err(NppesError::Custom {
                             message: "No main NPPES data file found in downloaded archive".to_string(),
                             suggestion: Some("Check that the URL points to a valid NPPES data archive".to_string()),
+                            source: None,
                         });
                     }
                     
                     Ok(ResolvedSources {
                         main_data_path: extracted.main_data_file.unwrap(),
+                        additional_main_data_paths: Vec::new(),
                         taxonomy_path: extracted.taxonomy_file,
                         other_names_path: extracted.other_names_file,
                         practice_locations_path: extracted.practice_locations_file,
@@ -499,6 +632,7 @@ impl NppesDatasetBuilder {
             return Err(NppesError::Custom {
                 message: format!("'{}' is not a directory", dir.display()),
                 suggestion: Some("Provide a directory path containing NPPES data files".to_string()),
+                source: None,
             });
         }
         
@@ -538,6 +672,7 @@ impl NppesDatasetBuilder {
             return Err(NppesError::Custom {
                 message: "No main NPPES data file found in latest download".to_string(),
                 suggestion: Some("The CMS website structure may have changed".to_string()),
+                source: None,
             });
         }
         
@@ -551,15 +686,81 @@ impl NppesDatasetBuilder {
     }
 }
 
+/// Canonical provider orderings for [`NppesDataset::sort_providers_by`]
+/// 
+/// Every ordering breaks ties on NPI, so identical input always sorts identically.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortKey {
+    /// NPI ascending
+    Npi,
+    /// Mailing address state, then last name (legal business name for organizations)
+    StateThenLastName,
+    /// Enumeration date, oldest first
+    EnumerationDate,
+}
+
+/// Missing values sort after present ones
+fn cmp_missing_last<T: Ord>(a: Option<T>, b: Option<T>) -> std::cmp::Ordering {
+    a.is_none().cmp(&b.is_none()).then(a.cmp(&b))
+}
+
+/// Last name for individuals, legal business name otherwise
+fn sort_name(provider: &NppesRecord) -> Option<&str> {
+    provider.provider_name.last.as_deref()
+        .or(provider.organization_name.legal_business_name.as_deref())
+}
+
 /// Resolved file paths after downloading
 struct ResolvedSources {
     main_data_path: PathBuf,
+    additional_main_data_paths: Vec<PathBuf>,
     taxonomy_path: Option<PathBuf>,
     other_names_path: Option<PathBuf>,
     practice_locations_path: Option<PathBuf>,
     endpoints_path: Option<PathBuf>,
 }
 
+/// Resolve additional main data sources, which must be local files
+fn additional_main_data_paths(sources: Vec<DataSource>) -> Result<Vec<PathBuf>> {
+    sources.into_iter()
+        .map(|source| match source {
+            DataSource::File(path) => Ok(path),
+            DataSource::Url(url) => Err(NppesError::Custom {
+                message: format!("Additional main data source '{}' is a URL", url),
+                suggestion: Some("Download the file first and pass its local path to add_main_data()".to_string()),
+                source: None,
+            }),
+        })
+        .collect()
+}
+
+/// Resolve an auxiliary source without downloading, rejecting URLs
+#[cfg(not(feature = "download"))]
+fn local_auxiliary_path(source: Option<DataSource>, label: &str) -> Result<Option<PathBuf>> {
+    match source {
+        None => Ok(None),
+        Some(DataSource::File(path)) => Ok(Some(path)),
+        Some(DataSource::Url(url)) => Err(NppesError::Custom {
+            message: format!("{} source '{}' is a URL, but the download feature is disabled", label, url),
+            suggestion: Some("Enable the 'download' feature, or download the file first and pass its local path".to_string()),
+            source: None,
+        }),
+    }
+}
+
+/// Resolve an auxiliary source, downloading it if it's a URL
+#[cfg(feature = "download")]
+async fn download_auxiliary_source(
+    downloader: &mut NppesDownloader,
+    source: Option<DataSource>,
+) -> Result<Option<PathBuf>> {
+    match source {
+        None => Ok(None),
+        Some(DataSource::File(path)) => Ok(Some(path)),
+        Some(DataSource::Url(url)) => downloader.download_file(&url, None).await.map(Some),
+    }
+}
+
 /// Unified NPPES dataset containing all loaded data and indexes
 pub struct NppesDataset {
     /// Main provider records
@@ -580,7 +781,16 @@ pub struct NppesDataset {
     // Indexes for fast lookup
     npi_index: Option<HashMap<Npi, usize>>,
     state_index: Option<HashMap<String, Vec<usize>>>,
+    /// Provider positions by mailing address country code
+    country_index: Option<HashMap<String, Vec<usize>>>,
     taxonomy_index: Option<HashMap<String, Vec<usize>>>,
+    /// Provider positions by other identifier value (Medicaid, legacy Medicare and so on)
+    other_id_index: Option<HashMap<String, Vec<usize>>>,
+    address_index: Option<HashMap<AddressKey, Vec<usize>>>,
+    /// Provider positions sorted by enumeration date, for range queries
+    enumeration_date_sorted: Option<Vec<(NaiveDate, usize)>>,
+    /// Original main file rows by NPI, when captured for debugging
+    raw_records: Option<HashMap<Npi, csv::StringRecord>>,
 }
 
 impl NppesDataset {
@@ -603,7 +813,12 @@ impl NppesDataset {
             endpoints_map,
             npi_index,
             state_index,
+            country_index: None,
             taxonomy_index,
+            other_id_index: None,
+            address_index: None,
+            enumeration_date_sorted: None,
+            raw_records: None,
         }
     }
     
@@ -631,6 +846,68 @@ impl NppesDataset {
         self.providers.is_empty()
     }
     
+    /// Release unused capacity held by the providers, reference maps and indexes
+    ///
+    /// Useful after filtering `providers` down from a much larger file, since a
+    /// `Vec` keeps its original allocation when elements are removed.
+    pub fn shrink_to_fit(&mut self) {
+        self.providers.shrink_to_fit();
+        
+        if let Some(map) = &mut self.taxonomy_map {
+            map.shrink_to_fit();
+        }
+        shrink_npi_map(&mut self.other_names_map);
+        shrink_npi_map(&mut self.practice_locations_map);
+        shrink_npi_map(&mut self.endpoints_map);
+        
+        if let Some(index) = &mut self.npi_index {
+            index.shrink_to_fit();
+        }
+        for index in [&mut self.state_index, &mut self.country_index, &mut self.taxonomy_index, &mut self.other_id_index].into_iter().flatten() {
+            shrink_position_index(index);
+        }
+        if let Some(index) = &mut self.address_index {
+            shrink_position_index(index);
+        }
+        if let Some(index) = &mut self.enumeration_date_sorted {
+            index.shrink_to_fit();
+        }
+        if let Some(map) = &mut self.raw_records {
+            map.shrink_to_fit();
+        }
+    }
+    
+    /// Estimate the heap memory currently held by this dataset, in bytes
+    ///
+    /// Counts allocated capacity (not just length) of the providers, reference
+    /// maps and indexes, plus the text stored in provider records. Text inside
+    /// reference records and allocator overhead are not included, so treat the
+    /// result as a lower bound.
+    pub fn memory_footprint(&self) -> usize {
+        let providers = self.providers.capacity() * size_of::<NppesRecord>()
+            + self.providers.iter().map(record_heap_bytes).sum::<usize>();
+        
+        let taxonomy = self.taxonomy_map.as_ref().map_or(0, map_bytes);
+        let reference = npi_map_bytes(&self.other_names_map)
+            + npi_map_bytes(&self.practice_locations_map)
+            + npi_map_bytes(&self.endpoints_map);
+        
+        let indexes = self.npi_index.as_ref().map_or(0, map_bytes)
+            + self.state_index.as_ref().map_or(0, position_index_bytes)
+            + self.country_index.as_ref().map_or(0, position_index_bytes)
+            + self.taxonomy_index.as_ref().map_or(0, position_index_bytes)
+            + self.other_id_index.as_ref().map_or(0, position_index_bytes)
+            + self.address_index.as_ref().map_or(0, position_index_bytes)
+            + self.enumeration_date_sorted.as_ref()
+                .map_or(0, |index| index.capacity() * size_of::<(NaiveDate, usize)>());
+        
+        let raw = self.raw_records.as_ref().map_or(0, |map| {
+            map_bytes(map) + map.values().map(|row| row.as_slice().len()).sum::<usize>()
+        });
+        
+        providers + taxonomy + reference + indexes + raw
+    }
+    
     /// Build indexes for fast lookups
     pub fn build_indexes(&mut self) {
         #[cfg(feature = "parallel")]
@@ -696,6 +973,346 @@ impl NppesDataset {
             self.state_index = Some(state_index);
             self.taxonomy_index = Some(taxonomy_index);
         }
+        
+        self.build_country_index();
+        self.build_other_id_index();
+        self.build_enumeration_date_index();
+    }
+    
+    /// Build the mailing address country index used by [`get_by_country`](Self::get_by_country)
+    fn build_country_index(&mut self) {
+        let mut index: HashMap<String, Vec<usize>> = HashMap::new();
+        for (idx, provider) in self.providers.iter().enumerate() {
+            if let Some(country) = &provider.mailing_address.country {
+                index.entry(country.as_code().to_string())
+                    .or_default()
+                    .push(idx);
+            }
+        }
+        self.country_index = Some(index);
+    }
+    
+    /// Build the other identifier index used by [`get_by_other_identifier`](Self::get_by_other_identifier)
+    fn build_other_id_index(&mut self) {
+        let mut index: HashMap<String, Vec<usize>> = HashMap::new();
+        for (idx, provider) in self.providers.iter().enumerate() {
+            for identifier in &provider.other_identifiers {
+                let value = identifier.identifier.trim();
+                if value.is_empty() {
+                    continue;
+                }
+                let positions = index.entry(value.to_string()).or_default();
+                // A provider can list the same identifier for several issuers
+                if positions.last() != Some(&idx) {
+                    positions.push(idx);
+                }
+            }
+        }
+        self.other_id_index = Some(index);
+    }
+    
+    /// Build the sorted enumeration date index used by [`enumerated_between`](Self::enumerated_between)
+    fn build_enumeration_date_index(&mut self) {
+        let mut index: Vec<(NaiveDate, usize)> = self.providers.iter()
+            .enumerate()
+            .filter_map(|(idx, provider)| provider.enumeration_date.map(|date| (date, idx)))
+            .collect();
+        index.sort_unstable();
+        self.enumeration_date_sorted = Some(index);
+    }
+    
+    /// Save the indexes built by [`build_indexes`](Self::build_indexes) to a binary file
+    /// 
+    /// Reload them with [`load_indexes`](Self::load_indexes) on a later run to skip
+    /// rebuilding. Indexes that haven't been built are saved as absent.
+    pub fn save_indexes<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let file = BufWriter::new(File::create(path.as_ref())?);
+        let indexes: IndexFileRef = (
+            INDEX_FILE_VERSION,
+            self.providers.len(),
+            &self.npi_index,
+            &self.state_index,
+            &self.country_index,
+            &self.taxonomy_index,
+            &self.other_id_index,
+            &self.enumeration_date_sorted,
+        );
+        bincode::serialize_into(file, &indexes).map_err(|e| index_file_error(path.as_ref(), e))
+    }
+    
+    /// Load indexes saved by [`save_indexes`](Self::save_indexes) instead of rebuilding them
+    /// 
+    /// The file must have been saved for a dataset with the same number of
+    /// providers in the same order; a different provider count is reported as an
+    /// error and leaves the current indexes untouched. Positions aren't otherwise
+    /// checked, so only load indexes saved from the same source files. The
+    /// address index isn't saved, so any built one is dropped; rebuild it with
+    /// [`build_address_index`](Self::build_address_index).
+    pub fn load_indexes<P: AsRef<Path>>(&mut self, path: P) -> Result<()> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Err(NppesError::file_not_found_with_suggestion(path.to_path_buf()));
+        }
+        let file = BufReader::new(File::open(path)?);
+        let (version, provider_count, npi_index, state_index, country_index, taxonomy_index, other_id_index, enumeration_date_sorted): IndexFile =
+            bincode::deserialize_from(file).map_err(|e| index_file_error(path, e))?;
+        
+        if version != INDEX_FILE_VERSION {
+            return Err(NppesError::Custom {
+                message: format!("Index file {} has format version {}, expected {}", path.display(), version, INDEX_FILE_VERSION),
+                suggestion: Some("Rebuild the indexes with build_indexes and save them again".to_string()),
+                source: None,
+            });
+        }
+        if provider_count != self.providers.len() {
+            return Err(NppesError::Custom {
+                message: format!(
+                    "Index file {} was saved for {} providers, but the dataset has {}",
+                    path.display(), provider_count, self.providers.len()
+                ),
+                suggestion: Some("The indexes are stale; rebuild them with build_indexes and save them again".to_string()),
+                source: None,
+            });
+        }
+        
+        self.npi_index = npi_index;
+        self.state_index = state_index;
+        self.country_index = country_index;
+        self.taxonomy_index = taxonomy_index;
+        self.other_id_index = other_id_index;
+        self.enumeration_date_sorted = enumeration_date_sorted;
+        self.address_index = None;
+        Ok(())
+    }
+    
+    /// Get providers enumerated between two dates, inclusive
+    ///
+    /// With indexes built this binary-searches a sorted date index, so narrow
+    /// windows only touch the matching providers; otherwise it scans. Results
+    /// are in enumeration date order when indexed and in file order otherwise.
+    pub fn enumerated_between(&self, start: NaiveDate, end: NaiveDate) -> Vec<&NppesRecord> {
+        match &self.enumeration_date_sorted {
+            Some(index) => {
+                let lower = index.partition_point(|&(date, _)| date < start);
+                let upper = index.partition_point(|&(date, _)| date <= end);
+                index[lower..upper.max(lower)].iter()
+                    .filter_map(|&(_, idx)| self.providers.get(idx))
+                    .collect()
+            }
+            None => self.providers.iter()
+                .filter(|p| p.enumeration_date.is_some_and(|date| date >= start && date <= end))
+                .collect(),
+        }
+    }
+    
+    /// Attach original main file rows for [`raw_field`](Self::raw_field) lookups
+    /// 
+    /// Rows are keyed by their NPI column; when an NPI repeats, the last row wins.
+    /// Pass the [`LoadReport::raw_records`](crate::reader::LoadReport::raw_records)
+    /// captured by a reader with raw capture enabled.
+    pub fn set_raw_records(&mut self, rows: impl IntoIterator<Item = csv::StringRecord>) {
+        let raw_records = rows.into_iter()
+            .filter_map(|row| {
+                let npi = Npi::new(row.get(0)?.trim().to_string()).ok()?;
+                Some((npi, row))
+            })
+            .collect();
+        self.raw_records = Some(raw_records);
+    }
+    
+    /// Get the original CSV value of a main file column for a provider
+    /// 
+    /// `column_name` is the official NPPES header, matched case-insensitively.
+    /// Returns `None` unless raw records were captured (see
+    /// [`NppesDatasetBuilder::capture_raw_records`]), or if the NPI or column is unknown.
+    pub fn raw_field(&self, npi: &Npi, column_name: &str) -> Option<&str> {
+        let column = crate::schema::NppesMainSchema::column_names()
+            .iter()
+            .position(|name| name.eq_ignore_ascii_case(column_name.trim()))?;
+        self.raw_records.as_ref()?.get(npi)?.get(column)
+    }
+    
+    /// Reorder providers canonically so exports are byte-identical for identical data
+    ///
+    /// Indexes built before sorting are rebuilt, since they hold positions.
+    pub fn sort_providers_by(&mut self, key: SortKey) {
+        let by_key = |a: &NppesRecord, b: &NppesRecord| match key {
+            SortKey::Npi => std::cmp::Ordering::Equal,
+            SortKey::StateThenLastName => cmp_missing_last(
+                a.mailing_address.state.as_ref().map(|s| s.as_code()),
+                b.mailing_address.state.as_ref().map(|s| s.as_code()),
+            ).then_with(|| cmp_missing_last(sort_name(a), sort_name(b))),
+            SortKey::EnumerationDate => cmp_missing_last(a.enumeration_date, b.enumeration_date),
+        };
+        self.providers.sort_by(|a, b| by_key(a, b).then_with(|| a.npi.as_str().cmp(b.npi.as_str())));
+        
+        if self.npi_index.is_some() {
+            self.build_indexes();
+        }
+        if self.address_index.is_some() {
+            self.build_address_index();
+        }
+    }
+    
+    /// Create a new dataset containing only currently active providers
+    ///
+    /// Providers that were deactivated and later reactivated are kept. Reference
+    /// data is filtered to the retained NPIs and any indexes built on this dataset
+    /// are rebuilt on the snapshot, leaving `self` untouched.
+    pub fn active_snapshot(&self) -> NppesDataset {
+        self.subset(NppesRecord::is_active)
+    }
+
+    /// List providers whose set of taxonomy codes differs in `other`
+    ///
+    /// Treats `self` as the earlier snapshot and `other` as the later one. Only NPIs
+    /// present in both datasets are compared; each entry holds the NPI with its old
+    /// and new codes, sorted and deduplicated. Results are ordered by NPI.
+    pub fn taxonomy_changes(&self, other: &NppesDataset) -> Vec<(Npi, Vec<String>, Vec<String>)> {
+        fn codes(provider: &NppesRecord) -> Vec<String> {
+            let mut codes: Vec<String> = provider.taxonomy_codes.iter().map(|t| t.code.clone()).collect();
+            codes.sort();
+            codes.dedup();
+            codes
+        }
+
+        let later: HashMap<&Npi, &NppesRecord> = other.providers.iter().map(|p| (&p.npi, p)).collect();
+        let mut changes: Vec<(Npi, Vec<String>, Vec<String>)> = self.providers.iter()
+            .filter_map(|old| {
+                let new = later.get(&old.npi)?;
+                let (old_codes, new_codes) = (codes(old), codes(new));
+                (old_codes != new_codes).then(|| (old.npi.clone(), old_codes, new_codes))
+            })
+            .collect();
+        changes.sort_by(|a, b| a.0.as_str().cmp(b.0.as_str()));
+        changes
+    }
+
+    /// Clone the providers matching `keep` into a new dataset, filtering
+    /// reference maps and captured raw rows to the retained NPIs and rebuilding
+    /// existing indexes
+    fn subset<F>(&self, keep: F) -> NppesDataset
+    where
+        F: Fn(&NppesRecord) -> bool,
+    {
+        let providers: Vec<NppesRecord> = self.providers.iter()
+            .filter(|p| keep(p))
+            .cloned()
+            .collect();
+
+        let retained: HashSet<&Npi> = providers.iter().map(|p| &p.npi).collect();
+        let other_names_map = retain_npis(&self.other_names_map, &retained);
+        let practice_locations_map = retain_npis(&self.practice_locations_map, &retained);
+        let endpoints_map = retain_npis(&self.endpoints_map, &retained);
+        let raw_records = retain_npis(&self.raw_records, &retained);
+
+        let mut dataset = NppesDataset::new(
+            providers,
+            self.taxonomy_map.clone(),
+            other_names_map,
+            practice_locations_map,
+            endpoints_map,
+            None,
+            None,
+            None,
+        );
+        dataset.raw_records = raw_records;
+        if self.npi_index.is_some() {
+            dataset.build_indexes();
+        }
+        if self.address_index.is_some() {
+            dataset.build_address_index();
+        }
+        dataset
+    }
+
+    /// Keep only the providers listed in an NPI file
+    ///
+    /// The file holds one NPI per line; blank lines and lines starting with `#`
+    /// are ignored. Invalid NPIs are skipped and listed in the returned
+    /// [`NpiFileFilter`] with how many providers remain. Reference maps are
+    /// filtered to the retained NPIs and existing indexes are rebuilt.
+    pub fn retain_npis_from_file<P: AsRef<Path>>(&mut self, path: P) -> Result<NpiFileFilter> {
+        let (allowed, invalid_lines) = read_npi_file(path.as_ref())?;
+        
+        self.providers.retain(|p| allowed.contains(&p.npi));
+        if let Some(map) = &mut self.other_names_map {
+            map.retain(|npi, _| allowed.contains(npi));
+        }
+        if let Some(map) = &mut self.practice_locations_map {
+            map.retain(|npi, _| allowed.contains(npi));
+        }
+        if let Some(map) = &mut self.endpoints_map {
+            map.retain(|npi, _| allowed.contains(npi));
+        }
+        if let Some(map) = &mut self.raw_records {
+            map.retain(|npi, _| allowed.contains(npi));
+        }
+        
+        if self.npi_index.is_some() {
+            self.build_indexes();
+        }
+        if self.address_index.is_some() {
+            self.build_address_index();
+        }
+        Ok(NpiFileFilter { retained: self.providers.len(), invalid_lines })
+    }
+    
+    /// Create a new dataset containing only the providers listed in an NPI file
+    ///
+    /// See [`retain_npis_from_file`](Self::retain_npis_from_file) for the file
+    /// format; `self` is left untouched. The skipped invalid lines are returned
+    /// alongside the subset.
+    pub fn subset_from_npi_file<P: AsRef<Path>>(&self, path: P) -> Result<(NppesDataset, Vec<InvalidNpiLine>)> {
+        let (allowed, invalid_lines) = read_npi_file(path.as_ref())?;
+        Ok((self.subset(|p| allowed.contains(&p.npi)), invalid_lines))
+    }
+    
+    /// Apply an update dataset, such as a weekly incremental file, on top of this one
+    ///
+    /// Providers in `other` replace existing providers with the same NPI and
+    /// new NPIs are appended. A provider already here is kept only when its
+    /// `last_update_date` is newer than the update's; on equal dates `other`
+    /// wins. Other names, practice locations and endpoints from `other` replace
+    /// this dataset's entries for each NPI whose update was applied, even when
+    /// `other` has none for that NPI (as long as it has that file), taxonomy
+    /// references are added or replaced by code, and indexes are rebuilt.
+    pub fn merge(&mut self, other: NppesDataset) {
+        let mut positions: HashMap<Npi, usize> = self.providers.iter()
+            .enumerate()
+            .map(|(idx, provider)| (provider.npi.clone(), idx))
+            .collect();
+        let mut kept: HashSet<Npi> = HashSet::new();
+        let mut replaced: HashSet<Npi> = HashSet::new();
+        
+        for record in other.providers {
+            match positions.get(&record.npi) {
+                Some(&idx) if self.providers[idx].last_update_date > record.last_update_date => {
+                    kept.insert(record.npi);
+                }
+                Some(&idx) => {
+                    replaced.insert(record.npi.clone());
+                    self.providers[idx] = record;
+                }
+                None => {
+                    positions.insert(record.npi.clone(), self.providers.len());
+                    self.providers.push(record);
+                }
+            }
+        }
+        
+        if let Some(taxonomy_map) = other.taxonomy_map {
+            self.taxonomy_map.get_or_insert_with(HashMap::new).extend(taxonomy_map);
+        }
+        merge_npi_map(&mut self.other_names_map, other.other_names_map, &kept, &replaced);
+        merge_npi_map(&mut self.practice_locations_map, other.practice_locations_map, &kept, &replaced);
+        merge_npi_map(&mut self.endpoints_map, other.endpoints_map, &kept, &replaced);
+        merge_npi_map(&mut self.raw_records, other.raw_records, &kept, &replaced);
+        
+        self.build_indexes();
+        if self.address_index.is_some() || other.address_index.is_some() {
+            self.build_address_index();
+        }
     }
     
     /// Get a provider by NPI (O(1) if indexed)
@@ -733,6 +1350,40 @@ impl NppesDataset {
         }
     }
     
+    /// Get all providers whose mailing address is in a country (fast if indexed)
+    ///
+    /// Takes the country code as NPPES stores it (`"US"`, `"CA"`, ...), ignoring
+    /// case. Useful for foreign providers, whose state is `ZZ` or missing.
+    pub fn get_by_country(&self, code: &str) -> Vec<&NppesRecord> {
+        let country = CountryCode::from_code(code.trim());
+        if let Some(index) = &self.country_index {
+            index.get(country.as_code())
+                .map(|indices| indices.iter().filter_map(|&idx| self.providers.get(idx)).collect())
+                .unwrap_or_default()
+        } else {
+            self.providers.iter()
+                .filter(|p| p.mailing_address.country.as_ref() == Some(&country))
+                .collect()
+        }
+    }
+    
+    /// Get all providers listing an other identifier, such as a Medicaid or legacy Medicare number (fast if indexed)
+    ///
+    /// Identifiers aren't unique across issuers, so several providers can match.
+    /// The value is compared exactly after trimming whitespace.
+    pub fn get_by_other_identifier(&self, id: &str) -> Vec<&NppesRecord> {
+        let id = id.trim();
+        if let Some(index) = &self.other_id_index {
+            index.get(id)
+                .map(|indices| indices.iter().filter_map(|&idx| self.providers.get(idx)).collect())
+                .unwrap_or_default()
+        } else {
+            self.providers.iter()
+                .filter(|p| p.other_identifiers.iter().any(|other| other.identifier.trim() == id))
+                .collect()
+        }
+    }
+    
     /// Get all providers with a specific taxonomy code (fast if indexed)
     pub fn get_by_taxonomy(&self, taxonomy_code: &str) -> Vec<&NppesRecord> {
         if let Some(index) = &self.taxonomy_index {
@@ -752,11 +1403,104 @@ impl NppesDataset {
         }
     }
     
+    /// Build an index of providers keyed by normalized practice address
+    /// 
+    /// Enables fast co-location lookups via [`providers_at_address`](Self::providers_at_address).
+    pub fn build_address_index(&mut self) {
+        let mut address_index: HashMap<AddressKey, Vec<usize>> = HashMap::new();
+        for (idx, provider) in self.providers.iter().enumerate() {
+            if let Some(key) = provider.practice_address.normalized_key() {
+                address_index.entry(key)
+                    .or_default()
+                    .push(idx);
+            }
+        }
+        self.address_index = Some(address_index);
+    }
+    
+    /// Get all providers whose practice address matches the given address (fast if indexed)
+    /// 
+    /// Addresses are compared on their normalized `(line_1, city, state, zip5)` key,
+    /// so differences in case, punctuation and ZIP+4 suffixes are ignored.
+    pub fn providers_at_address(&self, addr: &Address) -> Vec<&NppesRecord> {
+        let Some(key) = addr.normalized_key() else {
+            return Vec::new();
+        };
+        
+        if let Some(index) = &self.address_index {
+            index.get(&key)
+                .map(|indices| {
+                    indices.iter()
+                        .filter_map(|&idx| self.providers.get(idx))
+                        .collect()
+                })
+                .unwrap_or_default()
+        } else {
+            self.providers.iter()
+                .filter(|p| p.practice_address.normalized_key().as_ref() == Some(&key))
+                .collect()
+        }
+    }
+    
     /// Get taxonomy description for a code
     pub fn get_taxonomy_description(&self, code: &str) -> Option<&TaxonomyReference> {
         self.taxonomy_map.as_ref()?.get(code)
     }
     
+    /// Get a provider together with all of its reference data
+    ///
+    /// Side data that wasn't loaded, or has no entries for this NPI, comes back
+    /// as empty slices.
+    pub fn full_record(&self, npi: &Npi) -> Option<FullProvider<'_>> {
+        let provider = self.get_by_npi(npi)?;
+        Some(FullProvider {
+            provider,
+            other_names: self.get_other_names(npi).map_or(&[], Vec::as_slice),
+            practice_locations: self.get_practice_locations(npi).map_or(&[], Vec::as_slice),
+            endpoints: self.get_endpoints(npi).map_or(&[], Vec::as_slice),
+            primary_specialty: provider.primary_taxonomy()
+                .and_then(|t| self.get_taxonomy_description(&t.code)),
+        })
+    }
+    
+    /// Count reference records (other names, practice locations, endpoints) whose
+    /// NPI isn't among the providers
+    pub fn orphaned_side_records(&self) -> usize {
+        let npis: HashSet<&Npi> = self.providers.iter().map(|p| &p.npi).collect();
+        count_orphans(&self.other_names_map, &npis)
+            + count_orphans(&self.practice_locations_map, &npis)
+            + count_orphans(&self.endpoints_map, &npis)
+    }
+    
+    /// Check whether a taxonomy code exists in the loaded taxonomy reference
+    ///
+    /// Always `false` when no taxonomy reference was loaded.
+    pub fn is_known_taxonomy(&self, code: &str) -> bool {
+        self.get_taxonomy_description(code).is_some()
+    }
+    
+    /// List taxonomy reference entries that no provider uses, sorted by code
+    ///
+    /// Uses the taxonomy index when built. Empty when no taxonomy reference was loaded.
+    pub fn unused_taxonomy_references(&self) -> Vec<&TaxonomyReference> {
+        let Some(taxonomy_map) = &self.taxonomy_map else {
+            return Vec::new();
+        };
+        
+        let used: HashSet<&str> = match &self.taxonomy_index {
+            Some(index) => index.keys().map(String::as_str).collect(),
+            None => self.providers.iter()
+                .flat_map(|p| p.taxonomy_codes.iter().map(|t| t.code.as_str()))
+                .collect(),
+        };
+        
+        let mut unused: Vec<&TaxonomyReference> = taxonomy_map.values()
+            .filter(|reference| !used.contains(reference.code.as_str()))
+            .collect();
+        unused.sort_by(|a, b| a.code.cmp(&b.code));
+        unused
+    }
+    
     /// Get other names for an NPI
     pub fn get_other_names(&self, npi: &Npi) -> Option<&Vec<OtherNameRecord>> {
         self.other_names_map.as_ref()?.get(npi)
@@ -772,26 +1516,331 @@ impl NppesDataset {
         self.endpoints_map.as_ref()?.get(npi)
     }
     
-    /// Create an analytics engine for this dataset
-    pub fn analytics(&self) -> NppesAnalytics {
-        NppesAnalytics::new(&self.providers)
-    }
-    
-    /// Create a query builder for this dataset
-    pub fn query(&self) -> QueryBuilder {
-        QueryBuilder::new(self)
+    /// Get providers with at least one endpoint of a type, such as `"FHIR"` or `"DIRECT"`
+    ///
+    /// The type is compared ignoring case and surrounding whitespace. Each
+    /// provider appears once, in dataset order, however many matching endpoints
+    /// it has. Requires endpoint data to be loaded.
+    pub fn get_providers_with_endpoint_type(&self, endpoint_type: &str) -> Vec<&NppesRecord> {
+        let Some(endpoints_map) = &self.endpoints_map else {
+            return Vec::new();
+        };
+        let endpoint_type = endpoint_type.trim();
+        self.providers.iter()
+            .filter(|p| {
+                endpoints_map.get(&p.npi).is_some_and(|endpoints| {
+                    endpoints.iter().any(|e| {
+                        e.endpoint_type.as_deref()
+                            .is_some_and(|t| t.trim().eq_ignore_ascii_case(endpoint_type))
+                    })
+                })
+            })
+            .collect()
     }
-    
+
+    /// Export providers joined with their primary taxonomy descriptions to a flat CSV
+    ///
+    /// Streams the dataset in a single pass, writing one row per provider with the
+    /// primary taxonomy's classification, specialization and display name looked up
+    /// in the taxonomy reference. Requires taxonomy reference data to be loaded.
+    pub fn export_enriched_csv<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let taxonomy_map = self.taxonomy_map.as_ref().ok_or_else(|| NppesError::DataValidation {
+            message: "Taxonomy reference data required for enrichment".to_string(),
+            field: None,
+            value: None,
+            context: Default::default(),
+        })?;
+
+        let mut writer = csv::Writer::from_path(path.as_ref())?;
+        writer.write_record([
+            "npi",
+            "entity_type",
+            "name",
+            "is_active",
+            "enumeration_date",
+            "practice_address_line_1",
+            "practice_city",
+            "practice_state",
+            "practice_postal_code",
+            "practice_telephone",
+            "primary_taxonomy_code",
+            "taxonomy_classification",
+            "taxonomy_specialization",
+            "taxonomy_display_name",
+            "license_number",
+            "license_state",
+        ])?;
+
+        for provider in &self.providers {
+            let primary = provider.primary_taxonomy();
+            let reference = primary.and_then(|t| taxonomy_map.get(&t.code));
+            let address = &provider.practice_address;
+
+            writer.write_record([
+                provider.npi.as_str(),
+                provider.entity_type.as_ref().map_or("", |e| e.to_code()),
+                &provider.display_name(),
+                if provider.is_active() { "true" } else { "false" },
+                &provider.enumeration_date.map(|d| d.to_string()).unwrap_or_default(),
+                address.line_1.as_deref().unwrap_or(""),
+                address.city.as_deref().unwrap_or(""),
+                address.state.as_ref().map_or("", |s| s.as_code()),
+                address.postal_code.as_deref().unwrap_or(""),
+                address.telephone.as_deref().unwrap_or(""),
+                primary.map_or("", |t| t.code.as_str()),
+                reference.and_then(|r| r.classification.as_deref()).unwrap_or(""),
+                reference.and_then(|r| r.specialization.as_deref()).unwrap_or(""),
+                reference.and_then(|r| r.display_name.as_deref()).unwrap_or(""),
+                primary.and_then(|t| t.license_number.as_deref()).unwrap_or(""),
+                primary.and_then(|t| t.license_state.as_deref()).unwrap_or(""),
+            ])?;
+        }
+
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Iterate over providers in long format, one row per taxonomy code
+    ///
+    /// The long-format counterpart to the wide CSV export, for dataframe tools
+    /// such as polars or pandas. Providers without taxonomy codes still get one
+    /// row, with the taxonomy fields set to `None`. Display names are looked up
+    /// in the taxonomy reference when it's loaded.
+    pub fn iter_provider_taxonomy_rows(&self) -> impl Iterator<Item = ProviderTaxonomyRow> + '_ {
+        self.providers.iter().flat_map(move |provider| {
+            let row = |taxonomy: Option<&TaxonomyCode>| ProviderTaxonomyRow {
+                npi: provider.npi.clone(),
+                name: provider.display_name(),
+                state: provider.mailing_address.state.clone(),
+                taxonomy_code: taxonomy.map(|t| t.code.clone()),
+                is_primary: taxonomy.map(|t| t.is_primary),
+                taxonomy_display_name: taxonomy
+                    .and_then(|t| self.get_taxonomy_description(&t.code))
+                    .and_then(|r| r.display_name.clone()),
+            };
+            if provider.taxonomy_codes.is_empty() {
+                vec![row(None)]
+            } else {
+                provider.taxonomy_codes.iter().map(|t| row(Some(t))).collect()
+            }
+        })
+    }
+
+    /// Create an analytics engine for this dataset
+    pub fn analytics(&self) -> NppesAnalytics {
+        NppesAnalytics::new(&self.providers)
+    }
+    
+    /// Create a query builder for this dataset
+    pub fn query(&self) -> QueryBuilder {
+        QueryBuilder::new(self)
+    }
+    
     /// Get dataset statistics
     pub fn statistics(&self) -> DatasetStatistics {
         DatasetStatistics::from_dataset(self)
     }
 }
 
+/// Read an NPI allow-list, one NPI per line, along with its invalid lines
+fn read_npi_file(path: &Path) -> Result<(HashSet<Npi>, Vec<InvalidNpiLine>)> {
+    if !path.exists() {
+        return Err(NppesError::file_not_found_with_suggestion(path.to_path_buf()));
+    }
+    
+    let mut npis = HashSet::new();
+    let mut invalid_lines = Vec::new();
+    for (line_number, line) in std::fs::read_to_string(path)?.lines().enumerate() {
+        let value = line.trim();
+        if value.is_empty() || value.starts_with('#') {
+            continue;
+        }
+        match Npi::new(value.to_string()) {
+            Ok(npi) => {
+                npis.insert(npi);
+            }
+            Err(_) => invalid_lines.push(InvalidNpiLine { line_number: line_number + 1, value: value.to_string() }),
+        }
+    }
+    Ok((npis, invalid_lines))
+}
+
+/// Clone the entries of an NPI-keyed reference map whose NPI is in `retained`
+fn retain_npis<V: Clone>(
+    map: &Option<HashMap<Npi, V>>,
+    retained: &HashSet<&Npi>,
+) -> Option<HashMap<Npi, V>> {
+    map.as_ref().map(|m| {
+        m.iter()
+            .filter(|(npi, _)| retained.contains(npi))
+            .map(|(npi, records)| (npi.clone(), records.clone()))
+            .collect()
+    })
+}
+
+/// Format version written at the start of index files
+const INDEX_FILE_VERSION: u32 = 2;
+
+/// Index file contents: version, provider count, then the NPI, state, country,
+/// taxonomy, other identifier and enumeration date indexes
+type IndexFile = (
+    u32,
+    usize,
+    Option<HashMap<Npi, usize>>,
+    Option<HashMap<String, Vec<usize>>>,
+    Option<HashMap<String, Vec<usize>>>,
+    Option<HashMap<String, Vec<usize>>>,
+    Option<HashMap<String, Vec<usize>>>,
+    Option<Vec<(NaiveDate, usize)>>,
+);
+
+/// Borrowed form of [`IndexFile`] for saving
+type IndexFileRef<'a> = (
+    u32,
+    usize,
+    &'a Option<HashMap<Npi, usize>>,
+    &'a Option<HashMap<String, Vec<usize>>>,
+    &'a Option<HashMap<String, Vec<usize>>>,
+    &'a Option<HashMap<String, Vec<usize>>>,
+    &'a Option<HashMap<String, Vec<usize>>>,
+    &'a Option<Vec<(NaiveDate, usize)>>,
+);
+
+/// Wrap an index file encoding error
+fn index_file_error(path: &Path, err: bincode::Error) -> NppesError {
+    NppesError::Custom {
+        message: format!("Failed to read or write index file {}: {}", path.display(), err),
+        suggestion: Some("Rebuild the indexes with build_indexes and save them again".to_string()),
+        source: Some(err as crate::error::BoxedError),
+    }
+}
+
+/// Move entries from an update's NPI map into `base`, except for NPIs whose existing record was kept
+///
+/// Base entries for `replaced` NPIs are dropped first, so a replaced provider
+/// without entries in the update doesn't keep the old ones.
+fn merge_npi_map<V>(
+    base: &mut Option<HashMap<Npi, V>>,
+    update: Option<HashMap<Npi, V>>,
+    kept: &HashSet<Npi>,
+    replaced: &HashSet<Npi>,
+) {
+    let Some(update) = update else { return };
+    let base = base.get_or_insert_with(HashMap::new);
+    base.retain(|npi, _| !replaced.contains(npi));
+    base.extend(update.into_iter().filter(|(npi, _)| !kept.contains(npi)));
+}
+
+impl FromIterator<NppesRecord> for NppesDataset {
+    /// Collect records into a dataset with no reference data and no indexes
+    fn from_iter<I: IntoIterator<Item = NppesRecord>>(iter: I) -> Self {
+        NppesDataset::new(iter.into_iter().collect(), None, None, None, None, None, None, None)
+    }
+}
+
+impl Extend<NppesRecord> for NppesDataset {
+    /// Append records, dropping any built indexes since their positions are stale
+    ///
+    /// Call [`build_indexes`](NppesDataset::build_indexes) again afterwards if needed.
+    fn extend<I: IntoIterator<Item = NppesRecord>>(&mut self, iter: I) {
+        self.providers.extend(iter);
+        self.npi_index = None;
+        self.state_index = None;
+        self.country_index = None;
+        self.taxonomy_index = None;
+        self.other_id_index = None;
+        self.address_index = None;
+        self.enumeration_date_sorted = None;
+    }
+}
+
+/// Count the records of an NPI-keyed reference map whose NPI isn't in `npis`
+fn count_orphans<T>(map: &Option<HashMap<Npi, Vec<T>>>, npis: &HashSet<&Npi>) -> usize {
+    map.as_ref().map_or(0, |m| {
+        m.iter()
+            .filter(|(npi, _)| !npis.contains(npi))
+            .map(|(_, records)| records.len())
+            .sum()
+    })
+}
+
+/// Shrink an NPI-keyed reference map and each of its record lists
+fn shrink_npi_map<T>(map: &mut Option<HashMap<Npi, Vec<T>>>) {
+    if let Some(map) = map {
+        map.shrink_to_fit();
+        map.values_mut().for_each(Vec::shrink_to_fit);
+    }
+}
+
+/// Shrink an index of provider positions and each of its position lists
+fn shrink_position_index<K: Eq + std::hash::Hash>(index: &mut HashMap<K, Vec<usize>>) {
+    index.shrink_to_fit();
+    index.values_mut().for_each(Vec::shrink_to_fit);
+}
+
+/// Bytes allocated for a map's buckets, with one control byte per bucket
+fn map_bytes<K, V>(map: &HashMap<K, V>) -> usize {
+    map.capacity() * (size_of::<K>() + size_of::<V>() + 1)
+}
+
+/// Bytes allocated for an NPI-keyed reference map, including its record lists
+fn npi_map_bytes<T>(map: &Option<HashMap<Npi, Vec<T>>>) -> usize {
+    map.as_ref().map_or(0, |m| {
+        map_bytes(m)
+            + m.iter()
+                .map(|(npi, records)| npi.0.capacity() + records.capacity() * size_of::<T>())
+                .sum::<usize>()
+    })
+}
+
+/// Bytes allocated for an index of provider positions, including the position lists
+fn position_index_bytes<K>(index: &HashMap<K, Vec<usize>>) -> usize {
+    map_bytes(index)
+        + index.values().map(|positions| positions.capacity() * size_of::<usize>()).sum::<usize>()
+}
+
+/// Heap bytes owned by a provider record beyond its inline size
+fn record_heap_bytes(record: &NppesRecord) -> usize {
+    let text = |value: &Option<String>| value.as_ref().map_or(0, String::capacity);
+    let address = |a: &Address| {
+        text(&a.line_1) + text(&a.line_2) + text(&a.city)
+            + text(&a.postal_code) + text(&a.telephone) + text(&a.fax)
+    };
+    let name = |n: &ProviderName| text(&n.first) + text(&n.middle) + text(&n.last) + text(&n.credential);
+    
+    let official = record.authorized_official.as_ref().map_or(0, |o| {
+        text(&o.first_name) + text(&o.middle_name) + text(&o.last_name)
+            + text(&o.credential) + text(&o.title) + text(&o.telephone)
+    });
+    let taxonomies = record.taxonomy_codes.capacity() * size_of::<TaxonomyCode>()
+        + record.taxonomy_codes.iter()
+            .map(|t| t.code.capacity() + text(&t.license_number) + text(&t.license_state) + text(&t.taxonomy_group))
+            .sum::<usize>();
+    let identifiers = record.other_identifiers.capacity() * size_of::<OtherIdentifier>()
+        + record.other_identifiers.iter()
+            .map(|id| id.identifier.capacity() + text(&id.type_code))
+            .sum::<usize>();
+    
+    record.npi.0.capacity()
+        + record.replacement_npi.as_ref().map_or(0, |npi| npi.0.capacity())
+        + record.ein.as_ref().map_or(0, |ein| ein.as_str().len())
+        + name(&record.provider_name)
+        + name(&record.provider_other_name)
+        + text(&record.organization_name.legal_business_name)
+        + text(&record.organization_name.other_name)
+        + address(&record.mailing_address)
+        + address(&record.practice_address)
+        + official
+        + taxonomies
+        + identifiers
+        + text(&record.parent_organization_lbn)
+        + record.parent_organization_tin.as_ref().map_or(0, |tin| tin.as_str().len())
+}
+
 /// Query builder for NPPES dataset
 pub struct QueryBuilder<'a> {
     dataset: &'a NppesDataset,
-    filters: Vec<Box<dyn Fn(&NppesRecord) -> bool + Send + Sync + 'a>>,
+    query: CompiledQuery<'a>,
 }
 
 impl<'a> QueryBuilder<'a> {
@@ -799,84 +1848,206 @@ impl<'a> QueryBuilder<'a> {
     pub fn new(dataset: &'a NppesDataset) -> Self {
         Self {
             dataset,
-            filters: Vec::new(),
+            query: CompiledQuery::new(),
         }
     }
     
-    /// Filter by state
+    /// Apply the location filters that follow to the practice address
+    /// 
+    /// Location filters ([`state`](Self::state), [`state_in`](Self::state_in),
+    /// [`country`](Self::country), [`postal_code`](Self::postal_code), [`postal_code_prefix`](Self::postal_code_prefix),
+    /// [`city`](Self::city) and [`city_contains`](Self::city_contains)) check the
+    /// mailing address by default. Filters added before this call keep the
+    /// address they were added with.
+    /// 
+    /// # Example
+    /// ```no_run
+    /// # use nppes::prelude::*;
+    /// # let dataset = NppesDataset::load_standard("./data")?;
+    /// // Mailed to New Jersey, practicing in New York
+    /// let commuters = dataset.query()
+    ///     .state("NJ")
+    ///     .use_practice_address()
+    ///     .state("NY")
+    ///     .execute();
+    /// # Ok::<(), nppes::NppesError>(())
+    /// ```
+    pub fn use_practice_address(mut self) -> Self {
+        self.query = self.query.use_practice_address();
+        self
+    }
+    
+    /// Apply the location filters that follow to the mailing address (the default)
+    pub fn use_mailing_address(mut self) -> Self {
+        self.query = self.query.use_mailing_address();
+        self
+    }
+    
+    /// Filter by state of the selected address (mailing by default)
     pub fn state(mut self, state: &'a str) -> Self {
-        let state_enum = StateCode::from_code(state);
-        self.filters.push(Box::new(move |p| {
-            p.mailing_address.state.as_ref()
-                .map(|s| Some(s) == state_enum.as_ref())
-                .unwrap_or(false)
-        }));
+        self.query = self.query.state(state);
         self
     }
     
-    /// Filter by multiple states
+    /// Filter by multiple states of the selected address
     pub fn state_in(mut self, states: &'a [&str]) -> Self {
-        let state_enums: Vec<_> = states.iter().filter_map(|s| StateCode::from_code(s)).collect();
-        self.filters.push(Box::new(move |p| {
-            p.mailing_address.state.as_ref()
-                .map(|s| state_enums.iter().any(|se| se == s))
-                .unwrap_or(false)
-        }));
+        self.query = self.query.state_in(states);
+        self
+    }
+    
+    /// Filter by country code of the selected address, ignoring case
+    pub fn country(mut self, code: &'a str) -> Self {
+        self.query = self.query.country(code);
+        self
+    }
+    
+    /// Filter by postal code of the selected address, compared on ZIP5
+    /// 
+    /// `"94103"` matches providers stored as `"94103"`, `"941031234"` or `"94103-1234"`.
+    pub fn postal_code(mut self, postal_code: &'a str) -> Self {
+        self.query = self.query.postal_code(postal_code);
+        self
+    }
+    
+    /// Filter by postal code prefix of the selected address
+    /// 
+    /// A plain string prefix, so leading zeros matter: `"021"` matches Boston
+    /// ZIPs such as `"02115"` and `"021151234"`. Hyphens are ignored on both
+    /// sides, so `"02115-12"` matches a stored `"021151234"`. Providers without
+    /// a postal code never match.
+    pub fn postal_code_prefix(mut self, prefix: &'a str) -> Self {
+        self.query = self.query.postal_code_prefix(prefix);
+        self
+    }
+    
+    /// Filter by city of the selected address, ignoring case and surrounding whitespace
+    pub fn city(mut self, city: &'a str) -> Self {
+        self.query = self.query.city(city);
+        self
+    }
+    
+    /// Filter to cities of the selected address containing the text, ignoring case
+    pub fn city_contains(mut self, text: &'a str) -> Self {
+        self.query = self.query.city_contains(text);
+        self
+    }
+    
+    /// Filter to providers with a secondary practice location in a state
+    /// 
+    /// Checks the practice location reference file, not the provider's own
+    /// practice address, so it finds multi-site providers. Matches nothing
+    /// when practice locations weren't loaded.
+    pub fn secondary_location_state(mut self, state: &'a str) -> Self {
+        self.query = self.query.secondary_location_state(state);
         self
     }
     
     /// Filter by specialty (taxonomy display name)
     pub fn specialty(mut self, specialty: &'a str) -> Self {
-        let specialty_lower = specialty.to_lowercase();
-        self.filters.push(Box::new(move |p| {
-            p.taxonomy_codes.iter().any(|t| {
-                if let Some(taxonomy_ref) = self.dataset.get_taxonomy_description(&t.code) {
-                    taxonomy_ref.display_name.as_ref()
-                        .map(|name| name.to_lowercase().contains(&specialty_lower))
-                        .unwrap_or(false)
-                } else {
-                    false
-                }
-            })
-        }));
+        self.query = self.query.specialty(specialty);
+        self
+    }
+    
+    /// Filter by exact NUCC taxonomy code, primary or not
+    /// 
+    /// Unlike [`specialty`](Self::specialty), this needs no taxonomy reference.
+    /// With indexes built, the dataset's taxonomy index narrows the scan to
+    /// providers carrying the code.
+    pub fn taxonomy_code(mut self, taxonomy_code: &'a str) -> Self {
+        self.query = self.query.taxonomy_code(taxonomy_code);
+        self
+    }
+    
+    /// Filter by exact NUCC taxonomy code, only when it is the provider's primary taxonomy
+    pub fn primary_taxonomy_code(mut self, taxonomy_code: &'a str) -> Self {
+        self.query = self.query.primary_taxonomy_code(taxonomy_code);
+        self
+    }
+    
+    /// Treat taxonomy codes missing from the loaded reference as errors in [`try_execute`](Self::try_execute)
+    ///
+    /// Without strict mode, unknown codes only produce a warning.
+    pub fn strict_taxonomy(mut self) -> Self {
+        self.query = self.query.strict_taxonomy();
         self
     }
     
     /// Filter by entity type
     pub fn entity_type(mut self, entity_type: EntityType) -> Self {
-        let entity_type = entity_type.clone();
-        self.filters.push(Box::new(move |p| p.entity_type == Some(entity_type.clone())));
+        self.query = self.query.entity_type(entity_type);
         self
     }
     
     /// Filter by active status
     pub fn active_only(mut self) -> Self {
-        self.filters.push(Box::new(|p| p.is_active()));
+        self.query = self.query.active_only();
+        self
+    }
+    
+    /// Filter to inactive providers deactivated for a reason, such as death or fraud
+    pub fn deactivated_for(mut self, reason: DeactivationReasonCode) -> Self {
+        self.query = self.query.deactivated_for(reason);
+        self
+    }
+
+    /// Filter with an arbitrary predicate
+    ///
+    /// Escape hatch for logic the named filters don't cover; it can be freely
+    /// mixed with them.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use nppes::prelude::*;
+    /// # let dataset = NppesDataset::load_standard("./data")?;
+    /// let zips = ["94103", "94110"];
+    /// let results = dataset.query()
+    ///     .state("CA")
+    ///     .custom(|p| {
+    ///         p.practice_address.postal_code.as_deref()
+    ///             .map(|zip| zips.iter().any(|z| zip.starts_with(z)))
+    ///             .unwrap_or(false)
+    ///     })
+    ///     .execute();
+    /// # Ok::<(), nppes::NppesError>(())
+    /// ```
+    pub fn custom<F>(mut self, predicate: F) -> Self
+    where
+        F: Fn(&NppesRecord) -> bool + Send + Sync + 'a,
+    {
+        self.query = self.query.custom(predicate);
         self
     }
     
+    /// Finish building and return the filters as a [`CompiledQuery`] that can be
+    /// run repeatedly, including against other datasets
+    pub fn build(self) -> CompiledQuery<'a> {
+        self.query
+    }
+
     /// Execute the query and return matching providers
     pub fn execute(self) -> Vec<&'a NppesRecord> {
-        #[cfg(feature = "parallel")]
-        {
-            use rayon::prelude::*;
-            self.dataset.providers.par_iter()
-                .filter(|provider| {
-                    self.filters.iter().all(|filter| filter(provider))
-                })
-                .collect()
-        }
-        
-        #[cfg(not(feature = "parallel"))]
-        {
-            self.dataset.providers.iter()
-                .filter(|provider| {
-                    self.filters.iter().all(|filter| filter(provider))
-                })
-                .collect()
+        self.query.run(self.dataset)
+    }
+    
+    /// Execute the query and wrap the matches in a [`QueryResult`] for
+    /// aggregation and export without re-running it
+    pub fn run(self) -> QueryResult<'a> {
+        let dataset = self.dataset;
+        QueryResult {
+            dataset,
+            records: self.execute(),
         }
     }
     
+    /// Execute the query after checking queried taxonomy codes against the reference
+    ///
+    /// When a taxonomy reference is loaded, codes passed to [`taxonomy_code`](Self::taxonomy_code)
+    /// that it doesn't contain are reported instead of silently matching nothing: as
+    /// an error in [strict mode](Self::strict_taxonomy), otherwise as a warning on stderr.
+    pub fn try_execute(self) -> Result<Vec<&'a NppesRecord>> {
+        self.query.try_run(self.dataset)
+    }
+    
     /// Execute the query and return count only
     pub fn count(self) -> usize {
         self.execute().len()
@@ -886,17 +2057,507 @@ impl<'a> QueryBuilder<'a> {
     pub fn limit(self, limit: usize) -> Vec<&'a NppesRecord> {
         let mut results = Vec::new();
         for provider in &self.dataset.providers {
-            if self.filters.iter().all(|filter| filter(provider)) {
+            if self.query.matches(self.dataset, provider) {
                 results.push(provider);
                 if results.len() >= limit {
                     break;
                 }
             }
         }
-        results
+        results
+    }
+}
+
+/// Providers matched by a query, returned by [`QueryBuilder::run`]
+pub struct QueryResult<'a> {
+    dataset: &'a NppesDataset,
+    records: Vec<&'a NppesRecord>,
+}
+
+impl<'a> QueryResult<'a> {
+    /// Number of matching providers
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+    
+    /// Check if nothing matched
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+    
+    /// Iterate over the matching providers
+    pub fn iter(&self) -> impl Iterator<Item = &'a NppesRecord> + '_ {
+        self.records.iter().copied()
+    }
+    
+    /// Take the matching providers
+    pub fn into_vec(self) -> Vec<&'a NppesRecord> {
+        self.records
+    }
+    
+    /// Count matching providers by mailing address state code
+    pub fn count_by_state(&self) -> HashMap<String, usize> {
+        let mut counts = HashMap::new();
+        for provider in &self.records {
+            if let Some(state) = &provider.mailing_address.state {
+                *counts.entry(state.as_code().to_string()).or_insert(0) += 1;
+            }
+        }
+        counts
+    }
+    
+    /// Count matching providers by specialty
+    /// 
+    /// Keys are taxonomy display names when a taxonomy reference is loaded, falling
+    /// back to the taxonomy code. A provider counts once for each of its specialties.
+    pub fn count_by_specialty(&self) -> HashMap<String, usize> {
+        let mut counts = HashMap::new();
+        for provider in &self.records {
+            let specialties: HashSet<String> = provider.taxonomy_codes.iter()
+                .map(|t| {
+                    self.dataset.get_taxonomy_description(&t.code)
+                        .and_then(|r| r.display_name.clone())
+                        .unwrap_or_else(|| t.code.clone())
+                })
+                .collect();
+            for specialty in specialties {
+                *counts.entry(specialty).or_insert(0) += 1;
+            }
+        }
+        counts
+    }
+    
+    /// Owned summaries of the matching providers, e.g. for a JSON response
+    pub fn summaries(&self) -> Vec<ProviderSummary> {
+        self.records.iter().map(|p| p.summary_owned(self.dataset)).collect()
+    }
+    
+    /// Export the matching providers, with their reference data, in the given format
+    pub fn export<P: AsRef<Path>>(&self, path: P, format: ExportFormat) -> Result<()> {
+        let npis: HashSet<&Npi> = self.records.iter().map(|p| &p.npi).collect();
+        self.dataset.subset(|p| npis.contains(&p.npi)).export_as(path.as_ref(), format).map(drop)
+    }
+}
+
+impl<'a> IntoIterator for QueryResult<'a> {
+    type Item = &'a NppesRecord;
+    type IntoIter = std::vec::IntoIter<&'a NppesRecord>;
+    
+    fn into_iter(self) -> Self::IntoIter {
+        self.records.into_iter()
+    }
+}
+
+/// Which of a provider's addresses a location filter checks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum AddressKind {
+    #[default]
+    Mailing,
+    Practice,
+}
+
+impl AddressKind {
+    fn of(self, p: &NppesRecord) -> &Address {
+        match self {
+            AddressKind::Mailing => &p.mailing_address,
+            AddressKind::Practice => &p.practice_address,
+        }
+    }
+}
+
+/// A single filter in a [`CompiledQuery`]
+enum QueryFilter<'f> {
+    State(AddressKind, Option<StateCode>),
+    StateIn(AddressKind, Vec<StateCode>),
+    Country(AddressKind, CountryCode),
+    PostalCode(AddressKind, Option<String>),
+    /// Postal code prefix with hyphens removed
+    PostalCodePrefix(AddressKind, String),
+    /// Trimmed, lowercased city name
+    City(AddressKind, String),
+    /// Trimmed, lowercased part of a city name
+    CityContains(AddressKind, String),
+    SecondaryLocationState(Option<StateCode>),
+    /// Lowercased substring of the taxonomy display name
+    Specialty(String),
+    TaxonomyCode(String),
+    PrimaryTaxonomyCode(String),
+    EntityType(EntityType),
+    ActiveOnly,
+    DeactivatedFor(DeactivationReasonCode),
+    Custom(Box<dyn Fn(&NppesRecord) -> bool + Send + Sync + 'f>),
+}
+
+impl QueryFilter<'_> {
+    fn matches(&self, dataset: &NppesDataset, p: &NppesRecord) -> bool {
+        match self {
+            QueryFilter::State(address, state) => {
+                address.of(p).state.as_ref()
+                    .map(|s| Some(s) == state.as_ref())
+                    .unwrap_or(false)
+            }
+            QueryFilter::StateIn(address, states) => {
+                address.of(p).state.as_ref()
+                    .map(|s| states.iter().any(|se| se == s))
+                    .unwrap_or(false)
+            }
+            QueryFilter::Country(address, country) => address.of(p).country.as_ref() == Some(country),
+            QueryFilter::PostalCode(address, zip5) => zip5.is_some() && address.of(p).zip5() == *zip5,
+            QueryFilter::PostalCodePrefix(address, prefix) => {
+                address.of(p).postal_code.as_deref()
+                    .is_some_and(|code| code.replace('-', "").starts_with(prefix.as_str()))
+            }
+            QueryFilter::City(address, city) => {
+                address.of(p).city.as_deref().is_some_and(|c| c.trim().to_lowercase() == *city)
+            }
+            QueryFilter::CityContains(address, text) => {
+                address.of(p).city.as_deref().is_some_and(|c| c.to_lowercase().contains(text.as_str()))
+            }
+            QueryFilter::SecondaryLocationState(state) => {
+                state.is_some() && dataset.get_practice_locations(&p.npi)
+                    .is_some_and(|locations| locations.iter().any(|l| l.address.state == *state))
+            }
+            QueryFilter::Specialty(specialty_lower) => {
+                p.taxonomy_codes.iter().any(|t| {
+                    if let Some(taxonomy_ref) = dataset.get_taxonomy_description(&t.code) {
+                        taxonomy_ref.display_name.as_ref()
+                            .map(|name| name.to_lowercase().contains(specialty_lower))
+                            .unwrap_or(false)
+                    } else {
+                        false
+                    }
+                })
+            }
+            QueryFilter::TaxonomyCode(code) => p.taxonomy_codes.iter().any(|t| t.code == *code),
+            QueryFilter::PrimaryTaxonomyCode(code) => p.primary_taxonomy().is_some_and(|t| t.code == *code),
+            QueryFilter::EntityType(entity_type) => p.entity_type.as_ref() == Some(entity_type),
+            QueryFilter::ActiveOnly => p.is_active(),
+            QueryFilter::DeactivatedFor(reason) => {
+                !p.is_active() && p.deactivation_reason.as_ref() == Some(reason)
+            }
+            QueryFilter::Custom(predicate) => predicate(p),
+        }
+    }
+}
+
+/// A reusable set of query filters, independent of any dataset
+/// 
+/// Build one with [`QueryBuilder::build`] or [`CompiledQuery::new`] and run it
+/// as often as needed; the filter chain is only constructed once. The lifetime
+/// only bounds [`custom`](Self::custom) predicates, so a query without borrowed
+/// predicates is `CompiledQuery<'static>` and can be stored for the life of a
+/// server.
+/// 
+/// # Example
+/// ```no_run
+/// # use nppes::prelude::*;
+/// use nppes::dataset::CompiledQuery;
+/// 
+/// let query = CompiledQuery::new().state("CA").active_only();
+/// let dataset = NppesDataset::load_standard("./data")?;
+/// let results = query.run(&dataset);
+/// # Ok::<(), nppes::NppesError>(())
+/// ```
+#[derive(Default)]
+pub struct CompiledQuery<'f> {
+    filters: Vec<QueryFilter<'f>>,
+    taxonomy_codes: Vec<String>,
+    strict_taxonomy: bool,
+    /// Address checked by location filters added from now on
+    address: AddressKind,
+}
+
+impl<'f> CompiledQuery<'f> {
+    /// Create an empty query that matches every provider
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Apply the location filters that follow to the practice address
+    /// 
+    /// See [`QueryBuilder::use_practice_address`].
+    pub fn use_practice_address(mut self) -> Self {
+        self.address = AddressKind::Practice;
+        self
+    }
+    
+    /// Apply the location filters that follow to the mailing address (the default)
+    pub fn use_mailing_address(mut self) -> Self {
+        self.address = AddressKind::Mailing;
+        self
+    }
+    
+    /// Filter by state of the selected address (mailing by default)
+    pub fn state(mut self, state: &str) -> Self {
+        self.filters.push(QueryFilter::State(self.address, StateCode::from_code(state)));
+        self
+    }
+    
+    /// Filter by multiple states of the selected address
+    pub fn state_in(mut self, states: &[&str]) -> Self {
+        let state_enums = states.iter().filter_map(|s| StateCode::from_code(s)).collect();
+        self.filters.push(QueryFilter::StateIn(self.address, state_enums));
+        self
+    }
+    
+    /// Filter by country code of the selected address, ignoring case
+    pub fn country(mut self, code: &str) -> Self {
+        self.filters.push(QueryFilter::Country(self.address, CountryCode::from_code(code.trim())));
+        self
+    }
+    
+    /// Filter by postal code of the selected address, compared on ZIP5
+    pub fn postal_code(mut self, postal_code: &str) -> Self {
+        self.filters.push(QueryFilter::PostalCode(self.address, normalize_zip5(postal_code)));
+        self
+    }
+    
+    /// Filter by postal code prefix of the selected address
+    pub fn postal_code_prefix(mut self, prefix: &str) -> Self {
+        self.filters.push(QueryFilter::PostalCodePrefix(self.address, prefix.trim().replace('-', "")));
+        self
+    }
+    
+    /// Filter by city of the selected address, ignoring case and surrounding whitespace
+    pub fn city(mut self, city: &str) -> Self {
+        self.filters.push(QueryFilter::City(self.address, city.trim().to_lowercase()));
+        self
+    }
+    
+    /// Filter to cities of the selected address containing the text, ignoring case
+    pub fn city_contains(mut self, text: &str) -> Self {
+        self.filters.push(QueryFilter::CityContains(self.address, text.trim().to_lowercase()));
+        self
+    }
+    
+    /// Filter to providers with a secondary practice location in a state
+    pub fn secondary_location_state(mut self, state: &str) -> Self {
+        self.filters.push(QueryFilter::SecondaryLocationState(StateCode::from_code(state)));
+        self
+    }
+    
+    /// Filter by specialty (taxonomy display name)
+    pub fn specialty(mut self, specialty: &str) -> Self {
+        self.filters.push(QueryFilter::Specialty(specialty.to_lowercase()));
+        self
+    }
+    
+    /// Filter by exact NUCC taxonomy code, primary or not
+    pub fn taxonomy_code(mut self, taxonomy_code: &str) -> Self {
+        self.taxonomy_codes.push(taxonomy_code.to_string());
+        self.filters.push(QueryFilter::TaxonomyCode(taxonomy_code.to_string()));
+        self
+    }
+    
+    /// Filter by exact NUCC taxonomy code, only when it is the provider's primary taxonomy
+    pub fn primary_taxonomy_code(mut self, taxonomy_code: &str) -> Self {
+        self.taxonomy_codes.push(taxonomy_code.to_string());
+        self.filters.push(QueryFilter::PrimaryTaxonomyCode(taxonomy_code.to_string()));
+        self
+    }
+    
+    /// Treat taxonomy codes missing from the loaded reference as errors in [`try_run`](Self::try_run)
+    pub fn strict_taxonomy(mut self) -> Self {
+        self.strict_taxonomy = true;
+        self
+    }
+    
+    /// Filter by entity type
+    pub fn entity_type(mut self, entity_type: EntityType) -> Self {
+        self.filters.push(QueryFilter::EntityType(entity_type));
+        self
+    }
+    
+    /// Filter by active status
+    pub fn active_only(mut self) -> Self {
+        self.filters.push(QueryFilter::ActiveOnly);
+        self
+    }
+    
+    /// Filter to inactive providers deactivated for a reason
+    /// 
+    /// Reactivated providers don't match, even if their deactivation reason
+    /// does (see [`NppesRecord::is_active`]).
+    pub fn deactivated_for(mut self, reason: DeactivationReasonCode) -> Self {
+        self.filters.push(QueryFilter::DeactivatedFor(reason));
+        self
+    }
+    
+    /// Filter with an arbitrary predicate
+    pub fn custom<F>(mut self, predicate: F) -> Self
+    where
+        F: Fn(&NppesRecord) -> bool + Send + Sync + 'f,
+    {
+        self.filters.push(QueryFilter::Custom(Box::new(predicate)));
+        self
+    }
+    
+    /// Check whether a provider passes every filter
+    pub fn matches(&self, dataset: &NppesDataset, provider: &NppesRecord) -> bool {
+        self.filters.iter().all(|filter| filter.matches(dataset, provider))
+    }
+    
+    /// Positions of the only providers that can match, when an index can narrow the scan
+    /// 
+    /// Uses the first filter with a matching index: a mailing address state or
+    /// country filter with the state or country index, or a taxonomy code filter
+    /// with the taxonomy index. Returned in dataset order, so results match a full scan.
+    fn candidates(&self, dataset: &NppesDataset) -> Option<Vec<usize>> {
+        let lookup = |index: &HashMap<String, Vec<usize>>, key: &str| index.get(key).cloned().unwrap_or_default();
+        let mut positions = self.filters.iter().find_map(|filter| match filter {
+            QueryFilter::State(AddressKind::Mailing, Some(state)) => {
+                dataset.state_index.as_ref().map(|index| lookup(index, state.as_code()))
+            }
+            QueryFilter::StateIn(AddressKind::Mailing, states) => {
+                dataset.state_index.as_ref().map(|index| {
+                    states.iter().flat_map(|state| lookup(index, state.as_code())).collect()
+                })
+            }
+            QueryFilter::Country(AddressKind::Mailing, country) => {
+                dataset.country_index.as_ref().map(|index| lookup(index, country.as_code()))
+            }
+            QueryFilter::TaxonomyCode(code) | QueryFilter::PrimaryTaxonomyCode(code) => {
+                dataset.taxonomy_index.as_ref().map(|index| lookup(index, code))
+            }
+            _ => None,
+        })?;
+        positions.sort_unstable();
+        positions.dedup();
+        Some(positions)
+    }
+    
+    /// Run the query against a dataset and return matching providers
+    /// 
+    /// With indexes built, a mailing state, country or taxonomy code filter
+    /// narrows the scan to the providers its index lists, and the remaining
+    /// filters are only checked against those. The results are the same as a
+    /// full scan.
+    pub fn run<'d>(&self, dataset: &'d NppesDataset) -> Vec<&'d NppesRecord> {
+        if let Some(positions) = self.candidates(dataset) {
+            let candidates = positions.iter().filter_map(|&idx| dataset.providers.get(idx));
+            
+            #[cfg(feature = "parallel")]
+            {
+                use rayon::prelude::*;
+                let candidates: Vec<&NppesRecord> = candidates.collect();
+                return candidates.into_par_iter()
+                    .filter(|provider| self.matches(dataset, provider))
+                    .collect();
+            }
+            
+            #[cfg(not(feature = "parallel"))]
+            return candidates
+                .filter(|provider| self.matches(dataset, provider))
+                .collect();
+        }
+        
+        #[cfg(feature = "parallel")]
+        {
+            use rayon::prelude::*;
+            dataset.providers.par_iter()
+                .filter(|provider| self.matches(dataset, provider))
+                .collect()
+        }
+        
+        #[cfg(not(feature = "parallel"))]
+        {
+            dataset.providers.iter()
+                .filter(|provider| self.matches(dataset, provider))
+                .collect()
+        }
+    }
+    
+    /// Run the query after checking queried taxonomy codes against the dataset's reference
+    /// 
+    /// See [`QueryBuilder::try_execute`].
+    pub fn try_run<'d>(&self, dataset: &'d NppesDataset) -> Result<Vec<&'d NppesRecord>> {
+        if dataset.taxonomy_map.is_some() {
+            for code in &self.taxonomy_codes {
+                if dataset.is_known_taxonomy(code) {
+                    continue;
+                }
+                if self.strict_taxonomy {
+                    return Err(NppesError::DataValidation {
+                        message: format!("Unknown taxonomy code '{}'; it is not in the loaded taxonomy reference", code),
+                        field: Some("taxonomy_code".to_string()),
+                        value: Some(code.to_string()),
+                        context: Default::default(),
+                    });
+                }
+                eprintln!("Warning: taxonomy code '{}' is not in the loaded taxonomy reference", code);
+            }
+        }
+        Ok(self.run(dataset))
+    }
+}
+
+/// A provider with its joined reference data, as returned by [`NppesDataset::full_record`]
+#[derive(Debug, Clone, Copy)]
+pub struct FullProvider<'a> {
+    /// Main provider record
+    pub provider: &'a NppesRecord,
+    /// Other names from the other names reference file
+    pub other_names: &'a [OtherNameRecord],
+    /// Secondary practice locations
+    pub practice_locations: &'a [PracticeLocationRecord],
+    /// Electronic endpoints
+    pub endpoints: &'a [EndpointRecord],
+    /// Taxonomy reference entry for the primary taxonomy code
+    pub primary_specialty: Option<&'a TaxonomyReference>,
+}
+
+impl NppesRecord {
+    /// Build an owned [`ProviderSummary`], resolving the primary specialty with the dataset's taxonomy reference
+    pub fn summary_owned(&self, dataset: &NppesDataset) -> ProviderSummary {
+        ProviderSummary {
+            npi: self.npi.clone(),
+            name: self.display_name(),
+            entity_type: self.entity_type.clone(),
+            primary_specialty: self.primary_taxonomy().map(|taxonomy| {
+                dataset.get_taxonomy_description(&taxonomy.code)
+                    .and_then(|reference| reference.display_name.clone())
+                    .unwrap_or_else(|| taxonomy.code.clone())
+            }),
+            city: self.mailing_address.city.clone(),
+            state: self.mailing_address.state.clone(),
+            active: self.is_active(),
+        }
     }
 }
 
+/// Result of [`NppesDataset::retain_npis_from_file`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NpiFileFilter {
+    /// Number of providers remaining
+    pub retained: usize,
+    /// Lines of the NPI file that weren't valid NPIs, in file order
+    pub invalid_lines: Vec<InvalidNpiLine>,
+}
+
+/// A line of an NPI file that isn't a valid NPI
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidNpiLine {
+    /// 1-based line number
+    pub line_number: usize,
+    /// The line's text, trimmed
+    pub value: String,
+}
+
+/// One (provider, taxonomy) pair, as returned by [`NppesDataset::iter_provider_taxonomy_rows`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProviderTaxonomyRow {
+    pub npi: Npi,
+    /// Person's first and last name, or the organization's legal business name
+    pub name: String,
+    /// Mailing address state
+    pub state: Option<StateCode>,
+    /// Taxonomy code, or `None` for a provider without taxonomy codes
+    pub taxonomy_code: Option<String>,
+    pub is_primary: Option<bool>,
+    /// Display name from the taxonomy reference, if loaded and the code is in it
+    pub taxonomy_display_name: Option<String>,
+}
+
 /// Dataset statistics
 #[derive(Debug, Clone)]
 pub struct DatasetStatistics {
@@ -910,6 +2571,8 @@ pub struct DatasetStatistics {
     pub providers_with_other_names: usize,
     pub providers_with_practice_locations: usize,
     pub providers_with_endpoints: usize,
+    /// Separator used between digit groups when printing counts; `None` prints raw numbers
+    pub thousands_separator: Option<char>,
 }
 
 impl DatasetStatistics {
@@ -926,6 +2589,7 @@ impl DatasetStatistics {
             providers_with_other_names: 0,
             providers_with_practice_locations: 0,
             providers_with_endpoints: 0,
+            thousands_separator: Some(','),
         };
         
         let mut states = std::collections::HashSet::new();
@@ -973,32 +2637,68 @@ impl DatasetStatistics {
     
     /// Print a formatted summary of the statistics
     pub fn print_summary(&self) {
-        println!("=== NPPES Dataset Statistics ===");
-        println!("Total Providers: {}", self.total_providers);
-        println!("  Individual: {} ({:.1}%)", 
-            self.individual_providers,
-            (self.individual_providers as f64 / self.total_providers as f64) * 100.0
-        );
-        println!("  Organization: {} ({:.1}%)",
-            self.organization_providers,
-            (self.organization_providers as f64 / self.total_providers as f64) * 100.0
-        );
-        println!("Active Providers: {} ({:.1}%)",
-            self.active_providers,
-            (self.active_providers as f64 / self.total_providers as f64) * 100.0
-        );
-        println!("States Represented: {}", self.states_represented);
-        println!("Unique Taxonomy Codes: {}", self.unique_taxonomy_codes);
+        print!("{}", self);
+    }
+    
+    /// Write a summary of the statistics to `writer` instead of stdout
+    pub fn print_summary_to(&self, writer: &mut dyn std::io::Write) -> std::io::Result<()> {
+        write!(writer, "{}", self)
+    }
+    
+    /// Use a different digit group separator for printed counts, e.g. `Some('.')`
+    /// for German-style output or `None` for raw numbers
+    pub fn with_thousands_separator(mut self, separator: Option<char>) -> Self {
+        self.thousands_separator = separator;
+        self
+    }
+    
+    /// Share of all providers as a percentage, `0.0` for an empty dataset
+    fn percent(&self, count: usize) -> f64 {
+        if self.total_providers == 0 {
+            0.0
+        } else {
+            (count as f64 / self.total_providers as f64) * 100.0
+        }
+    }
+    
+    /// Format a count using the configured thousands separator
+    fn count(&self, value: usize) -> String {
+        match self.thousands_separator {
+            Some(separator) => group_thousands(value, separator),
+            None => value.to_string(),
+        }
+    }
+}
+
+impl std::fmt::Display for DatasetStatistics {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "=== NPPES Dataset Statistics ===")?;
+        writeln!(f, "Total Providers: {}", self.count(self.total_providers))?;
+        writeln!(f, "  Individual: {} ({:.1}%)", 
+            self.count(self.individual_providers),
+            self.percent(self.individual_providers)
+        )?;
+        writeln!(f, "  Organization: {} ({:.1}%)",
+            self.count(self.organization_providers),
+            self.percent(self.organization_providers)
+        )?;
+        writeln!(f, "Active Providers: {} ({:.1}%)",
+            self.count(self.active_providers),
+            self.percent(self.active_providers)
+        )?;
+        writeln!(f, "States Represented: {}", self.count(self.states_represented))?;
+        writeln!(f, "Unique Taxonomy Codes: {}", self.count(self.unique_taxonomy_codes))?;
         
         if self.providers_with_other_names > 0 {
-            println!("Providers with Other Names: {}", self.providers_with_other_names);
+            writeln!(f, "Providers with Other Names: {}", self.count(self.providers_with_other_names))?;
         }
         if self.providers_with_practice_locations > 0 {
-            println!("Providers with Practice Locations: {}", self.providers_with_practice_locations);
+            writeln!(f, "Providers with Practice Locations: {}", self.count(self.providers_with_practice_locations))?;
         }
         if self.providers_with_endpoints > 0 {
-            println!("Providers with Endpoints: {}", self.providers_with_endpoints);
+            writeln!(f, "Providers with Endpoints: {}", self.count(self.providers_with_endpoints))?;
         }
+        Ok(())
     }
 }
 
@@ -1009,6 +2709,19 @@ fn create_taxonomy_map(records: Vec<TaxonomyReference>) -> HashMap<String, Taxon
         .collect()
 }
 
+/// Format a number with `separator` between groups of three digits
+fn group_thousands(value: usize, separator: char) -> String {
+    let digits = value.to_string();
+    let mut grouped = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            grouped.push(separator);
+        }
+        grouped.push(digit);
+    }
+    grouped
+}
+
 fn create_other_names_map(records: Vec<OtherNameRecord>) -> HashMap<Npi, Vec<OtherNameRecord>> {
     let mut map = HashMap::new();
     for record in records {
@@ -1037,4 +2750,1137 @@ fn create_endpoints_map(records: Vec<EndpointRecord>) -> HashMap<Npi, Vec<Endpoi
             .push(record);
     }
     map
-} 
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn provider(npi: &str, state: StateCode, postal_code: &str) -> NppesRecord {
+        NppesRecord {
+            npi: Npi::new(npi.to_string()).unwrap(),
+            entity_type: Some(EntityType::Individual),
+            mailing_address: Address {
+                state: Some(state),
+                postal_code: Some(postal_code.to_string()),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+    
+    fn dataset(providers: Vec<NppesRecord>) -> NppesDataset {
+        NppesDataset::new(providers, None, None, None, None, None, None, None)
+    }
+    
+    /// Write a main data file with the standard header and the given sparse rows
+    fn write_main_file(path: &Path, rows: &[&[(usize, &str)]]) {
+        let mut writer = csv::Writer::from_path(path).unwrap();
+        writer.write_record(crate::schema::NppesMainSchema::column_names()).unwrap();
+        for fields in rows {
+            let mut columns = vec![""; crate::schema::NppesMainSchema::column_count()];
+            for &(index, value) in fields.iter() {
+                columns[index] = value;
+            }
+            writer.write_record(&columns).unwrap();
+        }
+        writer.flush().unwrap();
+    }
+    
+    #[test]
+    fn test_normalize_zip5() {
+        assert_eq!(normalize_zip5("94103").as_deref(), Some("94103"));
+        assert_eq!(normalize_zip5("941031234").as_deref(), Some("94103"));
+        assert_eq!(normalize_zip5("94103-1234").as_deref(), Some("94103"));
+        assert_eq!(normalize_zip5("9410"), None);
+        assert_eq!(normalize_zip5("SW1A 1AA"), None);
+    }
+    
+    #[test]
+    fn test_postal_code_query_matches_zip9() {
+        let dataset = dataset(vec![
+            provider("1234567893", StateCode::CA, "941031234"),
+            provider("1245319599", StateCode::CA, "94103"),
+            provider("1003000126", StateCode::CA, "94110"),
+        ]);
+        
+        let results = dataset.query().postal_code("94103").execute();
+        assert_eq!(results.len(), 2);
+        
+        let results = dataset.query().postal_code("94103-1234").execute();
+        assert_eq!(results.len(), 2);
+    }
+    
+    #[test]
+    fn test_postal_code_prefix_query() {
+        let mut no_zip = provider("1003000126", StateCode::MA, "");
+        no_zip.mailing_address.postal_code = None;
+        let dataset = dataset(vec![
+            provider("1234567893", StateCode::MA, "021151234"),
+            provider("1245319599", StateCode::MA, "02116"),
+            no_zip,
+        ]);
+        let npis = |prefix: &str| dataset.query().postal_code_prefix(prefix).execute()
+            .iter()
+            .map(|p| p.npi.as_str().to_string())
+            .collect::<Vec<_>>();
+        
+        assert_eq!(npis("021"), ["1234567893", "1245319599"]);
+        assert_eq!(npis("02116"), ["1245319599"]);
+        assert_eq!(npis("02115-12"), ["1234567893"]);
+        assert_eq!(npis("021151234"), ["1234567893"]);
+        assert!(npis("21").is_empty());
+        assert!(npis("9").is_empty());
+    }
+    
+    #[test]
+    fn test_city_filters_ignore_case_and_whitespace() {
+        let in_city = |npi: &str, city: Option<&str>| NppesRecord {
+            mailing_address: Address {
+                city: city.map(str::to_string),
+                ..Default::default()
+            },
+            ..provider(npi, StateCode::CA, "94103")
+        };
+        let dataset = dataset(vec![
+            in_city("1234567893", Some("SAN FRANCISCO ")),
+            in_city("1245319599", Some("South San Francisco")),
+            in_city("1003000126", None),
+        ]);
+        let npis = |results: Vec<&NppesRecord>| results.iter().map(|p| p.npi.as_str().to_string()).collect::<Vec<_>>();
+        
+        assert_eq!(npis(dataset.query().city("san francisco").execute()), ["1234567893"]);
+        assert_eq!(npis(dataset.query().city("  San Francisco").execute()), ["1234567893"]);
+        assert_eq!(npis(dataset.query().city_contains("FRANCISCO").execute()), ["1234567893", "1245319599"]);
+        assert!(dataset.query().city("Oakland").execute().is_empty());
+    }
+    
+    #[test]
+    fn test_provider_summaries_resolve_specialty() {
+        let mut record = provider("1234567893", StateCode::CA, "94103");
+        record.provider_name = ProviderName { first: Some("JANE".to_string()), last: Some("DOE".to_string()), ..Default::default() };
+        record.mailing_address.city = Some("SAN FRANCISCO".to_string());
+        record.taxonomy_codes = vec![
+            TaxonomyCode { code: "363L00000X".to_string(), ..Default::default() },
+            TaxonomyCode { code: "207Q00000X".to_string(), is_primary: true, ..Default::default() },
+        ];
+        let mut unknown = provider("1245319599", StateCode::NY, "10001");
+        unknown.taxonomy_codes = vec![TaxonomyCode { code: "999X00000X".to_string(), is_primary: true, ..Default::default() }];
+        unknown.deactivation_date = NaiveDate::from_ymd_opt(2020, 1, 1);
+        let taxonomy_map = create_taxonomy_map(vec![TaxonomyReference {
+            code: "207Q00000X".to_string(),
+            display_name: Some("Family Medicine Physician".to_string()),
+            ..Default::default()
+        }]);
+        let data = NppesDataset::new(vec![record, unknown], Some(taxonomy_map), None, None, None, None, None, None);
+        
+        let summaries = data.query().run().summaries();
+        assert_eq!(summaries[0], ProviderSummary {
+            npi: Npi::new("1234567893".to_string()).unwrap(),
+            name: "JANE DOE".to_string(),
+            entity_type: Some(EntityType::Individual),
+            primary_specialty: Some("Family Medicine Physician".to_string()),
+            city: Some("SAN FRANCISCO".to_string()),
+            state: Some(StateCode::CA),
+            active: true,
+        });
+        assert_eq!(summaries[1].primary_specialty.as_deref(), Some("999X00000X"));
+        assert!(!summaries[1].active);
+        
+        let json = serde_json::to_value(&summaries[0]).unwrap();
+        assert_eq!(json["npi"], "1234567893");
+    }
+    
+    #[test]
+    fn test_location_filters_on_practice_address() {
+        let mut commuter = provider("1234567893", StateCode::NJ, "07030");
+        commuter.practice_address = Address {
+            state: Some(StateCode::NY),
+            city: Some("NEW YORK".to_string()),
+            postal_code: Some("100011234".to_string()),
+            ..Default::default()
+        };
+        let local = provider("1245319599", StateCode::NY, "10001");
+        let dataset = dataset(vec![commuter, local]);
+        let npis = |results: Vec<&NppesRecord>| results.iter().map(|p| p.npi.as_str().to_string()).collect::<Vec<_>>();
+        
+        assert_eq!(npis(dataset.query().state("NY").execute()), ["1245319599"]);
+        assert_eq!(npis(dataset.query().use_practice_address().state("NY").execute()), ["1234567893"]);
+        assert_eq!(npis(dataset.query().use_practice_address().city("new york").postal_code("10001").execute()), ["1234567893"]);
+        
+        // Each filter keeps the address selected when it was added
+        let query = dataset.query().state("NJ").use_practice_address().state_in(&["NY"]);
+        assert_eq!(npis(query.execute()), ["1234567893"]);
+        assert_eq!(dataset.query().use_practice_address().use_mailing_address().state("NJ").execute().len(), 1);
+    }
+    
+    #[test]
+    fn test_taxonomy_code_filters_primary_and_secondary() {
+        let with_taxonomies = |npi: &str, codes: &[(&str, bool)]| NppesRecord {
+            taxonomy_codes: codes.iter()
+                .map(|&(code, is_primary)| TaxonomyCode { code: code.to_string(), is_primary, ..Default::default() })
+                .collect(),
+            ..provider(npi, StateCode::CA, "94103")
+        };
+        let mut dataset = dataset(vec![
+            with_taxonomies("1234567893", &[("207Q00000X", true), ("363L00000X", false)]),
+            with_taxonomies("1245319599", &[("363L00000X", true)]),
+            with_taxonomies("1003000126", &[("207R00000X", true)]),
+        ]);
+        let npis = |results: Vec<&NppesRecord>| results.iter().map(|p| p.npi.as_str().to_string()).collect::<Vec<_>>();
+        
+        for indexed in [false, true] {
+            if indexed {
+                dataset.build_indexes();
+            }
+            assert_eq!(npis(dataset.query().taxonomy_code("363L00000X").execute()), ["1234567893", "1245319599"]);
+            assert_eq!(npis(dataset.query().primary_taxonomy_code("363L00000X").execute()), ["1245319599"]);
+            assert_eq!(npis(dataset.query().primary_taxonomy_code("207Q00000X").execute()), ["1234567893"]);
+            assert!(dataset.query().taxonomy_code("999X00000X").execute().is_empty());
+        }
+    }
+    
+    #[test]
+    fn test_get_by_other_identifier() {
+        let with_ids = |npi: &str, ids: &[&str]| NppesRecord {
+            other_identifiers: ids.iter()
+                .map(|id| OtherIdentifier {
+                    identifier: id.to_string(),
+                    type_code: Some("05".to_string()),
+                    issuer: None,
+                    state: None,
+                })
+                .collect(),
+            ..provider(npi, StateCode::CA, "90210")
+        };
+        let mut data = dataset(vec![
+            with_ids("1234567893", &["LEGACY-1", "LEGACY-1"]),
+            with_ids("1245319599", &["OTHER"]),
+            with_ids("1003000126", &[" LEGACY-1 "]),
+        ]);
+        
+        for indexed in [false, true] {
+            if indexed {
+                data.build_indexes();
+            }
+            let npis: Vec<&str> = data.get_by_other_identifier("LEGACY-1").iter().map(|p| p.npi.as_str()).collect();
+            assert_eq!(npis, ["1234567893", "1003000126"], "indexed: {}", indexed);
+            assert_eq!(data.get_by_other_identifier("OTHER").len(), 1);
+            assert!(data.get_by_other_identifier("MISSING").is_empty());
+        }
+    }
+    
+    #[test]
+    fn test_save_and_load_indexes() {
+        let providers = || {
+            let mut individual = provider("1234567893", StateCode::CA, "90210");
+            individual.enumeration_date = NaiveDate::from_ymd_opt(2010, 5, 17);
+            individual.taxonomy_codes.push(TaxonomyCode { code: "207Q00000X".to_string(), is_primary: true, ..Default::default() });
+            vec![individual, provider("1245319599", StateCode::NY, "10001")]
+        };
+        let mut indexed = dataset(providers());
+        indexed.build_indexes();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("providers.idx");
+        indexed.save_indexes(&path).unwrap();
+        
+        let mut fresh = dataset(providers());
+        fresh.build_address_index();
+        fresh.load_indexes(&path).unwrap();
+        assert!(fresh.npi_index.is_some());
+        // The address index isn't in the file, so one built earlier can't be trusted
+        assert!(fresh.address_index.is_none());
+        let npi = Npi::new("1245319599".to_string()).unwrap();
+        assert_eq!(fresh.get_by_npi(&npi).map(|p| &p.npi), Some(&npi));
+        assert_eq!(fresh.get_by_state("CA").len(), 1);
+        assert_eq!(fresh.get_by_taxonomy("207Q00000X").len(), 1);
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        assert_eq!(fresh.enumerated_between(date(2010, 1, 1), date(2010, 12, 31)).len(), 1);
+        
+        // Stale indexes for a different provider count are rejected
+        let mut grown = dataset(providers());
+        grown.providers.push(provider("1003000126", StateCode::TX, "73301"));
+        assert!(grown.load_indexes(&path).is_err());
+        assert!(grown.npi_index.is_none());
+        
+        std::fs::write(&path, b"not an index file").unwrap();
+        assert!(fresh.load_indexes(&path).is_err());
+    }
+    
+    #[test]
+    fn test_providers_with_endpoint_type() {
+        let endpoint = |npi: &str, endpoint_type: Option<&str>| EndpointRecord {
+            npi: Npi::new(npi.to_string()).unwrap(),
+            endpoint_type: endpoint_type.map(str::to_string),
+            endpoint_type_description: None,
+            endpoint: Some(format!("https://{}.example.org", npi)),
+            affiliation: None,
+            endpoint_description: None,
+            affiliation_legal_business_name: None,
+            use_code: None,
+            use_description: None,
+            other_use_description: None,
+            content_type: None,
+            content_description: None,
+            other_content_description: None,
+            affiliation_address: None,
+        };
+        let endpoints = vec![
+            endpoint("1234567893", Some("FHIR")),
+            endpoint("1234567893", Some("FHIR")),
+            endpoint("1234567893", Some("DIRECT")),
+            endpoint("1245319599", Some("DIRECT")),
+            endpoint("1003000126", Some("fhir ")),
+            endpoint("1003000126", None),
+        ];
+        let providers = vec![
+            provider("1003000126", StateCode::TX, "73301"),
+            provider("1234567893", StateCode::CA, "90210"),
+            provider("1245319599", StateCode::CA, "90211"),
+            provider("1538144910", StateCode::NY, "10001"),
+        ];
+        
+        assert!(dataset(providers.clone()).get_providers_with_endpoint_type("FHIR").is_empty());
+        let mut with_endpoints = dataset(providers.clone());
+        with_endpoints.endpoints_map = Some(create_endpoints_map(endpoints.clone()));
+        let npis = |endpoint_type: &str| -> Vec<&str> {
+            with_endpoints.get_providers_with_endpoint_type(endpoint_type).iter().map(|p| p.npi.as_str()).collect()
+        };
+        assert_eq!(npis("FHIR"), ["1003000126", "1234567893"]);
+        assert_eq!(npis("direct"), ["1234567893", "1245319599"]);
+        assert!(npis("SOAP").is_empty());
+        
+        let distribution = crate::analytics::NppesAnalytics::new(&providers)
+            .with_endpoints(&endpoints)
+            .endpoint_type_distribution();
+        assert_eq!(distribution.len(), 2);
+        assert_eq!(distribution["FHIR"], 2);
+        assert_eq!(distribution["DIRECT"], 2);
+    }
+    
+    #[test]
+    fn test_get_by_country_separates_foreign_providers() {
+        let located = |npi: &str, state: StateCode, postal_code: &str, country: &str| {
+            let mut record = provider(npi, state, postal_code);
+            record.mailing_address.country = Some(CountryCode::from_code(country));
+            record
+        };
+        let mut data = dataset(vec![
+            located("1234567893", StateCode::ZZ, "M5V 2T6", "CA"),
+            located("1245319599", StateCode::CA, "90210", "US"),
+            provider("1003000126", StateCode::NY, "10001"),
+        ]);
+        
+        for indexed in [false, true] {
+            if indexed {
+                data.build_indexes();
+            }
+            let canadian: Vec<&str> = data.get_by_country("ca").iter().map(|p| p.npi.as_str()).collect();
+            assert_eq!(canadian, ["1234567893"]);
+            let american: Vec<&str> = data.get_by_country("US").iter().map(|p| p.npi.as_str()).collect();
+            assert_eq!(american, ["1245319599"]);
+            assert!(data.get_by_country("MX").is_empty());
+            
+            // The country filter doesn't confuse Canada with California
+            assert_eq!(data.query().country("CA").count(), 1);
+            assert_eq!(data.query().state("CA").execute()[0].npi.as_str(), "1245319599");
+            assert_eq!(data.query().use_practice_address().country("CA").count(), 0);
+        }
+    }
+    
+    #[test]
+    fn test_merge_applies_updates_by_npi() {
+        let dated = |npi: &str, state: StateCode, date: Option<(i32, u32, u32)>| NppesRecord {
+            last_update_date: date.and_then(|(y, m, d)| NaiveDate::from_ymd_opt(y, m, d)),
+            ..provider(npi, state, "90210")
+        };
+        let other_name = |npi: &str, name: &str| OtherNameRecord {
+            npi: Npi::new(npi.to_string()).unwrap(),
+            provider_other_organization_name: name.to_string(),
+            provider_other_organization_name_type_code: None,
+        };
+        let names = |records: Vec<OtherNameRecord>| -> HashMap<Npi, Vec<OtherNameRecord>> {
+            records.into_iter().map(|r| (r.npi.clone(), vec![r])).collect()
+        };
+        
+        let mut base = NppesDataset::new(
+            vec![
+                dated("1234567893", StateCode::CA, Some((2024, 1, 1))),
+                dated("1245319599", StateCode::CA, Some((2024, 3, 1))),
+            ],
+            None,
+            Some(names(vec![other_name("1234567893", "OLD NAME"), other_name("1245319599", "KEPT NAME")])),
+            None, None, None, None, None,
+        );
+        let update = NppesDataset::new(
+            vec![
+                dated("1234567893", StateCode::NY, Some((2024, 1, 1))),
+                dated("1245319599", StateCode::NY, Some((2024, 2, 1))),
+                dated("1003000126", StateCode::TX, None),
+            ],
+            None,
+            Some(names(vec![other_name("1234567893", "NEW NAME"), other_name("1245319599", "STALE NAME")])),
+            None, None, None, None, None,
+        );
+        
+        base.merge(update);
+        
+        let state_of = |npi: &str| base.get_by_npi(&Npi::new(npi.to_string()).unwrap())
+            .and_then(|p| p.mailing_address.state.clone());
+        assert_eq!(base.len(), 3);
+        // Equal dates: the update wins
+        assert_eq!(state_of("1234567893"), Some(StateCode::NY));
+        // Base record is newer: kept
+        assert_eq!(state_of("1245319599"), Some(StateCode::CA));
+        // New NPI appended
+        assert_eq!(state_of("1003000126"), Some(StateCode::TX));
+        assert_eq!(base.providers[2].npi.as_str(), "1003000126");
+        
+        let other_names = base.other_names_map.as_ref().unwrap();
+        let name_of = |npi: &str| other_names[&Npi::new(npi.to_string()).unwrap()][0]
+            .provider_other_organization_name.as_str();
+        assert_eq!(name_of("1234567893"), "NEW NAME");
+        assert_eq!(name_of("1245319599"), "KEPT NAME");
+        
+        // Indexes reflect the merged providers
+        assert_eq!(base.get_by_state("NY").len(), 1);
+        assert_eq!(base.get_by_state("CA").len(), 1);
+        assert_eq!(base.get_by_state("TX").len(), 1);
+    }
+    
+    #[test]
+    fn test_merge_drops_endpoints_missing_from_update() {
+        let endpoint = |npi: &str| EndpointRecord {
+            npi: Npi::new(npi.to_string()).unwrap(),
+            endpoint_type: Some("DIRECT".to_string()),
+            endpoint_type_description: None,
+            endpoint: Some(format!("{}@direct.example.org", npi)),
+            affiliation: None,
+            endpoint_description: None,
+            affiliation_legal_business_name: None,
+            use_code: None,
+            use_description: None,
+            other_use_description: None,
+            content_type: None,
+            content_description: None,
+            other_content_description: None,
+            affiliation_address: None,
+        };
+        let endpoints = |npis: &[&str]| -> HashMap<Npi, Vec<EndpointRecord>> {
+            npis.iter().map(|npi| (Npi::new(npi.to_string()).unwrap(), vec![endpoint(npi)])).collect()
+        };
+        let mut base = NppesDataset::new(
+            vec![provider("1234567893", StateCode::CA, "90210"), provider("1245319599", StateCode::CA, "90210")],
+            None, None, None,
+            Some(endpoints(&["1234567893", "1245319599"])),
+            None, None, None,
+        );
+        // The update replaces the first provider and no longer lists its endpoint
+        let update = NppesDataset::new(
+            vec![provider("1234567893", StateCode::NY, "10001")],
+            None, None, None,
+            Some(endpoints(&[])),
+            None, None, None,
+        );
+        
+        base.merge(update);
+        
+        assert!(base.get_endpoints(&Npi::new("1234567893".to_string()).unwrap()).is_none());
+        assert_eq!(base.get_endpoints(&Npi::new("1245319599".to_string()).unwrap()).map(Vec::len), Some(1));
+    }
+
+    #[test]
+    fn test_indexed_queries_match_full_scan() {
+        let states = [StateCode::CA, StateCode::NY, StateCode::TX];
+        let npis = ["1234567893", "1245319599", "1003000126"];
+        let providers = (0..30)
+            .map(|i| NppesRecord {
+                deactivation_date: (i % 4 == 0).then(|| NaiveDate::from_ymd_opt(2020, 1, 1).unwrap()),
+                taxonomy_codes: vec![TaxonomyCode { code: ["207Q00000X", "363L00000X"][i % 2].to_string(), is_primary: true, ..Default::default() }],
+                practice_address: Address { state: Some(states[(i + 1) % 3].clone()), ..Default::default() },
+                ..provider(npis[i % 3], states[i % 3].clone(), "00000")
+            })
+            .collect();
+        let mut data = dataset(providers);
+        let queries = || vec![
+            CompiledQuery::new().state("CA").active_only(),
+            CompiledQuery::new().active_only().state_in(&["NY", "TX"]),
+            CompiledQuery::new().taxonomy_code("363L00000X").state("TX"),
+            CompiledQuery::new().use_practice_address().state("CA"),
+            CompiledQuery::new().state("ZZ"),
+        ];
+        let brute_force: Vec<Vec<*const NppesRecord>> = queries().iter()
+            .map(|query| data.providers.iter().filter(|p| query.matches(&data, p)).map(|p| p as *const _).collect())
+            .collect();
+        
+        data.build_indexes();
+        for (query, expected) in queries().iter().zip(&brute_force) {
+            let results: Vec<*const NppesRecord> = query.run(&data).into_iter().map(|p| p as *const _).collect();
+            assert_eq!(&results, expected);
+        }
+        assert_eq!(brute_force[0].len(), 7);
+    }
+    
+    #[test]
+    fn test_organization_other_names_are_merged_and_deduplicated() {
+        let npi = Npi::new("1245319599".to_string()).unwrap();
+        let org = NppesRecord {
+            npi: npi.clone(),
+            entity_type: Some(EntityType::Organization),
+            organization_name: OrganizationName {
+                legal_business_name: Some("ACME HEALTH LLC".to_string()),
+                other_name: Some("ACME CLINIC".to_string()),
+                other_name_type: Some(OtherProviderNameTypeCode::DoingBusinessAs),
+            },
+            ..Default::default()
+        };
+        let other_names = vec![
+            OtherNameRecord {
+                npi: npi.clone(),
+                provider_other_organization_name: "Acme Clinic".to_string(),
+                provider_other_organization_name_type_code: Some("3".to_string()),
+            },
+            OtherNameRecord {
+                npi: npi.clone(),
+                provider_other_organization_name: "ACME MEDICAL GROUP".to_string(),
+                provider_other_organization_name_type_code: Some("4".to_string()),
+            },
+        ];
+        let dataset = NppesDataset::new(
+            vec![org], None, Some(HashMap::from([(npi.clone(), other_names)])), None, None, None, None, None,
+        );
+        
+        let record = dataset.get_by_npi(&npi).unwrap();
+        let names = record.organization_other_names(dataset.get_other_names(&npi).map_or(&[], |v| v));
+        assert_eq!(names, vec![
+            ("ACME CLINIC", Some(OtherProviderNameTypeCode::DoingBusinessAs)),
+            ("ACME MEDICAL GROUP", Some(OtherProviderNameTypeCode::FormerLegalBusinessName)),
+        ]);
+    }
+    
+    #[test]
+    fn test_query_deactivated_for_reason() {
+        let date = |y, m, d| chrono::NaiveDate::from_ymd_opt(y, m, d);
+        let deactivated = |npi: &str, reason: DeactivationReasonCode| NppesRecord {
+            deactivation_date: date(2022, 3, 1),
+            deactivation_reason: Some(reason),
+            ..provider(npi, StateCode::CA, "94103")
+        };
+        let reactivated = NppesRecord {
+            reactivation_date: date(2023, 1, 1),
+            ..deactivated("1538144910", DeactivationReasonCode::Fraud)
+        };
+        let data = dataset(vec![
+            provider("1234567893", StateCode::CA, "94103"),
+            deactivated("1245319599", DeactivationReasonCode::Death),
+            deactivated("1003000126", DeactivationReasonCode::Fraud),
+            reactivated,
+        ]);
+        let npis = |reason: DeactivationReasonCode| -> Vec<&str> {
+            data.query().deactivated_for(reason).execute().iter().map(|p| p.npi.as_str()).collect()
+        };
+        
+        assert_eq!(npis(DeactivationReasonCode::Death), ["1245319599"]);
+        assert_eq!(npis(DeactivationReasonCode::Fraud), ["1003000126"]);
+        assert!(npis(DeactivationReasonCode::Disbandment).is_empty());
+        assert_eq!(data.query().active_only().deactivated_for(DeactivationReasonCode::Fraud).count(), 0);
+    }
+    
+    #[test]
+    fn test_active_snapshot_keeps_reactivated_providers() {
+        let date = |y, m, d| chrono::NaiveDate::from_ymd_opt(y, m, d);
+        let mut deactivated = provider("1245319599", StateCode::CA, "94103");
+        deactivated.deactivation_date = date(2020, 1, 1);
+        let mut reactivated = provider("1003000126", StateCode::CA, "94103");
+        reactivated.deactivation_date = date(2020, 1, 1);
+        reactivated.reactivation_date = date(2021, 6, 1);
+        
+        let mut full = dataset(vec![provider("1234567893", StateCode::CA, "94103"), deactivated, reactivated]);
+        full.build_indexes();
+        let snapshot = full.active_snapshot();
+        
+        assert_eq!(full.len(), 3);
+        assert_eq!(snapshot.len(), 2);
+        assert!(snapshot.get_by_npi(&Npi::new("1245319599".to_string()).unwrap()).is_none());
+        assert_eq!(snapshot.get_by_state("CA").len(), 2);
+    }
+    
+    #[test]
+    fn test_taxonomy_changes_lists_only_changed_providers() {
+        let with_codes = |npi: &str, codes: &[&str]| NppesRecord {
+            taxonomy_codes: codes.iter()
+                .map(|code| TaxonomyCode { code: code.to_string(), ..Default::default() })
+                .collect(),
+            ..provider(npi, StateCode::CA, "94103")
+        };
+        let before = dataset(vec![
+            with_codes("1234567893", &["207Q00000X"]),
+            with_codes("1245319599", &["207R00000X", "207RC0000X"]),
+            with_codes("1003000126", &["363L00000X"]),
+        ]);
+        let after = dataset(vec![
+            with_codes("1234567893", &["207Q00000X"]),
+            with_codes("1245319599", &["207RC0000X", "207R00000X"]),
+            with_codes("1003000126", &["363L00000X", "207Q00000X"]),
+        ]);
+        
+        let changes = before.taxonomy_changes(&after);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].0.as_str(), "1003000126");
+        assert_eq!(changes[0].1, vec!["363L00000X"]);
+        assert_eq!(changes[0].2, vec!["207Q00000X", "363L00000X"]);
+    }
+    
+    #[test]
+    fn test_add_main_data_concatenates_and_deduplicates() {
+        let dir = tempfile::tempdir().unwrap();
+        let (first, second) = (dir.path().join("first.csv"), dir.path().join("second.csv"));
+        write_main_file(&first, &[
+            &[(0, "1234567893"), (1, "1"), (23, "CA")],
+            &[(0, "1245319599"), (1, "1"), (23, "CA")],
+        ]);
+        write_main_file(&second, &[
+            &[(0, "1245319599"), (1, "1"), (23, "NY")],
+            &[(0, "1003000126"), (1, "1"), (23, "TX")],
+        ]);
+        
+        let dataset = NppesDatasetBuilder::new()
+            .add_main_data(first.as_path())
+            .add_main_data(second.as_path())
+            .build()
+            .unwrap();
+        
+        assert_eq!(dataset.len(), 3);
+        let replaced = dataset.get_by_npi(&Npi::new("1245319599".to_string()).unwrap()).unwrap();
+        assert_eq!(replaced.mailing_address.state, Some(StateCode::NY));
+    }
+    
+    #[test]
+    fn test_shrink_to_fit_releases_filtered_capacity() {
+        let mut dataset = dataset(vec![provider("1234567893", StateCode::CA, "90210"); 100]);
+        dataset.providers.truncate(1);
+        let before = dataset.memory_footprint();
+        
+        dataset.shrink_to_fit();
+        assert_eq!(dataset.providers.capacity(), 1);
+        assert!(dataset.memory_footprint() < before);
+        assert!(dataset.memory_footprint() >= size_of::<NppesRecord>());
+    }
+    
+    #[test]
+    fn test_statistics_summary_groups_thousands() {
+        assert_eq!(group_thousands(8234567, ','), "8,234,567");
+        assert_eq!(group_thousands(999, ','), "999");
+        assert_eq!(group_thousands(1000, '.'), "1.000");
+        
+        let mut stats = dataset(vec![provider("1234567893", StateCode::CA, "90210")]).statistics();
+        stats.total_providers = 8234567;
+        assert!(stats.to_string().contains("Total Providers: 8,234,567\n"));
+        
+        let mut output = Vec::new();
+        stats.with_thousands_separator(None).print_summary_to(&mut output).unwrap();
+        assert!(String::from_utf8(output).unwrap().contains("Total Providers: 8234567\n"));
+    }
+    
+    #[test]
+    fn test_strict_taxonomy_rejects_unknown_codes() {
+        let mut record = provider("1234567893", StateCode::CA, "90210");
+        record.taxonomy_codes.push(TaxonomyCode { code: "207Q00000X".to_string(), ..Default::default() });
+        let mut dataset = dataset(vec![record]);
+        dataset.taxonomy_map = Some(HashMap::from([(
+            "207Q00000X".to_string(),
+            TaxonomyReference {
+                code: "207Q00000X".to_string(),
+                grouping: None,
+                classification: Some("Family Medicine".to_string()),
+                specialization: None,
+                definition: None,
+                notes: None,
+                display_name: None,
+                section: None,
+            },
+        )]));
+        
+        assert!(dataset.is_known_taxonomy("207Q00000X"));
+        assert!(!dataset.is_known_taxonomy("207Q0000XX"));
+        
+        let results = dataset.query().taxonomy_code("207Q00000X").strict_taxonomy().try_execute().unwrap();
+        assert_eq!(results.len(), 1);
+        assert!(dataset.query().taxonomy_code("207Q0000XX").try_execute().unwrap().is_empty());
+        
+        let err = dataset.query().taxonomy_code("207Q0000XX").strict_taxonomy().try_execute().unwrap_err();
+        assert!(matches!(err, NppesError::DataValidation { value: Some(ref v), .. } if v == "207Q0000XX"));
+    }
+    
+    #[test]
+    fn test_builder_loads_all_auxiliary_files() {
+        use crate::schema::{EndpointSchema, OtherNameSchema, PracticeLocationSchema, TaxonomySchema};
+        
+        let write_csv = |path: &Path, header: Vec<&str>, rows: &[&[(usize, &str)]]| {
+            let mut writer = csv::Writer::from_path(path).unwrap();
+            writer.write_record(&header).unwrap();
+            for fields in rows {
+                let mut row = vec![""; header.len()];
+                for &(idx, value) in *fields {
+                    row[idx] = value;
+                }
+                writer.write_record(&row).unwrap();
+            }
+            writer.flush().unwrap();
+        };
+        let dir = tempfile::tempdir().unwrap();
+        let path = |name: &str| dir.path().join(name);
+        write_main_file(&path("main.csv"), &[
+            &[(0, "1234567893"), (1, "1"), (5, "SMITH")],
+            &[(0, "1245319599"), (1, "2"), (4, "ACME CLINIC")],
+        ]);
+        write_csv(&path("taxonomy.csv"), TaxonomySchema::column_names(), &[
+            &[(0, "207Q00000X"), (2, "Family Medicine")],
+            &[(0, "261QP2300X"), (2, "Clinic/Center")],
+        ]);
+        write_csv(&path("othername.csv"), OtherNameSchema::column_names(), &[
+            &[(0, "1245319599"), (1, "ACME"), (2, "3")],
+        ]);
+        write_csv(&path("pl.csv"), PracticeLocationSchema::column_names(), &[
+            &[(0, "1234567893"), (1, "1 MAIN ST"), (3, "RENO"), (4, "NV"), (5, "89501")],
+            &[(0, "1234567893"), (1, "2 OAK AVE"), (3, "ELKO"), (4, "NV"), (5, "89801")],
+        ]);
+        write_csv(&path("endpoint.csv"), EndpointSchema::column_names(), &[
+            &[(0, "1245319599"), (1, "DIRECT"), (3, "clinic@direct.example.org")],
+        ]);
+        
+        let builder = || NppesDatasetBuilder::new()
+            .main_data(path("main.csv"))
+            .other_names(path("othername.csv"))
+            .practice_locations(path("pl.csv"))
+            .endpoints(path("endpoint.csv"));
+        let dataset = builder().taxonomy_reference(path("taxonomy.csv")).build().unwrap();
+        
+        // Same maps as loading each file in turn
+        let reader = NppesReader::new();
+        assert_eq!(dataset.taxonomy_map, Some(create_taxonomy_map(reader.load_taxonomy_data(path("taxonomy.csv")).unwrap())));
+        assert_eq!(dataset.other_names_map, Some(create_other_names_map(reader.load_other_name_data(path("othername.csv")).unwrap())));
+        assert_eq!(
+            dataset.practice_locations_map,
+            Some(create_practice_locations_map(reader.load_practice_location_data(path("pl.csv")).unwrap()))
+        );
+        assert_eq!(dataset.endpoints_map, Some(create_endpoints_map(reader.load_endpoint_data(path("endpoint.csv")).unwrap())));
+        assert_eq!(dataset.taxonomy_map.as_ref().unwrap().len(), 2);
+        assert_eq!(dataset.get_practice_locations(&Npi::new("1234567893".to_string()).unwrap()).unwrap().len(), 2);
+        
+        // Progress bars load the files one at a time, with the same result
+        #[cfg(feature = "progress")]
+        {
+            let shown = builder().taxonomy_reference(path("taxonomy.csv")).show_progress(false).build().unwrap();
+            assert_eq!(shown.taxonomy_map, dataset.taxonomy_map);
+            assert_eq!(shown.endpoints_map, dataset.endpoints_map);
+        }
+        
+        // A bad auxiliary file still fails the build
+        std::fs::write(path("bad_taxonomy.csv"), "not,a,taxonomy,header\n").unwrap();
+        assert!(builder().taxonomy_reference(path("bad_taxonomy.csv")).build().is_err());
+    }
+    
+    #[test]
+    fn test_builder_validation_level() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("main.csv");
+        write_main_file(&path, &[&[(0, "1234567893"), (1, "9"), (5, "SMITH")]]);
+        let builder = || NppesDatasetBuilder::new().main_data(path.clone()).build_indexes(false);
+        
+        assert!(builder().validation_level(ValidationLevel::Basic).build().is_err());
+        let dataset = builder().validation_level(ValidationLevel::None).build().unwrap();
+        assert_eq!(dataset.providers[0].entity_type, None);
+    }
+    
+    #[test]
+    fn test_strict_cross_file_rejects_orphaned_side_records() {
+        let dir = tempfile::tempdir().unwrap();
+        let (main, other_names) = (dir.path().join("main.csv"), dir.path().join("othername.csv"));
+        write_main_file(&main, &[&[(0, "1234567893"), (1, "2"), (4, "ACME CLINIC")]]);
+        let mut writer = csv::Writer::from_path(&other_names).unwrap();
+        writer.write_record(crate::schema::OtherNameSchema::column_names()).unwrap();
+        writer.write_record(["1234567893", "ACME", "3"]).unwrap();
+        writer.write_record(["1245319599", "OTHER CLINIC", "3"]).unwrap();
+        writer.flush().unwrap();
+        
+        let dataset = NppesDatasetBuilder::new()
+            .main_data(main.as_path())
+            .other_names(other_names.as_path())
+            .build()
+            .unwrap();
+        assert_eq!(dataset.orphaned_side_records(), 1);
+        
+        let result = NppesDatasetBuilder::new()
+            .main_data(main.as_path())
+            .other_names(other_names.as_path())
+            .strict_cross_file(true)
+            .build();
+        assert!(matches!(result, Err(NppesError::DataValidation { value: Some(ref v), .. }) if v == "1"));
+    }
+    
+    #[cfg(not(feature = "download"))]
+    #[test]
+    fn test_url_auxiliary_source_requires_download_feature() {
+        let dir = tempfile::tempdir().unwrap();
+        let main = dir.path().join("main.csv");
+        write_main_file(&main, &[&[(0, "1234567893"), (1, "1"), (5, "SMITH")]]);
+        
+        let result = NppesDatasetBuilder::new()
+            .main_data(main.as_path())
+            .taxonomy_reference("https://example.org/nucc_taxonomy_250.csv")
+            .build();
+        match result {
+            Err(NppesError::Custom { message, .. }) => {
+                assert!(message.contains("https://example.org/nucc_taxonomy_250.csv"), "{}", message);
+            }
+            other => panic!("expected an error, got {:?}", other.map(|d| d.len())),
+        }
+    }
+    
+    #[cfg(feature = "download")]
+    #[test]
+    fn test_local_main_with_url_taxonomy_is_downloaded() {
+        use std::io::{Read, Write};
+        
+        let body = "Code,Grouping,Classification,Specialization,Definition,Notes,Display Name,Section\n\
+                    207Q00000X,Allopathic & Osteopathic Physicians,Family Medicine,,,,Family Medicine Physician,Individual\n";
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        // Serve the HEAD and GET requests made by the downloader
+        let server = std::thread::spawn(move || {
+            for _ in 0..2 {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut request = Vec::new();
+                let mut buf = [0u8; 1024];
+                while !request.ends_with(b"\r\n\r\n") {
+                    let n = stream.read(&mut buf).unwrap();
+                    request.extend_from_slice(&buf[..n]);
+                }
+                let head = format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n", body.len());
+                stream.write_all(head.as_bytes()).unwrap();
+                if request.starts_with(b"GET") {
+                    stream.write_all(body.as_bytes()).unwrap();
+                }
+            }
+        });
+        
+        let dir = tempfile::tempdir().unwrap();
+        let main = dir.path().join("main.csv");
+        write_main_file(&main, &[&[(0, "1234567893"), (1, "1"), (5, "SMITH")]]);
+        let download_dir = dir.path().join("downloads");
+        
+        let dataset = NppesDatasetBuilder::new()
+            .main_data(main.as_path())
+            .taxonomy_reference(format!("http://127.0.0.1:{}/nucc_taxonomy_250.csv", port))
+            .with_download_config(DownloadConfig { download_dir: Some(download_dir.clone()), ..Default::default() })
+            .build()
+            .unwrap();
+        server.join().unwrap();
+        
+        assert_eq!(dataset.len(), 1);
+        let reference = dataset.get_taxonomy_description("207Q00000X").unwrap();
+        assert_eq!(reference.display_name.as_deref(), Some("Family Medicine Physician"));
+        assert!(download_dir.join("nucc_taxonomy_250.csv").exists());
+    }
+    
+    #[test]
+    fn test_compiled_query_runs_against_multiple_datasets() {
+        let query: CompiledQuery<'static> = CompiledQuery::new().state("CA").postal_code("94103");
+        
+        let first = dataset(vec![
+            provider("1234567893", StateCode::CA, "941031234"),
+            provider("1245319599", StateCode::NY, "94103"),
+        ]);
+        let second = dataset(vec![provider("1003000126", StateCode::CA, "90210")]);
+        
+        assert_eq!(query.run(&first).len(), 1);
+        assert!(query.run(&second).is_empty());
+        
+        let built = first.query().state("NY").build();
+        assert_eq!(built.run(&first)[0].npi.as_str(), "1245319599");
+    }
+    
+    #[test]
+    fn test_enumerated_between_uses_sorted_index() {
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        let mut providers = vec![provider("1234567893", StateCode::CA, "90210"); 4];
+        providers[0].enumeration_date = Some(date(2020, 3, 1));
+        providers[1].enumeration_date = Some(date(2010, 1, 1));
+        providers[2].enumeration_date = Some(date(2020, 3, 7));
+        
+        let mut dataset = dataset(providers);
+        let window = (date(2020, 3, 1), date(2020, 3, 7));
+        assert_eq!(dataset.enumerated_between(window.0, window.1).len(), 2);
+        
+        dataset.build_indexes();
+        let results = dataset.enumerated_between(window.0, window.1);
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].enumeration_date, Some(window.0));
+        assert!(dataset.enumerated_between(window.1, window.0).is_empty());
+        
+        // Providers removed behind the index's back are skipped, not a panic
+        dataset.providers.truncate(1);
+        assert_eq!(dataset.enumerated_between(window.0, window.1).len(), 1);
+    }
+    
+    #[test]
+    fn test_collect_and_extend_invalidate_indexes() {
+        let mut dataset: NppesDataset = vec![provider("1234567893", StateCode::CA, "90210")]
+            .into_iter()
+            .collect();
+        dataset.build_indexes();
+        assert_eq!(dataset.get_by_state("CA").len(), 1);
+        
+        dataset.extend([provider("1245319599", StateCode::CA, "94103")]);
+        assert!(dataset.npi_index.is_none());
+        assert_eq!(dataset.len(), 2);
+        assert_eq!(dataset.get_by_state("CA").len(), 2);
+    }
+    
+    #[test]
+    fn test_query_result_aggregates_and_exports() {
+        let mut providers = vec![
+            provider("1234567893", StateCode::CA, "90210"),
+            provider("1245319599", StateCode::CA, "94103"),
+            provider("1003000126", StateCode::NY, "10001"),
+        ];
+        providers[0].taxonomy_codes.push(TaxonomyCode { code: "207Q00000X".to_string(), ..Default::default() });
+        let dataset = dataset(providers);
+        
+        let result = dataset.query().entity_type(EntityType::Individual).run();
+        assert_eq!(result.len(), 3);
+        assert_eq!(result.count_by_state()["CA"], 2);
+        assert_eq!(result.count_by_specialty()["207Q00000X"], 1);
+        
+        let file = tempfile::NamedTempFile::new().unwrap();
+        dataset.query().state("NY").run().export(file.path(), ExportFormat::Json).unwrap();
+        let exported: Vec<NppesRecord> = serde_json::from_reader(std::fs::File::open(file.path()).unwrap()).unwrap();
+        assert_eq!(exported.len(), 1);
+        assert_eq!(exported[0].npi.as_str(), "1003000126");
+    }
+    
+    #[test]
+    fn test_statistics_summary_for_empty_dataset() {
+        let summary = dataset(Vec::new()).statistics().to_string();
+        assert!(!summary.contains("NaN"));
+        assert!(summary.contains("Total Providers: 0\n"));
+        assert!(summary.contains("Active Providers: 0 (0.0%)\n"));
+    }
+    
+    #[test]
+    fn test_raw_field_reports_original_column_value() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("npidata.csv");
+        write_main_file(&path, &[&[(0, "1234567893"), (1, "1"), (3, " 12-3456789 ")]]);
+        
+        let npi = Npi::new("1234567893".to_string()).unwrap();
+        let captured = NppesDatasetBuilder::new()
+            .main_data(path.clone())
+            .capture_raw_records(true)
+            .build()
+            .unwrap();
+        assert_eq!(captured.raw_field(&npi, "Employer Identification Number (EIN)"), Some(" 12-3456789 "));
+        assert_eq!(captured.raw_field(&npi, "entity type code"), Some("1"));
+        assert_eq!(captured.raw_field(&npi, "No Such Column"), None);
+        
+        let plain = NppesDatasetBuilder::new().main_data(path).build().unwrap();
+        assert_eq!(plain.raw_field(&npi, "Entity Type Code"), None);
+    }
+    
+    #[test]
+    fn test_active_snapshot_keeps_raw_records() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("npidata.csv");
+        write_main_file(&path, &[
+            &[(0, "1234567893"), (1, "1"), (3, " 12-3456789 ")],
+            &[(0, "1245319599"), (1, "1"), (39, "01/01/2020")],
+        ]);
+        
+        let dataset = NppesDatasetBuilder::new()
+            .main_data(path)
+            .capture_raw_records(true)
+            .build()
+            .unwrap();
+        let snapshot = dataset.active_snapshot();
+        
+        let active = Npi::new("1234567893".to_string()).unwrap();
+        let deactivated = Npi::new("1245319599".to_string()).unwrap();
+        assert_eq!(snapshot.raw_field(&active, "Employer Identification Number (EIN)"), Some(" 12-3456789 "));
+        assert_eq!(snapshot.raw_field(&deactivated, "Entity Type Code"), None);
+        assert_eq!(dataset.raw_field(&deactivated, "Entity Type Code"), Some("1"));
+    }
+    
+    #[test]
+    fn test_secondary_location_state_filter() {
+        let location = |npi: &str, state: StateCode| PracticeLocationRecord {
+            npi: Npi::new(npi.to_string()).unwrap(),
+            address: Address { state: Some(state), ..Default::default() },
+            telephone_extension: None,
+        };
+        let locations = create_practice_locations_map(vec![
+            location("1234567893", StateCode::NV),
+            location("1245319599", StateCode::AZ),
+        ]);
+        let data = NppesDataset::new(
+            vec![
+                provider("1234567893", StateCode::CA, "90210"),
+                provider("1245319599", StateCode::CA, "90210"),
+                provider("1003000126", StateCode::NV, "89101"),
+            ],
+            None, None, Some(locations), None, None, None, None,
+        );
+        
+        let results = data.query().secondary_location_state("NV").execute();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].npi.as_str(), "1234567893");
+        assert!(data.query().secondary_location_state("XX").execute().is_empty());
+    }
+    
+    #[test]
+    fn test_provider_taxonomy_rows() {
+        let taxonomy = |code: &str, is_primary| TaxonomyCode { code: code.to_string(), is_primary, ..Default::default() };
+        let mut three = provider("1234567893", StateCode::CA, "90210");
+        three.provider_name.last = Some("SMITH".to_string());
+        three.taxonomy_codes = vec![
+            taxonomy("207Q00000X", true),
+            taxonomy("207R00000X", false),
+            taxonomy("208D00000X", false),
+        ];
+        let mut one = provider("1245319599", StateCode::NY, "10001");
+        one.taxonomy_codes = vec![taxonomy("363L00000X", true)];
+        let none = provider("1003000126", StateCode::TX, "78701");
+        let taxonomy_map = create_taxonomy_map(vec![TaxonomyReference {
+            code: "207Q00000X".to_string(),
+            display_name: Some("Family Medicine Physician".to_string()),
+            ..Default::default()
+        }]);
+        let data = NppesDataset::new(vec![three, one, none], Some(taxonomy_map), None, None, None, None, None, None);
+        
+        let rows: Vec<ProviderTaxonomyRow> = data.iter_provider_taxonomy_rows().collect();
+        assert_eq!(rows.len(), 3 + 1 + 1);
+        assert_eq!(rows.iter().filter(|r| r.npi.as_str() == "1234567893").count(), 3);
+        assert_eq!(rows[0], ProviderTaxonomyRow {
+            npi: Npi::new("1234567893".to_string()).unwrap(),
+            name: "SMITH".to_string(),
+            state: Some(StateCode::CA),
+            taxonomy_code: Some("207Q00000X".to_string()),
+            is_primary: Some(true),
+            taxonomy_display_name: Some("Family Medicine Physician".to_string()),
+        });
+        assert_eq!(rows[1].is_primary, Some(false));
+        assert_eq!(rows[1].taxonomy_display_name, None);
+        assert_eq!(rows[3].taxonomy_code.as_deref(), Some("363L00000X"));
+        
+        // Providers without taxonomies keep a row
+        let last = &rows[4];
+        assert_eq!(last.npi.as_str(), "1003000126");
+        assert_eq!(last.state, Some(StateCode::TX));
+        assert_eq!((&last.taxonomy_code, last.is_primary, &last.taxonomy_display_name), (&None, None, &None));
+    }
+    
+    #[test]
+    fn test_unused_taxonomy_references() {
+        let reference = |code: &str| TaxonomyReference { code: code.to_string(), ..Default::default() };
+        let mut used = provider("1234567893", StateCode::CA, "90210");
+        used.taxonomy_codes.push(TaxonomyCode { code: "207Q00000X".to_string(), ..Default::default() });
+        let taxonomy_map = create_taxonomy_map(vec![
+            reference("207Q00000X"),
+            reference("363L00000X"),
+            reference("208D00000X"),
+        ]);
+        let mut data = NppesDataset::new(vec![used], Some(taxonomy_map), None, None, None, None, None, None);
+        
+        let codes = |data: &NppesDataset| data.unused_taxonomy_references().iter().map(|r| r.code.clone()).collect::<Vec<_>>();
+        assert_eq!(codes(&data), ["208D00000X", "363L00000X"]);
+        data.build_indexes();
+        assert_eq!(codes(&data), ["208D00000X", "363L00000X"]);
+        assert!(dataset(Vec::new()).unused_taxonomy_references().is_empty());
+    }
+    
+    #[test]
+    fn test_retain_npis_from_file() {
+        let name = |npi: &str| OtherNameRecord {
+            npi: Npi::new(npi.to_string()).unwrap(),
+            provider_other_organization_name: "OTHER".to_string(),
+            provider_other_organization_name_type_code: None,
+        };
+        let mut data = NppesDataset::new(
+            vec![
+                provider("1234567893", StateCode::CA, "90210"),
+                provider("1245319599", StateCode::NY, "10001"),
+                provider("1003000126", StateCode::TX, "73301"),
+            ],
+            None,
+            Some(create_other_names_map(vec![name("1234567893"), name("1245319599")])),
+            None, None, None, None, None,
+        );
+        data.build_indexes();
+        
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cohort.txt");
+        std::fs::write(&path, "# cohort\n1234567893\n\n 1003000126 \n1234567890\n12345\n").unwrap();
+        let invalid = vec![InvalidNpiLine { line_number: 6, value: "12345".to_string() }];
+        
+        let (subset, skipped) = data.subset_from_npi_file(&path).unwrap();
+        assert_eq!(subset.len(), 2);
+        assert_eq!(skipped, invalid);
+        assert_eq!(data.len(), 3);
+        
+        assert_eq!(data.retain_npis_from_file(&path).unwrap(), NpiFileFilter { retained: 2, invalid_lines: invalid });
+        assert!(data.get_by_npi(&Npi::new("1245319599".to_string()).unwrap()).is_none());
+        assert!(data.get_by_npi(&Npi::new("1003000126".to_string()).unwrap()).is_some());
+        assert_eq!(data.other_names_map.as_ref().unwrap().len(), 1);
+        assert!(data.retain_npis_from_file(dir.path().join("missing.txt")).is_err());
+    }
+    
+    #[test]
+    fn test_sort_providers_by() {
+        let named = |npi: &str, state: StateCode, last: Option<&str>, enumerated: Option<(i32, u32, u32)>| NppesRecord {
+            provider_name: ProviderName { last: last.map(str::to_string), ..Default::default() },
+            enumeration_date: enumerated.and_then(|(y, m, d)| NaiveDate::from_ymd_opt(y, m, d)),
+            ..provider(npi, state, "90210")
+        };
+        let mut data = dataset(vec![
+            named("1245319599", StateCode::TX, Some("ADAMS"), Some((2010, 1, 1))),
+            named("1234567893", StateCode::CA, Some("ZUNIGA"), None),
+            named("1003000126", StateCode::CA, Some("BAKER"), Some((2007, 6, 1))),
+        ]);
+        data.build_indexes();
+        let order = |data: &NppesDataset| data.providers.iter().map(|p| p.npi.as_str().to_string()).collect::<Vec<_>>();
+        
+        data.sort_providers_by(SortKey::Npi);
+        assert_eq!(order(&data), ["1003000126", "1234567893", "1245319599"]);
+        
+        data.sort_providers_by(SortKey::StateThenLastName);
+        assert_eq!(order(&data), ["1003000126", "1234567893", "1245319599"]);
+        
+        data.sort_providers_by(SortKey::EnumerationDate);
+        assert_eq!(order(&data), ["1003000126", "1245319599", "1234567893"]);
+        
+        // Indexes follow the new positions
+        let npi = Npi::new("1234567893".to_string()).unwrap();
+        assert_eq!(data.get_by_npi(&npi).map(|p| &p.npi), Some(&npi));
+    }
+    
+    #[test]
+    fn test_sort_providers_rebuilds_address_index() {
+        let at = |npi: &str, line_1: &str| {
+            let mut record = provider(npi, StateCode::CA, "90210");
+            record.practice_address = Address {
+                line_1: Some(line_1.to_string()),
+                city: Some("BEVERLY HILLS".to_string()),
+                state: Some(StateCode::CA),
+                postal_code: Some("90210".to_string()),
+                ..Default::default()
+            };
+            record
+        };
+        let mut data = dataset(vec![at("1245319599", "1 MAIN ST"), at("1003000126", "2 OAK AVE")]);
+        data.build_address_index();
+        data.sort_providers_by(SortKey::Npi);
+        
+        let found = data.providers_at_address(&at("1234567893", "1 MAIN ST").practice_address);
+        assert_eq!(found.iter().map(|p| p.npi.as_str()).collect::<Vec<_>>(), ["1245319599"]);
+    }
+}
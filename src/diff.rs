@@ -0,0 +1,218 @@
+/*!
+ * Comparing NPPES dataset snapshots
+ *
+ * [`NppesDataset::diff`] compares two releases by NPI and reports which
+ * providers were added, removed or changed. The result serializes with serde,
+ * so it can be written out as JSON for churn analysis.
+ */
+
+use std::collections::HashMap;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::data_types::*;
+use crate::dataset::NppesDataset;
+
+/// Provider field compared by [`NppesDataset::diff`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangedField {
+    /// Individual's name, including prefix, suffix and credential
+    ProviderName,
+    /// Individual's other name
+    ProviderOtherName,
+    /// Organization legal business and other names
+    OrganizationName,
+    MailingAddress,
+    PracticeAddress,
+    /// Taxonomy codes or which one is primary, ignoring their order
+    TaxonomyCodes,
+    /// Whether the provider is active (see [`NppesRecord::is_active`])
+    ActiveStatus,
+}
+
+impl fmt::Display for ChangedField {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            ChangedField::ProviderName => "provider_name",
+            ChangedField::ProviderOtherName => "provider_other_name",
+            ChangedField::OrganizationName => "organization_name",
+            ChangedField::MailingAddress => "mailing_address",
+            ChangedField::PracticeAddress => "practice_address",
+            ChangedField::TaxonomyCodes => "taxonomy_codes",
+            ChangedField::ActiveStatus => "active_status",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl ChangedField {
+    /// Fields of two records for the same NPI that differ
+    pub fn compare(previous: &NppesRecord, current: &NppesRecord) -> Vec<ChangedField> {
+        let mut changed = Vec::new();
+        if previous.provider_name != current.provider_name {
+            changed.push(ChangedField::ProviderName);
+        }
+        if previous.provider_other_name != current.provider_other_name {
+            changed.push(ChangedField::ProviderOtherName);
+        }
+        if previous.organization_name != current.organization_name {
+            changed.push(ChangedField::OrganizationName);
+        }
+        if previous.mailing_address != current.mailing_address {
+            changed.push(ChangedField::MailingAddress);
+        }
+        if previous.practice_address != current.practice_address {
+            changed.push(ChangedField::PracticeAddress);
+        }
+        if taxonomy_key(previous) != taxonomy_key(current) {
+            changed.push(ChangedField::TaxonomyCodes);
+        }
+        if previous.is_active() != current.is_active() {
+            changed.push(ChangedField::ActiveStatus);
+        }
+        changed
+    }
+}
+
+/// Taxonomy codes and primary flags in a canonical order
+fn taxonomy_key(record: &NppesRecord) -> Vec<(&str, bool)> {
+    let mut key: Vec<(&str, bool)> = record.taxonomy_codes.iter()
+        .map(|t| (t.code.as_str(), t.is_primary))
+        .collect();
+    key.sort_unstable();
+    key
+}
+
+/// A provider present in both snapshots whose compared fields differ
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProviderChange {
+    pub npi: Npi,
+    pub fields: Vec<ChangedField>,
+}
+
+/// Differences between two dataset snapshots
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DatasetDiff {
+    /// NPIs only in the current snapshot, in its order
+    pub added: Vec<Npi>,
+    /// NPIs only in the previous snapshot, in its order
+    pub removed: Vec<Npi>,
+    /// Providers in both snapshots with changed fields, in current order
+    pub changed: Vec<ProviderChange>,
+}
+
+impl DatasetDiff {
+    /// Check if the snapshots are the same on every compared field
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+
+    /// Number of providers changed in any way
+    pub fn len(&self) -> usize {
+        self.added.len() + self.removed.len() + self.changed.len()
+    }
+
+    /// Serialize the diff as pretty-printed JSON
+    pub fn to_json(&self) -> crate::Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+}
+
+impl NppesDataset {
+    /// Compare this snapshot with an earlier one
+    ///
+    /// Providers are matched by NPI. Names, addresses, taxonomy codes and
+    /// active status are compared; see [`ChangedField`].
+    pub fn diff(&self, previous: &NppesDataset) -> DatasetDiff {
+        let before: HashMap<&Npi, &NppesRecord> = previous.providers.iter()
+            .map(|p| (&p.npi, p))
+            .collect();
+        let after: HashMap<&Npi, &NppesRecord> = self.providers.iter()
+            .map(|p| (&p.npi, p))
+            .collect();
+
+        let mut diff = DatasetDiff::default();
+        for current in &self.providers {
+            match before.get(&current.npi) {
+                None => diff.added.push(current.npi.clone()),
+                Some(old) => {
+                    let fields = ChangedField::compare(old, current);
+                    if !fields.is_empty() {
+                        diff.changed.push(ProviderChange { npi: current.npi.clone(), fields });
+                    }
+                }
+            }
+        }
+        diff.removed = previous.providers.iter()
+            .filter(|p| !after.contains_key(&p.npi))
+            .map(|p| p.npi.clone())
+            .collect();
+        diff
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn provider(npi: &str, last: &str) -> NppesRecord {
+        NppesRecord {
+            npi: Npi::new(npi.to_string()).unwrap(),
+            entity_type: Some(EntityType::Individual),
+            provider_name: ProviderName { last: Some(last.to_string()), ..Default::default() },
+            mailing_address: Address { city: Some("AUSTIN".to_string()), ..Default::default() },
+            taxonomy_codes: vec![
+                TaxonomyCode { code: "207Q00000X".to_string(), is_primary: true, ..Default::default() },
+                TaxonomyCode { code: "208D00000X".to_string(), ..Default::default() },
+            ],
+            ..Default::default()
+        }
+    }
+
+    fn dataset(providers: Vec<NppesRecord>) -> NppesDataset {
+        NppesDataset::new(providers, None, None, None, None, None, None, None)
+    }
+
+    #[test]
+    fn test_diff_reports_each_change_category() {
+        let previous = dataset(vec![
+            provider("1234567893", "SMITH"),
+            provider("1245319599", "JONES"),
+            provider("1003000126", "BROWN"),
+        ]);
+
+        let mut renamed = provider("1234567893", "SMYTHE");
+        renamed.mailing_address.city = Some("DALLAS".to_string());
+        let mut reordered = provider("1245319599", "JONES");
+        reordered.taxonomy_codes.reverse();
+        let added = provider("1538144910", "GREEN");
+        let current = dataset(vec![renamed, reordered, added]);
+
+        let diff = current.diff(&previous);
+        assert_eq!(diff.added.iter().map(Npi::as_str).collect::<Vec<_>>(), ["1538144910"]);
+        assert_eq!(diff.removed.iter().map(Npi::as_str).collect::<Vec<_>>(), ["1003000126"]);
+        // Taxonomy order alone isn't a change
+        assert_eq!(diff.changed, [ProviderChange {
+            npi: Npi::new("1234567893".to_string()).unwrap(),
+            fields: vec![ChangedField::ProviderName, ChangedField::MailingAddress],
+        }]);
+        assert_eq!(diff.len(), 3);
+
+        let mut deactivated = provider("1245319599", "JONES");
+        deactivated.deactivation_date = NaiveDate::from_ymd_opt(2024, 5, 1);
+        deactivated.taxonomy_codes[1].is_primary = true;
+        deactivated.taxonomy_codes[0].is_primary = false;
+        let diff = dataset(vec![deactivated]).diff(&dataset(vec![provider("1245319599", "JONES")]));
+        assert_eq!(diff.changed[0].fields, [ChangedField::TaxonomyCodes, ChangedField::ActiveStatus]);
+
+        let json = diff.to_json().unwrap();
+        assert!(json.contains("\"active_status\""));
+        let parsed: DatasetDiff = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, diff);
+
+        assert!(previous.diff(&previous).is_empty());
+    }
+}
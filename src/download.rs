@@ -33,6 +33,10 @@ pub struct DownloadConfig {
     pub download_dir: Option<PathBuf>,
     /// Whether to keep downloaded files after processing
     pub keep_files: bool,
+    /// Base URL of the monthly NPPES release archives
+    pub release_base_url: String,
+    /// Hex SHA-256 digest that downloaded ZIP archives must match
+    pub expected_sha256: Option<String>,
 }
 
 #[cfg(feature = "download")]
@@ -45,6 +49,8 @@ impl Default for DownloadConfig {
             user_agent: Some(format!("nppes-rust/{}", env!("CARGO_PKG_VERSION"))),
             download_dir: None,
             keep_files: false,
+            release_base_url: "https://download.cms.gov/nppes".to_string(),
+            expected_sha256: None,
         }
     }
 }
@@ -88,6 +94,7 @@ impl NppesDownloader {
             self.client = Some(builder.build().map_err(|e| NppesError::Custom {
                 message: format!("Failed to create HTTP client: {}", e),
                 suggestion: Some("Check your network configuration".to_string()),
+                source: Some(Box::new(e)),
             })?);
         }
         
@@ -95,6 +102,13 @@ impl NppesDownloader {
     }
     
     /// Download a file from a URL
+    /// 
+    /// Data is written to `<filename>.part` and renamed once complete. If a
+    /// `.part` file is left over from an interrupted download and the server
+    /// accepts byte ranges, only the rest of the file is requested; otherwise
+    /// the download starts over. The server's ETag (or Last-Modified date) is
+    /// kept in `<filename>.part.validator` and sent as `If-Range`, so a partial
+    /// file of an older release with the same name is never resumed.
     pub async fn download_file(&mut self, url: &str, filename: Option<&str>) -> Result<PathBuf> {
         println!("Downloading from: {}", url);
         // Move config fields out before borrowing self mutably
@@ -108,6 +122,7 @@ impl NppesDownloader {
             NppesError::Custom {
                 message: format!("Failed to connect to URL: {}", e),
                 suggestion: Some("Check the URL and your internet connection".to_string()),
+                source: Some(Box::new(e)),
             }
         })?;
         
@@ -115,13 +130,23 @@ impl NppesDownloader {
             return Err(NppesError::Custom {
                 message: format!("HTTP error {}: {}", response.status(), url),
                 suggestion: Some("Check if the URL is correct and accessible".to_string()),
+                source: None,
             });
         }
         
-        let content_length = response.headers()
+        let content_length: Option<u64> = response.headers()
             .get(reqwest::header::CONTENT_LENGTH)
             .and_then(|ct_len| ct_len.to_str().ok())
             .and_then(|ct_len| ct_len.parse().ok());
+        let accepts_ranges = response.headers()
+            .get(reqwest::header::ACCEPT_RANGES)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| value.eq_ignore_ascii_case("bytes"));
+        let validator = response.headers()
+            .get(reqwest::header::ETAG)
+            .or_else(|| response.headers().get(reqwest::header::LAST_MODIFIED))
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
         
         // Check file size limit
         if let (Some(max_size), Some(size)) = (max_file_size, content_length) {
@@ -133,6 +158,7 @@ impl NppesDownloader {
                         format_bytes(max_size as usize)
                     ),
                     suggestion: Some("Increase max_file_size in DownloadConfig or download manually".to_string()),
+                    source: None,
                 });
             }
         }
@@ -151,27 +177,60 @@ impl NppesDownloader {
         });
         
         let file_path = download_dir.join(file_name);
+        let part_path = download_dir.join(format!("{}.part", file_name));
+        let validator_path = download_dir.join(format!("{}.part.validator", file_name));
         
-        // Start actual download
-        let response = client.get(url).send().await.map_err(|e| {
-            NppesError::Custom {
-                message: format!("Failed to download file: {}", e),
-                suggestion: Some("Check your internet connection and try again".to_string()),
+        // Resume from a partial download of the same file when the server can send the rest
+        let saved_validator = std::fs::read_to_string(&validator_path).ok();
+        let partial_len = match &validator {
+            Some(validator) if saved_validator.as_ref() == Some(validator) => {
+                std::fs::metadata(&part_path).map(|m| m.len()).unwrap_or(0)
             }
-        })?;
+            _ => 0,
+        };
+        let resume_from = match content_length {
+            Some(total) if accepts_ranges && partial_len > 0 && partial_len <= total => partial_len,
+            _ => 0,
+        };
+        if content_length.is_some_and(|total| resume_from == total) {
+            std::fs::rename(&part_path, &file_path)?;
+            let _ = std::fs::remove_file(&validator_path);
+            println!("Already downloaded {}", file_path.display());
+            return Ok(file_path);
+        }
         
-        if !response.status().is_success() {
-            return Err(NppesError::Custom {
-                message: format!("HTTP error {}: {}", response.status(), url),
-                suggestion: Some("Check if the URL is correct and accessible".to_string()),
-            });
+        // Start actual download
+        let range = validator.as_deref().filter(|_| resume_from > 0).map(|validator| (resume_from, validator));
+        if range.is_some() {
+            println!("Resuming download at {}", format_bytes(resume_from as usize));
         }
+        let mut response = send_download_request(client, url, range).await?;
         
-        let mut file = tokio::fs::File::create(&file_path).await?;
+        // A full response (200) to a range request means the server restarted from
+        // byte 0, and a range starting elsewhere can't be appended
+        let resume_from = if response.status() != reqwest::StatusCode::PARTIAL_CONTENT {
+            0
+        } else if content_range_start(&response) == Some(resume_from) {
+            resume_from
+        } else {
+            println!("Server sent a different byte range; restarting download");
+            response = send_download_request(client, url, None).await?;
+            0
+        };
+        let mut file = if resume_from > 0 {
+            tokio::fs::OpenOptions::new().append(true).open(&part_path).await?
+        } else {
+            match &validator {
+                Some(validator) => std::fs::write(&validator_path, validator)?,
+                None => { let _ = std::fs::remove_file(&validator_path); }
+            }
+            tokio::fs::File::create(&part_path).await?
+        };
         
         #[cfg(feature = "progress")]
         let progress_bar = if let Some(total_size) = content_length {
             let pb = ProgressBar::new(total_size);
+            pb.set_position(resume_from);
             pb.set_style(
                 ProgressStyle::default_bar()
                     .template("[{elapsed_precise}] [{bar:40.cyan/blue}] {bytes}/{total_bytes} ({eta})")
@@ -184,7 +243,7 @@ impl NppesDownloader {
         };
         
         // Download with progress tracking
-        let mut downloaded = 0u64;
+        let mut downloaded = resume_from;
         let mut stream = response.bytes_stream();
         
         use futures_util::StreamExt;
@@ -192,6 +251,7 @@ impl NppesDownloader {
             let chunk = chunk.map_err(|e| NppesError::Custom {
                 message: format!("Error downloading chunk: {}", e),
                 suggestion: Some("Try downloading again".to_string()),
+                source: Some(Box::new(e)),
             })?;
             
             tokio::io::AsyncWriteExt::write_all(&mut file, &chunk).await?;
@@ -202,6 +262,11 @@ impl NppesDownloader {
                 pb.set_position(downloaded);
             }
         }
+        // Finish pending writes before the file is read back
+        tokio::io::AsyncWriteExt::flush(&mut file).await?;
+        drop(file);
+        tokio::fs::rename(&part_path, &file_path).await?;
+        let _ = std::fs::remove_file(&validator_path);
         
         #[cfg(feature = "progress")]
         if let Some(pb) = progress_bar {
@@ -214,10 +279,28 @@ impl NppesDownloader {
     }
     
     /// Download and extract a ZIP file
+    /// 
+    /// With [`DownloadConfig::expected_sha256`] set, the archive is checked
+    /// before extraction and deleted if it doesn't match.
     pub async fn download_and_extract_zip(&mut self, url: &str, extract_to: Option<&Path>) -> Result<ExtractedFiles> {
         // Download the ZIP file
         let zip_path = self.download_file(url, None).await?;
         
+        if let Some(expected) = &self.config.expected_sha256 {
+            let actual = sha256_file(&zip_path)?;
+            if !actual.eq_ignore_ascii_case(expected.trim()) {
+                let _ = std::fs::remove_file(&zip_path);
+                return Err(NppesError::Custom {
+                    message: format!(
+                        "Checksum mismatch for {}: expected SHA-256 {}, got {}",
+                        url, expected.trim(), actual
+                    ),
+                    suggestion: Some("The download may be truncated or corrupt; try downloading again".to_string()),
+                    source: None,
+                });
+            }
+        }
+        
         // Extract the ZIP file
         let extracted = self.extract_zip(&zip_path, extract_to)?;
         
@@ -230,6 +313,9 @@ impl NppesDownloader {
     }
     
     /// Extract a ZIP file
+    /// 
+    /// Fails before extracting anything if the archive has no main NPPES data
+    /// file (`npidata_pfile_*.csv`), listing the entries it does have.
     pub fn extract_zip(&self, zip_path: &Path, extract_to: Option<&Path>) -> Result<ExtractedFiles> {
         use std::fs::File;
         use std::io::BufReader;
@@ -240,8 +326,23 @@ impl NppesDownloader {
         let mut archive = ZipArchive::new(reader).map_err(|e| NppesError::Custom {
             message: format!("Failed to open ZIP file: {}", e),
             suggestion: Some("Check if the file is a valid ZIP archive".to_string()),
+            source: Some(Box::new(e)),
         })?;
         
+        if !archive.file_names().any(|name| is_nppes_csv(name, "npidata_pfile")) {
+            let mut entries: Vec<&str> = archive.file_names().collect();
+            entries.sort_unstable();
+            return Err(NppesError::Custom {
+                message: format!(
+                    "No main NPPES data file in {}; entries: {}",
+                    zip_path.display(),
+                    if entries.is_empty() { "(none)".to_string() } else { entries.join(", ") }
+                ),
+                suggestion: Some("Check that the archive is a complete NPPES data dissemination file".to_string()),
+                source: None,
+            });
+        }
+        
         // Determine extraction directory
         let extract_dir = if let Some(dir) = extract_to {
             dir.to_path_buf()
@@ -269,6 +370,7 @@ impl NppesDownloader {
             let mut file = archive.by_index(i).map_err(|e| NppesError::Custom {
                 message: format!("Failed to read file from ZIP: {}", e),
                 suggestion: None,
+                source: Some(Box::new(e)),
             })?;
             
             let file_path = extract_dir.join(file.name());
@@ -285,16 +387,16 @@ impl NppesDownloader {
             println!("Extracted: {}", file.name());
             
             // Categorize extracted files
-            let filename = file.name().to_lowercase();
-            if filename.contains("npidata_pfile") && filename.ends_with(".csv") && !filename.contains("_fileheader") {
+            let filename = file.name();
+            if is_nppes_csv(filename, "npidata_pfile") {
                 extracted_files.main_data_file = Some(file_path.clone());
-            } else if filename.contains("nucc_taxonomy") && filename.ends_with(".csv") && !filename.contains("_fileheader") {
+            } else if is_nppes_csv(filename, "nucc_taxonomy") {
                 extracted_files.taxonomy_file = Some(file_path.clone());
-            } else if filename.contains("othername_pfile") && filename.ends_with(".csv") && !filename.contains("_fileheader") {
+            } else if is_nppes_csv(filename, "othername_pfile") {
                 extracted_files.other_names_file = Some(file_path.clone());
-            } else if filename.contains("pl_pfile") && filename.ends_with(".csv") && !filename.contains("_fileheader") {
+            } else if is_nppes_csv(filename, "pl_pfile") {
                 extracted_files.practice_locations_file = Some(file_path.clone());
-            } else if filename.contains("endpoint_pfile") && filename.ends_with(".csv") && !filename.contains("_fileheader") {
+            } else if is_nppes_csv(filename, "endpoint_pfile") {
                 extracted_files.endpoints_file = Some(file_path.clone());
             }
             
@@ -307,18 +409,36 @@ impl NppesDownloader {
     }
     
     /// Download the latest NPPES data from CMS
+    /// 
+    /// CMS publishes each month's file some days into the month, so when the
+    /// current month's archive isn't there yet (404), this falls back to the
+    /// previous month, then to the `_V1` archives of both months. Other HTTP
+    /// errors are returned immediately.
     pub async fn download_latest_nppes(&mut self) -> Result<ExtractedFiles> {
-        use chrono::Datelike;
-        // Get current year and month
-        let today = chrono::Utc::now();
-        let year = today.year();
-        let month = today.format("%B").to_string(); // Full month name, e.g., "May"
-        // Construct the URL for the latest file (Version 2)
-        let url = format!(
-            "https://download.cms.gov/nppes/NPPES_Data_Dissemination_{}_{}_V2.zip",
-            month, year
-        );
-        self.download_and_extract_zip(&url, None).await
+        let today = chrono::Utc::now().date_naive();
+        let urls = release_urls(&self.config.release_base_url, today);
+        
+        for url in &urls {
+            let status = self.get_client().await?
+                .head(url)
+                .send()
+                .await
+                .map_err(|e| NppesError::Custom {
+                    message: format!("Failed to connect to URL: {}", e),
+                    suggestion: Some("Check the URL and your internet connection".to_string()),
+                    source: Some(Box::new(e)),
+                })?
+                .status();
+            if status != reqwest::StatusCode::NOT_FOUND {
+                return self.download_and_extract_zip(url, None).await;
+            }
+        }
+        
+        Err(NppesError::Custom {
+            message: format!("No NPPES release archive found; tried {}", urls.join(", ")),
+            suggestion: Some("Check https://download.cms.gov/nppes/NPI_Files.html and pass the archive URL to download_and_extract_zip()".to_string()),
+            source: None,
+        })
     }
 }
 
@@ -377,6 +497,90 @@ impl ExtractedFiles {
     }
 }
 
+/// Check whether an archive entry is an NPPES data CSV of a kind (not its header file)
+#[cfg(feature = "download")]
+fn is_nppes_csv(name: &str, kind: &str) -> bool {
+    let name = name.to_lowercase();
+    name.contains(kind) && name.ends_with(".csv") && !name.contains("_fileheader")
+}
+
+/// Send a download GET, optionally for the bytes from an offset if the file still matches a validator
+#[cfg(feature = "download")]
+async fn send_download_request(
+    client: &reqwest::Client,
+    url: &str,
+    range: Option<(u64, &str)>,
+) -> Result<reqwest::Response> {
+    let mut request = client.get(url);
+    if let Some((start, validator)) = range {
+        request = request
+            .header(reqwest::header::RANGE, format!("bytes={}-", start))
+            .header(reqwest::header::IF_RANGE, validator);
+    }
+    let response = request.send().await.map_err(|e| {
+        NppesError::Custom {
+            message: format!("Failed to download file: {}", e),
+            suggestion: Some("Check your internet connection and try again".to_string()),
+            source: Some(Box::new(e)),
+        }
+    })?;
+    
+    if !response.status().is_success() {
+        return Err(NppesError::Custom {
+            message: format!("HTTP error {}: {}", response.status(), url),
+            suggestion: Some("Check if the URL is correct and accessible".to_string()),
+            source: None,
+        });
+    }
+    Ok(response)
+}
+
+/// First byte of a partial response, from its `Content-Range: bytes START-END/TOTAL` header
+#[cfg(feature = "download")]
+fn content_range_start(response: &reqwest::Response) -> Option<u64> {
+    response.headers()
+        .get(reqwest::header::CONTENT_RANGE)?
+        .to_str().ok()?
+        .strip_prefix("bytes ")?
+        .split('-')
+        .next()?
+        .trim()
+        .parse()
+        .ok()
+}
+
+/// Hex SHA-256 digest of a file
+#[cfg(feature = "download")]
+fn sha256_file(path: &Path) -> Result<String> {
+    use sha2::{Digest, Sha256};
+    
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut std::fs::File::open(path)?, &mut hasher)?;
+    Ok(hasher.finalize().iter().map(|byte| format!("{:02x}", byte)).collect())
+}
+
+/// Candidate release archive URLs, most preferred first
+/// 
+/// V2 archives of this month and the previous one, then their V1 archives.
+#[cfg(feature = "download")]
+fn release_urls(base_url: &str, today: chrono::NaiveDate) -> Vec<String> {
+    use chrono::Datelike;
+    
+    let previous = today.with_day(1)
+        .and_then(|first| first.pred_opt())
+        .unwrap_or(today);
+    let base_url = base_url.trim_end_matches('/');
+    ["V2", "V1"].iter()
+        .flat_map(|version| [today, previous].map(|date| (date, version)))
+        .map(|(date, version)| {
+            format!(
+                "{}/NPPES_Data_Dissemination_{}_{}_{}.zip",
+                base_url, date.format("%B"), date.year(), version
+            )
+        })
+        .collect()
+}
+
 // Helper function to format bytes
 fn format_bytes(bytes: usize) -> String {
     const UNITS: &[&str] = &["B", "KB", "MB", "GB", "TB"];
@@ -423,7 +627,125 @@ impl Default for NppesDownloader {
 mod tests {
     use super::*;
     use std::fs;
-    use std::io::{BufRead, BufReader};
+    use std::io::{BufRead, BufReader, Read, Write};
+    
+    #[test]
+    fn test_release_urls_fall_back_across_months() {
+        let date = |y, m, d| chrono::NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        assert_eq!(release_urls("https://example.org/nppes/", date(2025, 1, 3)), [
+            "https://example.org/nppes/NPPES_Data_Dissemination_January_2025_V2.zip",
+            "https://example.org/nppes/NPPES_Data_Dissemination_December_2024_V2.zip",
+            "https://example.org/nppes/NPPES_Data_Dissemination_January_2025_V1.zip",
+            "https://example.org/nppes/NPPES_Data_Dissemination_December_2024_V1.zip",
+        ]);
+    }
+    
+    /// Build a ZIP archive in memory with a small CSV per entry name
+    fn zip_archive(names: &[&str]) -> Vec<u8> {
+        let mut archive = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+        for name in names {
+            archive.start_file(*name, zip::write::SimpleFileOptions::default()).unwrap();
+            archive.write_all(b"\"NPI\"\n").unwrap();
+        }
+        archive.finish().unwrap().into_inner()
+    }
+    
+    /// Serve `requests` HTTP requests, answering 404 for paths in `missing`
+    /// 
+    /// With `ranges`, advertises and honors `Range: bytes=N-` requests.
+    fn stub_server(
+        body: Vec<u8>,
+        missing: Vec<String>,
+        requests: usize,
+        ranges: bool,
+    ) -> (String, std::thread::JoinHandle<Vec<String>>) {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || {
+            let mut seen = Vec::new();
+            for _ in 0..requests {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut request = Vec::new();
+                let mut buf = [0u8; 1024];
+                while !request.ends_with(b"\r\n\r\n") {
+                    let n = stream.read(&mut buf).unwrap();
+                    request.extend_from_slice(&buf[..n]);
+                }
+                let request = String::from_utf8(request).unwrap();
+                let mut parts = request.split_whitespace();
+                let (method, path) = (parts.next().unwrap().to_string(), parts.next().unwrap().to_string());
+                let header = |name: &str| request.lines()
+                    .find_map(|line| line.to_ascii_lowercase().strip_prefix(name).map(str::to_string));
+                let range_start = header("range: bytes=")
+                    .map(|range| range.trim_end_matches('-').parse::<usize>().unwrap());
+                // The ETag changes with the file, and ranges are served from 1 KiB boundaries
+                let etag = format!("\"{}\"", body.len());
+                let same_file = header("if-range: ").is_none_or(|validator| validator == etag);
+                if missing.contains(&path) {
+                    stream.write_all(b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n").unwrap();
+                } else {
+                    let (status, start) = match range_start {
+                        Some(start) if ranges && same_file => {
+                            let start = start - start % 1024;
+                            (format!("206 Partial Content\r\nContent-Range: bytes {}-{}/{}", start, body.len() - 1, body.len()), start)
+                        }
+                        _ => ("200 OK".to_string(), 0),
+                    };
+                    let accept_ranges = if ranges { format!("Accept-Ranges: bytes\r\nETag: {}\r\n", etag) } else { String::new() };
+                    let head = format!(
+                        "HTTP/1.1 {}\r\n{}Content-Length: {}\r\nConnection: close\r\n\r\n",
+                        status, accept_ranges, body.len() - start
+                    );
+                    stream.write_all(head.as_bytes()).unwrap();
+                    if method == "GET" {
+                        stream.write_all(&body[start..]).unwrap();
+                    }
+                }
+                match range_start {
+                    Some(start) => seen.push(format!("{} {} from {}", method, path, start)),
+                    None => seen.push(format!("{} {}", method, path)),
+                }
+            }
+            seen
+        });
+        (base_url, server)
+    }
+    
+    #[tokio::test]
+    async fn test_download_latest_falls_back_after_404() {
+        let body = zip_archive(&["npidata_pfile_20250101-20250131.csv"]);
+        let dir = tempfile::tempdir().unwrap();
+        let today = chrono::Utc::now().date_naive();
+        let path = |url: &str| url[url.find("/NPPES").unwrap()..].to_string();
+        let urls = release_urls("http://unused", today);
+        // 404 probe, successful probe, then the download's own HEAD and GET
+        let (base_url, server) = stub_server(body, vec![path(&urls[0])], 4, false);
+        let mut downloader = NppesDownloader::with_config(DownloadConfig {
+            release_base_url: base_url,
+            download_dir: Some(dir.path().to_path_buf()),
+            ..Default::default()
+        });
+        
+        let extracted = downloader.download_latest_nppes().await.unwrap();
+        assert!(extracted.has_main_data());
+        let seen = server.join().unwrap();
+        assert_eq!(seen, [
+            format!("HEAD {}", path(&urls[0])),
+            format!("HEAD {}", path(&urls[1])),
+            format!("HEAD {}", path(&urls[1])),
+            format!("GET {}", path(&urls[1])),
+        ]);
+        
+        let missing: Vec<String> = urls.iter().map(|url| path(url)).collect();
+        let (base_url, server) = stub_server(Vec::new(), missing, urls.len(), false);
+        let mut downloader = NppesDownloader::with_config(DownloadConfig {
+            release_base_url: base_url,
+            ..Default::default()
+        });
+        let err = downloader.download_latest_nppes().await.unwrap_err();
+        server.join().unwrap();
+        assert!(err.to_string().contains(&path(&urls[3])), "{}", err);
+    }
 
     #[tokio::test]
     async fn test_download_and_read_nppes_file() {
@@ -444,4 +766,93 @@ mod tests {
         }
         let _ = fs::remove_dir_all(&extracted.directory);
     }
+    
+    #[tokio::test]
+    async fn test_download_resumes_partial_file() {
+        let body: Vec<u8> = (0..10_000u32).map(|i| (i % 251) as u8).collect();
+        let dir = tempfile::tempdir().unwrap();
+        let config = DownloadConfig { download_dir: Some(dir.path().to_path_buf()), ..Default::default() };
+        let part = dir.path().join("npidata.zip.part");
+        let validator = dir.path().join("npidata.zip.part.validator");
+        let download = |partial: &[u8], saved_validator: Option<&str>, requests: usize| {
+            std::fs::write(&part, partial).unwrap();
+            match saved_validator {
+                Some(saved) => std::fs::write(&validator, saved).unwrap(),
+                None => { let _ = std::fs::remove_file(&validator); }
+            }
+            let (base_url, server) = stub_server(body.clone(), Vec::new(), requests, true);
+            let mut downloader = NppesDownloader::with_config(config.clone());
+            async move {
+                let path = downloader.download_file(&format!("{}/npidata.zip", base_url), None).await.unwrap();
+                (path, server.join().unwrap())
+            }
+        };
+        
+        let (path, seen) = download(&body[..4_096], Some("\"10000\""), 2).await;
+        assert_eq!(seen, ["HEAD /npidata.zip", "GET /npidata.zip from 4096"]);
+        assert_eq!(path, dir.path().join("npidata.zip"));
+        assert_eq!(fs::read(&path).unwrap(), body);
+        assert!(!part.exists());
+        assert!(!validator.exists());
+        
+        // A partial file of another release, or of unknown origin, is started over
+        for saved in [Some("\"9000\""), None] {
+            let (path, seen) = download(b"old release", saved, 2).await;
+            assert_eq!(seen, ["HEAD /npidata.zip", "GET /npidata.zip"]);
+            assert_eq!(fs::read(&path).unwrap(), body);
+        }
+        
+        // A range starting anywhere but the end of the partial file is refetched whole
+        let (path, seen) = download(&body[..4_000], Some("\"10000\""), 3).await;
+        assert_eq!(seen, ["HEAD /npidata.zip", "GET /npidata.zip from 4000", "GET /npidata.zip"]);
+        assert_eq!(fs::read(&path).unwrap(), body);
+        assert!(!part.exists());
+        
+        // Without range support, a stale partial file is replaced
+        std::fs::write(&part, b"stale bytes").unwrap();
+        let (base_url, server) = stub_server(body.clone(), Vec::new(), 2, false);
+        let mut downloader = NppesDownloader::with_config(config);
+        let path = downloader.download_file(&format!("{}/npidata.zip", base_url), None).await.unwrap();
+        assert_eq!(server.join().unwrap(), ["HEAD /npidata.zip", "GET /npidata.zip"]);
+        assert_eq!(fs::read(&path).unwrap(), body);
+        assert!(!part.exists());
+    }
+    
+    #[tokio::test]
+    async fn test_download_verifies_checksum_and_contents() {
+        use sha2::{Digest, Sha256};
+        
+        let body = zip_archive(&["npidata_pfile_20250101-20250131.csv", "nucc_taxonomy_250.csv"]);
+        let digest: String = Sha256::digest(&body).iter().map(|b| format!("{:02X}", b)).collect();
+        let dir = tempfile::tempdir().unwrap();
+        let config = DownloadConfig {
+            download_dir: Some(dir.path().to_path_buf()),
+            expected_sha256: Some(digest.clone()),
+            ..Default::default()
+        };
+        
+        let (base_url, server) = stub_server(body.clone(), Vec::new(), 2, false);
+        let mut downloader = NppesDownloader::with_config(config.clone());
+        let extracted = downloader.download_and_extract_zip(&format!("{}/valid.zip", base_url), None).await.unwrap();
+        server.join().unwrap();
+        assert!(extracted.has_main_data());
+        assert!(extracted.taxonomy_file.is_some());
+        
+        // Flip one byte, as a corrupted transfer would
+        let mut tampered = body.clone();
+        tampered[body.len() / 2] ^= 0xFF;
+        let (base_url, server) = stub_server(tampered, Vec::new(), 2, false);
+        let mut downloader = NppesDownloader::with_config(config);
+        let err = downloader.download_and_extract_zip(&format!("{}/tampered.zip", base_url), None).await.unwrap_err();
+        server.join().unwrap();
+        assert!(err.to_string().contains("Checksum mismatch"), "{}", err);
+        assert!(!dir.path().join("tampered.zip").exists());
+        
+        let incomplete = dir.path().join("incomplete.zip");
+        fs::write(&incomplete, zip_archive(&["othername_pfile_20250101-20250131.csv", "README.txt"])).unwrap();
+        let extract_dir = dir.path().join("extracted");
+        let err = NppesDownloader::new().extract_zip(&incomplete, Some(&extract_dir)).unwrap_err();
+        assert!(err.to_string().contains("entries: README.txt, othername_pfile_20250101-20250131.csv"), "{}", err);
+        assert!(!extract_dir.exists());
+    }
 } 
\ No newline at end of file
@@ -41,6 +41,20 @@ pub enum NppesError {
         line: Option<usize>,
         column: Option<String>,
         context: ErrorContext,
+        #[source]
+        source: Option<BoxedError>,
+    },
+    
+    /// Text encoding errors, such as invalid UTF-8 in an input file
+    #[error("Invalid text encoding at line {line:?}, byte {byte_offset:?}: {message}")]
+    Encoding {
+        message: String,
+        line: Option<usize>,
+        byte_offset: Option<u64>,
+        context: ErrorContext,
+        suggestion: String,
+        #[source]
+        source: Option<BoxedError>,
     },
     
     /// Data validation errors with detailed information
@@ -104,6 +118,8 @@ pub enum NppesError {
         message: String,
         format: ExportFormat,
         suggestion: Option<String>,
+        #[source]
+        source: Option<BoxedError>,
     },
     
     /// Memory estimation errors
@@ -126,9 +142,14 @@ pub enum NppesError {
     Custom {
         message: String,
         suggestion: Option<String>,
+        #[source]
+        source: Option<BoxedError>,
     },
 }
 
+/// Boxed underlying error preserved as an error's `source()`
+pub type BoxedError = Box<dyn std::error::Error + Send + Sync>;
+
 /// Error context providing additional information
 #[derive(Debug, Default, Clone)]
 pub struct ErrorContext {
@@ -147,6 +168,9 @@ pub enum ExportFormat {
     Parquet,
     Arrow,
     Sql,
+    Avro,
+    Xml,
+    DataFrame,
 }
 
 impl fmt::Display for ExportFormat {
@@ -157,6 +181,9 @@ impl fmt::Display for ExportFormat {
             ExportFormat::Parquet => write!(f, "Parquet"),
             ExportFormat::Arrow => write!(f, "Arrow"),
             ExportFormat::Sql => write!(f, "SQL"),
+            ExportFormat::Avro => write!(f, "Avro"),
+            ExportFormat::Xml => write!(f, "XML"),
+            ExportFormat::DataFrame => write!(f, "DataFrame"),
         }
     }
 }
@@ -246,6 +273,36 @@ impl NppesError {
         }
     }
     
+    /// Create an error from a CSV error, separating encoding problems from parse errors
+    pub fn from_csv_error(err: csv::Error, line: Option<usize>, context: ErrorContext) -> Self {
+        if let csv::ErrorKind::Utf8 { pos, err: utf8_err } = err.kind() {
+            let line = pos.as_ref().map(|p| p.line() as usize).or(line);
+            let byte_offset = pos.as_ref().map(|p| p.byte());
+            let message = format!(
+                "invalid UTF-8 in field {} after {} valid bytes",
+                utf8_err.field(),
+                utf8_err.valid_up_to()
+            );
+            return Self::Encoding {
+                message,
+                line,
+                byte_offset,
+                context: ErrorContext { line_number: line, ..context },
+                suggestion: "The file is not valid UTF-8. If it uses a legacy encoding, \
+                    load it with NppesReader::new().with_encoding(TextEncoding::Latin1)".to_string(),
+                source: Some(Box::new(err)),
+            };
+        }
+        
+        Self::CsvParse {
+            message: err.to_string(),
+            line,
+            column: None,
+            context,
+            source: Some(Box::new(err)),
+        }
+    }
+    
     /// Create a date parsing error with format information
     pub fn date_parse_with_format(value: &str, expected_format: &str) -> Self {
         Self::DateParse {
@@ -301,6 +358,9 @@ impl NppesError {
             Self::InvalidEntityType { valid_options, .. } => {
                 format!("{}\n\nValid options: {}", self, valid_options.join(", "))
             }
+            Self::Encoding { suggestion, .. } => {
+                format!("{}\n\nSuggestion: {}", self, suggestion)
+            }
             Self::DateParse { expected_format, .. } => {
                 format!("{}\n\nExpected format: {}", self, expected_format)
             }
@@ -342,17 +402,8 @@ impl From<std::io::Error> for NppesError {
 
 impl From<csv::Error> for NppesError {
     fn from(err: csv::Error) -> Self {
-        let (line, message) = match err.position() {
-            Some(pos) => (Some(pos.line() as usize), err.to_string()),
-            None => (None, err.to_string()),
-        };
-        
-        Self::CsvParse {
-            message,
-            line,
-            column: None,
-            context: ErrorContext::default(),
-        }
+        let line = err.position().map(|pos| pos.line() as usize);
+        Self::from_csv_error(err, line, ErrorContext::default())
     }
 }
 
@@ -362,6 +413,31 @@ impl From<serde_json::Error> for NppesError {
             message: err.to_string(),
             format: ExportFormat::Json,
             suggestion: Some("Check if the data is serializable to JSON.".to_string()),
+            source: Some(Box::new(err)),
+        }
+    }
+}
+
+#[cfg(feature = "avro")]
+impl From<apache_avro::Error> for NppesError {
+    fn from(err: apache_avro::Error) -> Self {
+        NppesError::Export {
+            message: err.to_string(),
+            format: ExportFormat::Avro,
+            suggestion: Some("Check Avro file and schema consistency.".to_string()),
+            source: Some(Box::new(err)),
+        }
+    }
+}
+
+#[cfg(feature = "dataframe")]
+impl From<polars::error::PolarsError> for NppesError {
+    fn from(err: polars::error::PolarsError) -> Self {
+        NppesError::Export {
+            message: err.to_string(),
+            format: ExportFormat::DataFrame,
+            suggestion: Some("Check DataFrame column lengths and types.".to_string()),
+            source: Some(Box::new(err)),
         }
     }
 }
@@ -373,6 +449,7 @@ impl From<ArrowError> for NppesError {
             message: err.to_string(),
             format: ExportFormat::Arrow,
             suggestion: Some("Check Arrow schema and data consistency.".to_string()),
+            source: Some(Box::new(err)),
         }
     }
 }
@@ -384,6 +461,7 @@ impl From<ParquetError> for NppesError {
             message: err.to_string(),
             format: ExportFormat::Parquet,
             suggestion: Some("Check Parquet file and schema consistency.".to_string()),
+            source: Some(Box::new(err)),
         }
     }
 }
\ No newline at end of file
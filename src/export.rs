@@ -2,10 +2,10 @@
  * Export functionality for NPPES data
  * 
  * Provides various export formats for NPPES data including JSON, CSV,
- * SQL, and optionally Parquet and Arrow formats.
+ * SQL, XML, and optionally Parquet, Arrow and Avro formats.
  */
 
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::fs::File;
 use std::io::{Write, BufWriter};
 use serde_json;
@@ -28,11 +28,61 @@ use std::sync::Arc;
 use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
 #[cfg(feature = "arrow-export")]
 use arrow::array::ArrayRef;
+#[cfg(any(feature = "arrow-export", feature = "avro"))]
+use crate::reader::NppesReader;
+
+/// How postal codes are written by exporters
+/// 
+/// Only [`CsvExporter`] (normalized output) and [`SqlExporter`] take a ZIP
+/// format. JSON, XML, Parquet and denormalized CSV always write the postal
+/// code as stored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ZipFormat {
+    /// First five digits only (see [`normalize_zip5`])
+    Zip5,
+    /// Full postal code as stored, including the ZIP+4 suffix when present
+    #[default]
+    Zip9,
+}
+
+impl ZipFormat {
+    /// Format an address's postal code; values that aren't US ZIPs pass through unchanged
+    pub fn format(&self, address: &Address) -> Option<String> {
+        match self {
+            ZipFormat::Zip5 => address.zip5().or_else(|| address.postal_code.clone()),
+            ZipFormat::Zip9 => address.postal_code.clone(),
+        }
+    }
+}
+
+/// What an exporter wrote, returned by [`NppesExporter::export`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExportSummary {
+    /// Number of provider records written
+    pub records: usize,
+    /// Files written, in the order they were created
+    pub files: Vec<PathBuf>,
+    /// Total size of the files written, in bytes
+    pub bytes_written: u64,
+}
+
+impl ExportSummary {
+    /// Summarize finished files, reading their sizes from disk
+    fn from_files(records: usize, files: Vec<PathBuf>) -> Result<Self> {
+        let mut bytes_written = 0;
+        for file in &files {
+            bytes_written += std::fs::metadata(file)?.len();
+        }
+        Ok(Self { records, files, bytes_written })
+    }
+}
 
 /// Trait for implementing NPPES data exporters
 pub trait NppesExporter {
-    /// Export the dataset
-    fn export(&self, dataset: &NppesDataset, path: &Path) -> Result<()>;
+    /// Export the dataset, returning what was written
+    /// 
+    /// Exporters don't print anything; report progress to users from the summary.
+    fn export(&self, dataset: &NppesDataset, path: &Path) -> Result<ExportSummary>;
     
     /// Get the export format
     fn format(&self) -> ExportFormat;
@@ -85,7 +135,7 @@ impl JsonExporter {
 }
 
 impl NppesExporter for JsonExporter {
-    fn export(&self, dataset: &NppesDataset, path: &Path) -> Result<()> {
+    fn export(&self, dataset: &NppesDataset, path: &Path) -> Result<ExportSummary> {
         let file = File::create(path)?;
         let mut writer = BufWriter::new(file);
         
@@ -98,13 +148,14 @@ impl NppesExporter for JsonExporter {
         } else {
             // Export as single JSON array
             if self.pretty_print {
-                serde_json::to_writer_pretty(writer, &dataset.providers)?;
+                serde_json::to_writer_pretty(&mut writer, &dataset.providers)?;
             } else {
-                serde_json::to_writer(writer, &dataset.providers)?;
+                serde_json::to_writer(&mut writer, &dataset.providers)?;
             }
         }
+        writer.flush()?;
         
-        Ok(())
+        ExportSummary::from_files(dataset.providers.len(), vec![path.to_path_buf()])
     }
     
     fn format(&self) -> ExportFormat {
@@ -122,6 +173,14 @@ pub struct CsvExporter {
     pub delimiter: u8,
     /// Whether to normalize into multiple files
     pub normalize: bool,
+    /// Postal code output format
+    pub zip_format: ZipFormat,
+    /// Whether coded columns hold readable labels instead of NPPES codes
+    pub decoded_labels: bool,
+    /// Whether the taxonomies file includes descriptions from the taxonomy reference
+    pub taxonomy_descriptions: bool,
+    /// Maximum providers per normalized file, or `None` for a single file
+    pub max_rows_per_file: Option<usize>,
 }
 
 impl Default for CsvExporter {
@@ -130,6 +189,10 @@ impl Default for CsvExporter {
             include_headers: true,
             delimiter: b',',
             normalize: true,
+            zip_format: ZipFormat::default(),
+            decoded_labels: false,
+            taxonomy_descriptions: false,
+            max_rows_per_file: None,
         }
     }
 }
@@ -147,14 +210,97 @@ impl CsvExporter {
     }
     
     /// Set normalization
+    /// 
+    /// Without normalization, providers are written to a single file in the
+    /// official NPPES main file layout, one row per provider.
     pub fn with_normalization(mut self, normalize: bool) -> Self {
         self.normalize = normalize;
         self
     }
+    
+    /// Set the postal code output format
+    /// 
+    /// Applies to the mailing postal code column of the normalized providers
+    /// file. Denormalized output reproduces the NPPES main file layout and
+    /// keeps postal codes as stored.
+    pub fn with_zip_format(mut self, zip_format: ZipFormat) -> Self {
+        self.zip_format = zip_format;
+        self
+    }
+    
+    /// Write labels ("Individual", "California", "Male") instead of NPPES codes
+    /// 
+    /// Applies to every coded column of the normalized files, and appends a
+    /// `gender` column to the providers file. Leave off to keep the raw codes,
+    /// which round-trip through the NPPES parsers, and the providers file's
+    /// original five columns. Denormalized output reproduces the NPPES main
+    /// file layout, which holds codes, so exporting it with labels on is an
+    /// error.
+    pub fn with_decoded_labels(mut self, decoded: bool) -> Self {
+        self.decoded_labels = decoded;
+        self
+    }
+    
+    /// Add `classification`, `specialization` and `display_name` columns to the taxonomies file
+    /// 
+    /// Values come from the dataset's taxonomy reference. Codes it doesn't
+    /// describe, or datasets loaded without one, get empty fields.
+    pub fn with_taxonomy_descriptions(mut self, include: bool) -> Self {
+        self.taxonomy_descriptions = include;
+        self
+    }
+    
+    /// Split normalized output into files of at most `max_rows` providers each
+    /// 
+    /// Files are numbered `<name>_providers_0001.csv`, `<name>_providers_0002.csv`
+    /// and so on, each with its own header row when headers are on. (The single
+    /// `<name>_providers.csv` written without splitting has never had one, and
+    /// still doesn't.) The taxonomies are split to
+    /// match: `<name>_taxonomies_0001.csv` holds the taxonomy rows of exactly the
+    /// providers in `<name>_providers_0001.csv`, so it may have more or fewer
+    /// than `max_rows` rows. A `max_rows` of 0 is treated as 1. Has no effect
+    /// on denormalized output.
+    pub fn with_max_rows_per_file(mut self, max_rows: usize) -> Self {
+        self.max_rows_per_file = Some(max_rows);
+        self
+    }
+    
+    fn entity_type_value(&self, entity_type: Option<&EntityType>) -> String {
+        match entity_type {
+            Some(e) if self.decoded_labels => e.to_string(),
+            Some(e) => e.to_code().to_string(),
+            None => String::new(),
+        }
+    }
+    
+    fn state_value(&self, state: Option<&StateCode>) -> &'static str {
+        match state {
+            Some(s) if self.decoded_labels => s.name(),
+            Some(s) => s.as_code(),
+            None => "",
+        }
+    }
+    
+    fn sex_value(&self, sex: Option<&SexCode>) -> &'static str {
+        match sex {
+            Some(s) if self.decoded_labels => s.description(),
+            Some(s) => s.as_code(),
+            None => "",
+        }
+    }
+    
+    fn flag_value(&self, flag: bool) -> &'static str {
+        match (flag, self.decoded_labels) {
+            (true, true) => "Yes",
+            (false, true) => "No",
+            (true, false) => "Y",
+            (false, false) => "N",
+        }
+    }
 }
 
 impl NppesExporter for CsvExporter {
-    fn export(&self, dataset: &NppesDataset, path: &Path) -> Result<()> {
+    fn export(&self, dataset: &NppesDataset, path: &Path) -> Result<ExportSummary> {
         if self.normalize {
             self.export_normalized(dataset, path)
         } else {
@@ -168,69 +314,514 @@ impl NppesExporter for CsvExporter {
 }
 
 impl CsvExporter {
-    fn export_normalized(&self, dataset: &NppesDataset, base_path: &Path) -> Result<()> {
+    fn export_normalized(&self, dataset: &NppesDataset, base_path: &Path) -> Result<ExportSummary> {
         // Create directory for normalized files
         let dir = base_path.parent().unwrap_or(Path::new("."));
         let base_name = base_path.file_stem()
             .and_then(|s| s.to_str())
             .unwrap_or("nppes_export");
         
-        // Export main provider data
-        let providers_path = dir.join(format!("{}_providers.csv", base_name));
-        let providers_file = File::create(&providers_path)?;
+        let Some(max_rows) = self.max_rows_per_file else {
+            let providers_path = dir.join(format!("{}_providers.csv", base_name));
+            let taxonomy_path = dir.join(format!("{}_taxonomies.csv", base_name));
+            self.write_providers(&dataset.providers, &providers_path, false)?;
+            self.write_taxonomies(dataset, &dataset.providers, &taxonomy_path)?;
+            return ExportSummary::from_files(dataset.providers.len(), vec![providers_path, taxonomy_path]);
+        };
+        
+        // An empty dataset still gets one (header only) file of each kind
+        let mut chunks: Vec<&[NppesRecord]> = dataset.providers.chunks(max_rows.max(1)).collect();
+        if chunks.is_empty() {
+            chunks.push(&[]);
+        }
+        let mut provider_files = Vec::with_capacity(chunks.len());
+        let mut taxonomy_files = Vec::with_capacity(chunks.len());
+        for (i, chunk) in chunks.into_iter().enumerate() {
+            let providers_path = dir.join(format!("{}_providers_{:04}.csv", base_name, i + 1));
+            let taxonomy_path = dir.join(format!("{}_taxonomies_{:04}.csv", base_name, i + 1));
+            self.write_providers(chunk, &providers_path, self.include_headers)?;
+            self.write_taxonomies(dataset, chunk, &taxonomy_path)?;
+            provider_files.push(providers_path);
+            taxonomy_files.push(taxonomy_path);
+        }
+        provider_files.extend(taxonomy_files);
+        ExportSummary::from_files(dataset.providers.len(), provider_files)
+    }
+    
+    fn write_providers(&self, providers: &[NppesRecord], path: &Path, headers: bool) -> Result<()> {
+        let providers_file = File::create(path)?;
         let mut providers_writer = csv::WriterBuilder::new()
             .delimiter(self.delimiter)
             .has_headers(self.include_headers)
             .from_writer(providers_file);
         
+        if headers {
+            let mut header = vec!["npi", "entity_type", "name", "state", "postal_code"];
+            if self.decoded_labels {
+                header.push("gender");
+            }
+            providers_writer.write_record(&header)?;
+        }
+        
         // Write provider records (simplified - would need custom serialization for full data)
-        for provider in &dataset.providers {
-            providers_writer.write_record(&[
+        for provider in providers {
+            let entity_type = self.entity_type_value(provider.entity_type.as_ref());
+            let name = provider.display_name();
+            let postal_code = self.zip_format.format(&provider.mailing_address).unwrap_or_default();
+            let mut row = vec![
                 provider.npi.as_str(),
-                provider.entity_type.as_ref().map_or("", |e| e.to_code()),
-                &provider.display_name(),
-                &provider.mailing_address.state.as_ref().map(|s| s.as_code().to_string()).unwrap_or("".to_string()),
-                provider.mailing_address.postal_code.as_deref().unwrap_or(""),
-            ])?;
+                &entity_type,
+                &name,
+                self.state_value(provider.mailing_address.state.as_ref()),
+                &postal_code,
+            ];
+            if self.decoded_labels {
+                row.push(self.sex_value(provider.provider_gender.as_ref()));
+            }
+            providers_writer.write_record(&row)?;
         }
         providers_writer.flush()?;
-        
-        // Export taxonomy codes
-        let taxonomy_path = dir.join(format!("{}_taxonomies.csv", base_name));
-        let taxonomy_file = File::create(&taxonomy_path)?;
+        Ok(())
+    }
+    
+    fn write_taxonomies(&self, dataset: &NppesDataset, providers: &[NppesRecord], path: &Path) -> Result<()> {
+        let taxonomy_file = File::create(path)?;
         let mut taxonomy_writer = csv::WriterBuilder::new()
             .delimiter(self.delimiter)
             .has_headers(self.include_headers)
             .from_writer(taxonomy_file);
         
         if self.include_headers {
-            taxonomy_writer.write_record(&["npi", "taxonomy_code", "is_primary", "license_number", "license_state"])?;
+            let mut headers = vec!["npi", "taxonomy_code", "is_primary", "license_number", "license_state"];
+            if self.taxonomy_descriptions {
+                headers.extend(["classification", "specialization", "display_name"]);
+            }
+            taxonomy_writer.write_record(&headers)?;
         }
         
-        for provider in &dataset.providers {
+        for provider in providers {
             for taxonomy in &provider.taxonomy_codes {
-                taxonomy_writer.write_record(&[
+                let mut row = vec![
                     provider.npi.as_str(),
                     &taxonomy.code,
-                    if taxonomy.is_primary { "Y" } else { "N" },
+                    self.flag_value(taxonomy.is_primary),
                     taxonomy.license_number.as_deref().unwrap_or(""),
-                    taxonomy.license_state.as_deref().unwrap_or(""),
-                ])?;
+                    match &taxonomy.license_state_code {
+                        Some(state) if self.decoded_labels => state.name(),
+                        _ => taxonomy.license_state.as_deref().unwrap_or(""),
+                    },
+                ];
+                if self.taxonomy_descriptions {
+                    let reference = dataset.get_taxonomy_description(&taxonomy.code);
+                    let field = |value: fn(&TaxonomyReference) -> &Option<String>| {
+                        reference.and_then(|r| value(r).as_deref()).unwrap_or("")
+                    };
+                    row.extend([
+                        field(|r| &r.classification),
+                        field(|r| &r.specialization),
+                        field(|r| &r.display_name),
+                    ]);
+                }
+                taxonomy_writer.write_record(&row)?;
             }
         }
         taxonomy_writer.flush()?;
+        Ok(())
+    }
+    
+    fn export_denormalized(&self, dataset: &NppesDataset, path: &Path) -> Result<ExportSummary> {
+        if self.decoded_labels {
+            return Err(NppesError::Configuration {
+                message: "Decoded labels are not supported for denormalized CSV output".to_string(),
+                suggestion: Some("Enable normalization or turn off decoded labels".to_string()),
+            });
+        }
+        
+        // Export as single denormalized file in the original NPPES main file layout
+        let file = File::create(path)?;
+        let mut writer = csv::WriterBuilder::new()
+            .delimiter(self.delimiter)
+            .has_headers(false)
+            .from_writer(BufWriter::new(file));
+        
+        if self.include_headers {
+            writer.write_record(crate::schema::NppesMainSchema::column_names())?;
+        }
+        for provider in &dataset.providers {
+            writer.write_record(denormalized_row(provider))?;
+        }
+        writer.flush()?;
+        
+        ExportSummary::from_files(dataset.providers.len(), vec![path.to_path_buf()])
+    }
+}
+
+/// Lay out a record as a row in the official NPPES main file column order
+///
+/// This is the inverse of the main file parser: every field is written to the
+/// column named for it in `NppesMainSchema::column_names()`.
+pub(crate) fn denormalized_row(record: &NppesRecord) -> Vec<String> {
+    use crate::constants::{MAX_OTHER_IDENTIFIERS, MAX_TAXONOMY_CODES};
+    use crate::schema::NppesMainSchema;
+
+    fn opt(value: &Option<String>) -> String {
+        value.clone().unwrap_or_default()
+    }
+    fn ein(value: &Option<Ein>) -> String {
+        value.as_ref().map(|e| e.as_str().to_string()).unwrap_or_default()
+    }
+    fn date(value: &Option<chrono::NaiveDate>) -> String {
+        value.map(|d| d.format("%m/%d/%Y").to_string()).unwrap_or_default()
+    }
+    fn write_address(row: &mut [String], base: usize, address: &Address) {
+        row[base] = opt(&address.line_1);
+        row[base + 1] = opt(&address.line_2);
+        row[base + 2] = opt(&address.city);
+        row[base + 3] = address.state.as_ref().map(|s| s.as_code().to_string()).unwrap_or_default();
+        row[base + 4] = opt(&address.postal_code);
+        row[base + 5] = address.country.as_ref().map(|c| c.as_code().to_string()).unwrap_or_default();
+        row[base + 6] = opt(&address.telephone);
+        row[base + 7] = opt(&address.fax);
+    }
+
+    let mut row = vec![String::new(); NppesMainSchema::column_count()];
+
+    // Core identifiers and names
+    row[0] = record.npi.as_str().to_string();
+    row[1] = record.entity_type.as_ref().map(|e| e.to_code().to_string()).unwrap_or_default();
+    row[2] = record.replacement_npi.as_ref().map(|n| n.as_str().to_string()).unwrap_or_default();
+    row[3] = ein(&record.ein);
+    row[4] = opt(&record.organization_name.legal_business_name);
+
+    let name = &record.provider_name;
+    row[5] = opt(&name.last);
+    row[6] = opt(&name.first);
+    row[7] = opt(&name.middle);
+    row[8] = name.prefix.as_ref().map(|p| p.as_code().to_string()).unwrap_or_default();
+    row[9] = name.suffix.as_ref().map(|s| s.as_code().to_string()).unwrap_or_default();
+    row[10] = opt(&name.credential);
+
+    row[11] = opt(&record.organization_name.other_name);
+    row[12] = record.organization_name.other_name_type.as_ref().map(|t| t.as_code().to_string()).unwrap_or_default();
+
+    let other = &record.provider_other_name;
+    row[13] = opt(&other.last);
+    row[14] = opt(&other.first);
+    row[15] = opt(&other.middle);
+    row[16] = other.prefix.as_ref().map(|p| p.as_code().to_string()).unwrap_or_default();
+    row[17] = other.suffix.as_ref().map(|s| s.as_code().to_string()).unwrap_or_default();
+    row[18] = opt(&other.credential);
+    row[19] = record.provider_other_name_type.as_ref().map(|t| t.as_code().to_string()).unwrap_or_default();
+
+    // Addresses
+    write_address(&mut row, 20, &record.mailing_address);
+    write_address(&mut row, 28, &record.practice_address);
+
+    // Dates and status
+    row[36] = date(&record.enumeration_date);
+    row[37] = date(&record.last_update_date);
+    row[38] = record.deactivation_reason.as_ref().map(|r| r.as_code().to_string()).unwrap_or_default();
+    row[39] = date(&record.deactivation_date);
+    row[40] = date(&record.reactivation_date);
+    row[41] = record.provider_gender.as_ref().map(|g| g.as_code().to_string()).unwrap_or_default();
+
+    // Authorized official
+    if let Some(official) = &record.authorized_official {
+        row[42] = opt(&official.last_name);
+        row[43] = opt(&official.first_name);
+        row[44] = opt(&official.middle_name);
+        row[45] = opt(&official.title);
+        row[46] = opt(&official.telephone);
+        row[311] = official.prefix.as_ref().map(|p| p.as_code().to_string()).unwrap_or_default();
+        row[312] = official.suffix.as_ref().map(|s| s.as_code().to_string()).unwrap_or_default();
+        row[313] = opt(&official.credential);
+    }
+
+    // Taxonomy codes and their groups
+    for (i, taxonomy) in record.taxonomy_codes.iter().take(MAX_TAXONOMY_CODES).enumerate() {
+        let base = 47 + i * 4;
+        row[base] = taxonomy.code.clone();
+        row[base + 1] = opt(&taxonomy.license_number);
+        row[base + 2] = taxonomy.license_state.clone()
+            .or_else(|| taxonomy.license_state_code.as_ref().map(|s| s.as_code().to_string()))
+            .unwrap_or_default();
+        row[base + 3] = match &taxonomy.primary_switch {
+            Some(switch) => switch.as_code().to_string(),
+            None if taxonomy.is_primary => "Y".to_string(),
+            None => String::new(),
+        };
+        row[314 + i] = taxonomy.taxonomy_group.clone()
+            .or_else(|| taxonomy.group_taxonomy_code.as_ref().map(|g| g.as_code().to_string()))
+            .unwrap_or_default();
+    }
+
+    // Other identifiers
+    for (i, identifier) in record.other_identifiers.iter().take(MAX_OTHER_IDENTIFIERS).enumerate() {
+        let base = 107 + i * 4;
+        row[base] = identifier.identifier.clone();
+        row[base + 1] = opt(&identifier.type_code);
+        row[base + 2] = identifier.state.as_ref().map(|s| s.as_code().to_string()).unwrap_or_default();
+        row[base + 3] = identifier.issuer.as_ref().map(|i| i.as_code().to_string()).unwrap_or_default();
+    }
+
+    // Organization flags and parent info
+    row[307] = record.sole_proprietor.as_ref().map(|s| s.as_code().to_string()).unwrap_or_default();
+    row[308] = record.organization_subpart.as_ref().map(|s| s.as_code().to_string()).unwrap_or_default();
+    row[309] = opt(&record.parent_organization_lbn);
+    row[310] = ein(&record.parent_organization_tin);
+    row[329] = date(&record.certification_date);
+
+    row
+}
+
+/// Search document exporter for NPPES data
+/// 
+/// Writes one flattened JSON document per line for indexing in a search engine.
+/// Names and specialties are resolved and deduplicated rather than reproduced
+/// faithfully; use [`JsonExporter`] for a lossless export.
+#[derive(Debug, Clone, Default)]
+pub struct SearchDocExporter;
+
+/// A provider's search document
+#[derive(Debug, Clone, serde::Serialize)]
+struct SearchDoc<'a> {
+    npi: &'a str,
+    full_name: String,
+    all_names: Vec<String>,
+    specialties: Vec<String>,
+    city: Option<&'a str>,
+    state: Option<&'a str>,
+    zip5: Option<String>,
+    active: bool,
+}
+
+impl SearchDocExporter {
+    /// Create a new search document exporter
+    pub fn new() -> Self {
+        Self
+    }
+    
+    /// Build the search document for a single provider
+    fn document<'a>(dataset: &'a NppesDataset, provider: &'a NppesRecord) -> SearchDoc<'a> {
+        let full_name = provider.full_display_name();
+        
+        let other_names = dataset.get_other_names(&provider.npi).map_or(&[][..], |names| names.as_slice());
+        let mut all_names = provider.all_names();
+        for (name, _) in provider.organization_other_names(other_names) {
+            if !all_names.iter().any(|existing| existing.eq_ignore_ascii_case(name)) {
+                all_names.push(name.to_string());
+            }
+        }
+        
+        let mut specialties: Vec<String> = Vec::new();
+        for taxonomy in &provider.taxonomy_codes {
+            let reference = dataset.get_taxonomy_description(&taxonomy.code);
+            let specialty = reference
+                .and_then(|r| r.display_name.clone().or_else(|| r.classification.clone()))
+                .unwrap_or_else(|| taxonomy.code.clone());
+            if !specialties.contains(&specialty) {
+                specialties.push(specialty);
+            }
+        }
+        
+        let address = &provider.practice_address;
+        SearchDoc {
+            npi: provider.npi.as_str(),
+            full_name,
+            all_names,
+            specialties,
+            city: address.city.as_deref(),
+            state: address.state.as_ref().map(|s| s.as_code()),
+            zip5: address.zip5(),
+            active: provider.is_active(),
+        }
+    }
+}
+
+impl NppesExporter for SearchDocExporter {
+    fn export(&self, dataset: &NppesDataset, path: &Path) -> Result<ExportSummary> {
+        let file = File::create(path)?;
+        let mut writer = BufWriter::new(file);
+        
+        for provider in &dataset.providers {
+            let json = serde_json::to_string(&Self::document(dataset, provider))?;
+            writeln!(writer, "{}", json)?;
+        }
         
-        println!("Exported normalized CSV files to: {}", dir.display());
+        writer.flush()?;
+        ExportSummary::from_files(dataset.providers.len(), vec![path.to_path_buf()])
+    }
+    
+    fn format(&self) -> ExportFormat {
+        ExportFormat::Json
+    }
+}
+
+/// XML exporter for NPPES data
+/// 
+/// Writes a UTF-8 `<providers>` document with one `<provider npi="...">`
+/// element per record. Child elements are named after the record fields, with
+/// nested `<taxonomy>` and `<other_identifier>` elements in file order. Coded
+/// fields hold NPPES codes, dates are ISO 8601 and empty fields are omitted.
+#[derive(Debug, Clone, Default)]
+pub struct XmlExporter;
+
+/// `(element name, value)` pairs written inside an XML group element
+type XmlFields<'a> = [(&'a str, Option<&'a str>)];
+
+impl XmlExporter {
+    /// Create a new XML exporter
+    pub fn new() -> Self {
+        Self
+    }
+    
+    /// Child elements of a `<provider_name>`
+    fn name_fields(name: &ProviderName) -> [(&'static str, Option<&str>); 6] {
+        [
+            ("prefix", name.prefix.as_ref().map(NamePrefixCode::as_code)),
+            ("first", name.first.as_deref()),
+            ("middle", name.middle.as_deref()),
+            ("last", name.last.as_deref()),
+            ("suffix", name.suffix.as_ref().map(NameSuffixCode::as_code)),
+            ("credential", name.credential.as_deref()),
+        ]
+    }
+    
+    /// Child elements of a `<mailing_address>` or `<practice_address>`
+    fn address_fields(address: &Address) -> [(&'static str, Option<&str>); 8] {
+        [
+            ("line_1", address.line_1.as_deref()),
+            ("line_2", address.line_2.as_deref()),
+            ("city", address.city.as_deref()),
+            ("state", address.state.as_ref().map(StateCode::as_code)),
+            ("postal_code", address.postal_code.as_deref()),
+            ("country", address.country.as_ref().map(CountryCode::as_code)),
+            ("telephone", address.telephone.as_deref()),
+            ("fax", address.fax.as_deref()),
+        ]
+    }
+    
+    /// Write a single `<provider>` element
+    fn write_provider(writer: &mut dyn Write, provider: &NppesRecord) -> Result<()> {
+        let date = |value: Option<chrono::NaiveDate>| value.map(|d| d.format("%Y-%m-%d").to_string());
+        writeln!(writer, "  <provider npi=\"{}\">", xml_escape(provider.npi.as_str()))?;
+        xml_element(writer, 4, "entity_type", provider.entity_type.as_ref().map(EntityType::to_code))?;
+        xml_element(writer, 4, "replacement_npi", provider.replacement_npi.as_ref().map(Npi::as_str))?;
+        xml_element(writer, 4, "ein", provider.ein.as_ref().map(Ein::as_str))?;
+        xml_group(writer, 4, "provider_name", &Self::name_fields(&provider.provider_name))?;
+        xml_group(writer, 4, "provider_other_name", &Self::name_fields(&provider.provider_other_name))?;
+        xml_element(writer, 4, "provider_other_name_type", provider.provider_other_name_type.as_ref().map(OtherProviderNameTypeCode::as_code))?;
+        let organization = &provider.organization_name;
+        xml_group(writer, 4, "organization_name", &[
+            ("legal_business_name", organization.legal_business_name.as_deref()),
+            ("other_name", organization.other_name.as_deref()),
+            ("other_name_type", organization.other_name_type.as_ref().map(OtherProviderNameTypeCode::as_code)),
+        ])?;
+        xml_group(writer, 4, "mailing_address", &Self::address_fields(&provider.mailing_address))?;
+        xml_group(writer, 4, "practice_address", &Self::address_fields(&provider.practice_address))?;
+        xml_element(writer, 4, "enumeration_date", date(provider.enumeration_date).as_deref())?;
+        xml_element(writer, 4, "last_update_date", date(provider.last_update_date).as_deref())?;
+        xml_element(writer, 4, "deactivation_reason", provider.deactivation_reason.as_ref().map(DeactivationReasonCode::as_code))?;
+        xml_element(writer, 4, "deactivation_date", date(provider.deactivation_date).as_deref())?;
+        xml_element(writer, 4, "reactivation_date", date(provider.reactivation_date).as_deref())?;
+        xml_element(writer, 4, "certification_date", date(provider.certification_date).as_deref())?;
+        xml_element(writer, 4, "gender", provider.provider_gender.as_ref().map(SexCode::as_code))?;
+        if let Some(official) = &provider.authorized_official {
+            xml_group(writer, 4, "authorized_official", &[
+                ("prefix", official.prefix.as_ref().map(NamePrefixCode::as_code)),
+                ("first_name", official.first_name.as_deref()),
+                ("middle_name", official.middle_name.as_deref()),
+                ("last_name", official.last_name.as_deref()),
+                ("suffix", official.suffix.as_ref().map(NameSuffixCode::as_code)),
+                ("credential", official.credential.as_deref()),
+                ("title", official.title.as_deref()),
+                ("telephone", official.telephone.as_deref()),
+            ])?;
+        }
+        for taxonomy in &provider.taxonomy_codes {
+            xml_group(writer, 4, "taxonomy", &[
+                ("code", Some(taxonomy.code.as_str())),
+                ("primary", Some(if taxonomy.is_primary { "true" } else { "false" })),
+                ("license_number", taxonomy.license_number.as_deref()),
+                ("license_state", taxonomy.license_state.as_deref()),
+                ("group_taxonomy_code", taxonomy.group_taxonomy_code.as_ref().map(GroupTaxonomyCode::as_code)),
+            ])?;
+        }
+        for identifier in &provider.other_identifiers {
+            xml_group(writer, 4, "other_identifier", &[
+                ("identifier", Some(identifier.identifier.as_str())),
+                ("type_code", identifier.type_code.as_deref()),
+                ("issuer", identifier.issuer.as_ref().map(OtherProviderIdentifierIssuerCode::as_code)),
+                ("state", identifier.state.as_ref().map(StateCode::as_code)),
+            ])?;
+        }
+        xml_element(writer, 4, "sole_proprietor", provider.sole_proprietor.as_ref().map(SoleProprietorCode::as_code))?;
+        xml_element(writer, 4, "organization_subpart", provider.organization_subpart.as_ref().map(SubpartCode::as_code))?;
+        xml_element(writer, 4, "parent_organization_lbn", provider.parent_organization_lbn.as_deref())?;
+        xml_element(writer, 4, "parent_organization_tin", provider.parent_organization_tin.as_ref().map(Ein::as_str))?;
+        writeln!(writer, "  </provider>")?;
         Ok(())
     }
+}
+
+impl NppesExporter for XmlExporter {
+    fn export(&self, dataset: &NppesDataset, path: &Path) -> Result<ExportSummary> {
+        let file = File::create(path)?;
+        let mut writer = BufWriter::new(file);
+        
+        writeln!(writer, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>")?;
+        writeln!(writer, "<providers>")?;
+        for provider in &dataset.providers {
+            Self::write_provider(&mut writer, provider)?;
+        }
+        writeln!(writer, "</providers>")?;
+        
+        writer.flush()?;
+        ExportSummary::from_files(dataset.providers.len(), vec![path.to_path_buf()])
+    }
     
-    fn export_denormalized(&self, dataset: &NppesDataset, path: &Path) -> Result<()> {
-        // Export as single denormalized file (similar to original NPPES format)
-        Err(NppesError::Custom {
-            message: "Denormalized CSV export not yet implemented".to_string(),
-            suggestion: Some("Use normalized export or JSON export instead".to_string()),
-        })
+    fn format(&self) -> ExportFormat {
+        ExportFormat::Xml
+    }
+}
+
+/// Write `<name>value</name>` on its own line, skipping missing or empty values
+fn xml_element(writer: &mut dyn Write, indent: usize, name: &str, value: Option<&str>) -> Result<()> {
+    if let Some(value) = value.filter(|v| !v.is_empty()) {
+        writeln!(writer, "{:indent$}<{name}>{}</{name}>", "", xml_escape(value))?;
     }
+    Ok(())
+}
+
+/// Write an element wrapping `fields`, skipping it when every field is empty
+fn xml_group(writer: &mut dyn Write, indent: usize, name: &str, fields: &XmlFields) -> Result<()> {
+    if fields.iter().all(|(_, value)| value.is_none_or(str::is_empty)) {
+        return Ok(());
+    }
+    writeln!(writer, "{:indent$}<{name}>", "")?;
+    for (field, value) in fields {
+        xml_element(writer, indent + 2, field, *value)?;
+    }
+    writeln!(writer, "{:indent$}</{name}>", "")?;
+    Ok(())
+}
+
+/// Escape XML markup characters and drop control characters XML 1.0 can't represent
+fn xml_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\t' | '\n' | '\r' => escaped.push(c),
+            c if (c as u32) < 0x20 => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
 }
 
 /// SQL exporter for NPPES data
@@ -243,6 +834,10 @@ pub struct SqlExporter {
     pub batch_size: usize,
     /// Whether to include CREATE TABLE statements
     pub include_schema: bool,
+    /// Postal code output format
+    pub zip_format: ZipFormat,
+    /// Emit PostgreSQL `COPY ... FROM STDIN` data instead of `INSERT` statements
+    pub use_copy: bool,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -260,6 +855,8 @@ impl Default for SqlExporter {
             table_prefix: "nppes".to_string(),
             batch_size: 1000,
             include_schema: true,
+            zip_format: ZipFormat::default(),
+            use_copy: false,
         }
     }
 }
@@ -281,10 +878,35 @@ impl SqlExporter {
         self.table_prefix = prefix;
         self
     }
+    
+    /// Set the postal code output format
+    /// 
+    /// Applies to the `mailing_address_postal_code` column in both `INSERT`
+    /// and `COPY` output.
+    pub fn with_zip_format(mut self, zip_format: ZipFormat) -> Self {
+        self.zip_format = zip_format;
+        self
+    }
+    
+    /// Emit provider rows as a PostgreSQL `COPY ... FROM STDIN` block
+    /// 
+    /// Loads much faster than batched `INSERT`s with `psql -f`. Only supported
+    /// for the PostgreSQL dialect.
+    pub fn with_copy(mut self, use_copy: bool) -> Self {
+        self.use_copy = use_copy;
+        self
+    }
 }
 
 impl NppesExporter for SqlExporter {
-    fn export(&self, dataset: &NppesDataset, path: &Path) -> Result<()> {
+    fn export(&self, dataset: &NppesDataset, path: &Path) -> Result<ExportSummary> {
+        if self.use_copy && !matches!(self.dialect, SqlDialect::PostgreSQL) {
+            return Err(NppesError::Configuration {
+                message: format!("COPY output is not supported for {:?}", self.dialect),
+                suggestion: Some("Use SqlDialect::PostgreSQL or disable COPY output".to_string()),
+            });
+        }
+        
         let file = File::create(path)?;
         let mut writer = BufWriter::new(file);
         
@@ -292,11 +914,16 @@ impl NppesExporter for SqlExporter {
             self.write_schema(&mut writer)?;
         }
         
-        // Write provider inserts
+        // Write provider data
         writeln!(writer, "\n-- Provider data")?;
-        self.write_provider_inserts(&mut writer, &dataset.providers)?;
+        if self.use_copy {
+            self.write_provider_copy(&mut writer, &dataset.providers)?;
+        } else {
+            self.write_provider_inserts(&mut writer, &dataset.providers)?;
+        }
+        writer.flush()?;
         
-        Ok(())
+        ExportSummary::from_files(dataset.providers.len(), vec![path.to_path_buf()])
     }
     
     fn format(&self) -> ExportFormat {
@@ -304,92 +931,189 @@ impl NppesExporter for SqlExporter {
     }
 }
 
+impl SqlDialect {
+    /// Quote an identifier (table or column name)
+    fn quote(&self, identifier: &str) -> String {
+        match self {
+            SqlDialect::PostgreSQL | SqlDialect::SQLite => format!("\"{}\"", identifier),
+            SqlDialect::MySQL => format!("`{}`", identifier),
+            SqlDialect::SqlServer => format!("[{}]", identifier),
+        }
+    }
+    
+    /// Column type for text of at most `length` characters
+    fn text_type(&self, length: usize) -> String {
+        match self {
+            SqlDialect::PostgreSQL | SqlDialect::MySQL => format!("VARCHAR({})", length),
+            SqlDialect::SQLite => "TEXT".to_string(),
+            SqlDialect::SqlServer => format!("NVARCHAR({})", length),
+        }
+    }
+    
+    /// Column type for small integer codes
+    fn small_int_type(&self) -> &'static str {
+        match self {
+            SqlDialect::PostgreSQL => "SMALLINT",
+            SqlDialect::MySQL | SqlDialect::SqlServer => "TINYINT",
+            SqlDialect::SQLite => "INTEGER",
+        }
+    }
+    
+    /// Column type for calendar dates
+    fn date_type(&self) -> &'static str {
+        match self {
+            SqlDialect::SQLite => "TEXT",
+            _ => "DATE",
+        }
+    }
+    
+    /// Column type for flags
+    fn boolean_type(&self) -> &'static str {
+        match self {
+            SqlDialect::PostgreSQL | SqlDialect::MySQL => "BOOLEAN",
+            SqlDialect::SQLite => "INTEGER",
+            SqlDialect::SqlServer => "BIT",
+        }
+    }
+    
+    /// Boolean literal
+    fn boolean(&self, value: bool) -> &'static str {
+        match (self, value) {
+            (SqlDialect::PostgreSQL | SqlDialect::MySQL, true) => "TRUE",
+            (SqlDialect::PostgreSQL | SqlDialect::MySQL, false) => "FALSE",
+            (SqlDialect::SQLite | SqlDialect::SqlServer, true) => "1",
+            (SqlDialect::SQLite | SqlDialect::SqlServer, false) => "0",
+        }
+    }
+    
+    /// Auto-incrementing integer primary key column definition
+    fn auto_id(&self) -> &'static str {
+        match self {
+            SqlDialect::PostgreSQL => "SERIAL PRIMARY KEY",
+            SqlDialect::MySQL => "INT AUTO_INCREMENT PRIMARY KEY",
+            SqlDialect::SQLite => "INTEGER PRIMARY KEY AUTOINCREMENT",
+            SqlDialect::SqlServer => "INT IDENTITY(1,1) PRIMARY KEY",
+        }
+    }
+    
+    /// Quote a string literal, or `NULL`
+    fn string(&self, value: Option<&str>) -> String {
+        match (self, value) {
+            (_, None) => "NULL".to_string(),
+            // MySQL treats backslashes in literals as escapes by default
+            (SqlDialect::MySQL, Some(s)) => format!("'{}'", s.replace('\\', "\\\\").replace('\'', "''")),
+            // N'' keeps non-ASCII names intact in NVARCHAR columns
+            (SqlDialect::SqlServer, Some(s)) => format!("N'{}'", s.replace('\'', "''")),
+            (_, Some(s)) => format!("'{}'", s.replace('\'', "''")),
+        }
+    }
+}
+
 impl SqlExporter {
-    fn write_schema(&self, writer: &mut dyn Write) -> Result<()> {
+    /// Quoted name of one of the exported tables
+    fn table(&self, name: &str) -> String {
+        self.dialect.quote(&format!("{}_{}", self.table_prefix, name))
+    }
+    
+    /// Quoted, comma-separated column list
+    fn columns(&self, names: &[&str]) -> String {
+        names.iter().map(|name| self.dialect.quote(name)).collect::<Vec<_>>().join(", ")
+    }
+    
+    /// `CREATE TABLE` opening line, guarded against existing tables
+    fn create_table(&self, writer: &mut dyn Write, name: &str) -> Result<()> {
         match self.dialect {
-            SqlDialect::PostgreSQL => {
-                writeln!(writer, "-- NPPES Database Schema for PostgreSQL\n")?;
-                writeln!(writer, "CREATE TABLE IF NOT EXISTS {}_providers (", self.table_prefix)?;
-                writeln!(writer, "  npi VARCHAR(10) PRIMARY KEY,")?;
-                writeln!(writer, "  entity_type SMALLINT NOT NULL,")?;
-                writeln!(writer, "  organization_name VARCHAR(255),")?;
-                writeln!(writer, "  last_name VARCHAR(100),")?;
-                writeln!(writer, "  first_name VARCHAR(100),")?;
-                writeln!(writer, "  middle_name VARCHAR(100),")?;
-                writeln!(writer, "  mailing_address_line1 VARCHAR(255),")?;
-                writeln!(writer, "  mailing_address_city VARCHAR(100),")?;
-                writeln!(writer, "  mailing_address_state VARCHAR(2),")?;
-                writeln!(writer, "  mailing_address_postal_code VARCHAR(10),")?;
-                writeln!(writer, "  enumeration_date DATE,")?;
-                writeln!(writer, "  last_update_date DATE,")?;
-                writeln!(writer, "  is_active BOOLEAN DEFAULT TRUE")?;
-                writeln!(writer, ");\n")?;
-                
-                writeln!(writer, "CREATE TABLE IF NOT EXISTS {}_taxonomies (", self.table_prefix)?;
-                writeln!(writer, "  id SERIAL PRIMARY KEY,")?;
-                writeln!(writer, "  npi VARCHAR(10) REFERENCES {}_providers(npi),", self.table_prefix)?;
-                writeln!(writer, "  taxonomy_code VARCHAR(10) NOT NULL,")?;
-                writeln!(writer, "  is_primary BOOLEAN DEFAULT FALSE,")?;
-                writeln!(writer, "  license_number VARCHAR(50),")?;
-                writeln!(writer, "  license_state VARCHAR(2)")?;
-                writeln!(writer, ");\n")?;
-                
-                writeln!(writer, "CREATE INDEX idx_{}_state ON {}_providers(mailing_address_state);", 
-                    self.table_prefix, self.table_prefix)?;
-                writeln!(writer, "CREATE INDEX idx_{}_taxonomy ON {}_taxonomies(taxonomy_code);", 
-                    self.table_prefix, self.table_prefix)?;
-            }
-            _ => {
-                writeln!(writer, "-- Schema generation for {:?} not yet implemented", self.dialect)?;
+            SqlDialect::SqlServer => {
+                writeln!(writer, "IF OBJECT_ID(N'{}_{}', N'U') IS NULL", self.table_prefix, name)?;
+                writeln!(writer, "CREATE TABLE {} (", self.table(name))?;
             }
+            _ => writeln!(writer, "CREATE TABLE IF NOT EXISTS {} (", self.table(name))?,
         }
         Ok(())
     }
     
+    fn write_schema(&self, writer: &mut dyn Write) -> Result<()> {
+        let d = self.dialect;
+        let q = |name: &str| d.quote(name);
+        
+        writeln!(writer, "-- NPPES Database Schema for {:?}\n", d)?;
+        self.create_table(writer, "providers")?;
+        writeln!(writer, "  {} {} PRIMARY KEY,", q("npi"), d.text_type(10))?;
+        writeln!(writer, "  {} {} NOT NULL,", q("entity_type"), d.small_int_type())?;
+        writeln!(writer, "  {} {},", q("organization_name"), d.text_type(255))?;
+        writeln!(writer, "  {} {},", q("last_name"), d.text_type(100))?;
+        writeln!(writer, "  {} {},", q("first_name"), d.text_type(100))?;
+        writeln!(writer, "  {} {},", q("middle_name"), d.text_type(100))?;
+        writeln!(writer, "  {} {},", q("mailing_address_line1"), d.text_type(255))?;
+        writeln!(writer, "  {} {},", q("mailing_address_city"), d.text_type(100))?;
+        writeln!(writer, "  {} {},", q("mailing_address_state"), d.text_type(2))?;
+        writeln!(writer, "  {} {},", q("mailing_address_postal_code"), d.text_type(10))?;
+        writeln!(writer, "  {} {},", q("enumeration_date"), d.date_type())?;
+        writeln!(writer, "  {} {},", q("last_update_date"), d.date_type())?;
+        writeln!(writer, "  {} {} DEFAULT {}", q("is_active"), d.boolean_type(), d.boolean(true))?;
+        writeln!(writer, ");\n")?;
+        
+        self.create_table(writer, "taxonomies")?;
+        writeln!(writer, "  {} {},", q("id"), d.auto_id())?;
+        writeln!(writer, "  {} {},", q("npi"), d.text_type(10))?;
+        writeln!(writer, "  {} {} NOT NULL,", q("taxonomy_code"), d.text_type(10))?;
+        writeln!(writer, "  {} {} DEFAULT {},", q("is_primary"), d.boolean_type(), d.boolean(false))?;
+        writeln!(writer, "  {} {},", q("license_number"), d.text_type(50))?;
+        writeln!(writer, "  {} {},", q("license_state"), d.text_type(2))?;
+        writeln!(writer, "  FOREIGN KEY ({}) REFERENCES {}({})", q("npi"), self.table("providers"), q("npi"))?;
+        writeln!(writer, ");\n")?;
+        
+        writeln!(writer, "CREATE INDEX {} ON {}({});",
+            q(&format!("idx_{}_state", self.table_prefix)), self.table("providers"), q("mailing_address_state"))?;
+        writeln!(writer, "CREATE INDEX {} ON {}({});",
+            q(&format!("idx_{}_taxonomy", self.table_prefix)), self.table("taxonomies"), q("taxonomy_code"))?;
+        Ok(())
+    }
+    
     fn write_provider_inserts(&self, writer: &mut dyn Write, providers: &[NppesRecord]) -> Result<()> {
+        let d = self.dialect;
+        let text = |value: &Option<String>| d.string(value.as_deref());
         let mut count = 0;
         
         for chunk in providers.chunks(self.batch_size) {
-            writeln!(writer, "INSERT INTO {}_providers (npi, entity_type, organization_name, last_name, first_name, middle_name, mailing_address_line1, mailing_address_city, mailing_address_state, mailing_address_postal_code, enumeration_date, last_update_date, is_active) VALUES", 
-                self.table_prefix)?;
+            writeln!(writer, "INSERT INTO {} ({}) VALUES", self.table("providers"), self.columns(&PROVIDER_COLUMNS))?;
             
             for (i, provider) in chunk.iter().enumerate() {
                 let state_code_opt: Option<String> = provider.mailing_address.state.as_ref().map(|s| s.as_code().to_string());
                 let values = match provider.entity_type {
                     Some(EntityType::Organization) => {
-                        format!("('{}', {}, {}, NULL, NULL, NULL, {}, {}, {}, {}, {}, {}, {})",
-                            provider.npi.as_str(),
+                        format!("({}, {}, {}, NULL, NULL, NULL, {}, {}, {}, {}, {}, {}, {})",
+                            d.string(Some(provider.npi.as_str())),
                             provider.entity_type.as_ref().map_or("NULL", |e| e.to_code()),
-                            sql_string(&provider.organization_name.legal_business_name),
-                            sql_string(&provider.mailing_address.line_1),
-                            sql_string(&provider.mailing_address.city),
-                            sql_string(&state_code_opt),
-                            sql_string(&provider.mailing_address.postal_code),
+                            text(&provider.organization_name.legal_business_name),
+                            text(&provider.mailing_address.line_1),
+                            text(&provider.mailing_address.city),
+                            text(&state_code_opt),
+                            text(&self.zip_format.format(&provider.mailing_address)),
                             sql_date(&provider.enumeration_date),
                             sql_date(&provider.last_update_date),
-                            provider.is_active()
+                            d.boolean(provider.is_active())
                         )
                     }
                     Some(EntityType::Individual) => {
-                        let state_code_opt: Option<String> = provider.mailing_address.state.as_ref().map(|s| s.as_code().to_string());
-                        format!("('{}', {}, NULL, {}, {}, {}, {}, {}, {}, {}, {}, {}, {})",
-                            provider.npi.as_str(),
+                        format!("({}, {}, NULL, {}, {}, {}, {}, {}, {}, {}, {}, {}, {})",
+                            d.string(Some(provider.npi.as_str())),
                             provider.entity_type.as_ref().map_or("NULL", |e| e.to_code()),
-                            sql_string(&provider.provider_name.last),
-                            sql_string(&provider.provider_name.first),
-                            sql_string(&provider.provider_name.middle),
-                            sql_string(&provider.mailing_address.line_1),
-                            sql_string(&provider.mailing_address.city),
-                            sql_string(&state_code_opt),
-                            sql_string(&provider.mailing_address.postal_code),
+                            text(&provider.provider_name.last),
+                            text(&provider.provider_name.first),
+                            text(&provider.provider_name.middle),
+                            text(&provider.mailing_address.line_1),
+                            text(&provider.mailing_address.city),
+                            text(&state_code_opt),
+                            text(&self.zip_format.format(&provider.mailing_address)),
                             sql_date(&provider.enumeration_date),
                             sql_date(&provider.last_update_date),
-                            provider.is_active()
+                            d.boolean(provider.is_active())
                         )
                     }
                     None => {
                         // Fallback for missing entity_type
-                        format!("('{}', NULL, NULL, NULL, NULL, NULL, NULL, NULL, NULL, NULL, NULL, NULL, NULL)", provider.npi.as_str())
+                        format!("({}, NULL, NULL, NULL, NULL, NULL, NULL, NULL, NULL, NULL, NULL, NULL, NULL)", d.string(Some(provider.npi.as_str())))
                     }
                 };
                 
@@ -408,16 +1132,64 @@ impl SqlExporter {
         
         Ok(())
     }
+    
+    fn write_provider_copy(&self, writer: &mut dyn Write, providers: &[NppesRecord]) -> Result<()> {
+        writeln!(writer, "COPY {} ({}) FROM STDIN;", self.table("providers"), self.columns(&PROVIDER_COLUMNS))?;
+        
+        for provider in providers {
+            let is_organization = provider.entity_type == Some(EntityType::Organization);
+            let is_individual = provider.entity_type == Some(EntityType::Individual);
+            let name_field = |value: &Option<String>, applies: bool| {
+                if applies { copy_text(value.as_deref()) } else { COPY_NULL.to_string() }
+            };
+            
+            let fields = [
+                copy_text(Some(provider.npi.as_str())),
+                copy_text(provider.entity_type.as_ref().map(|e| e.to_code())),
+                name_field(&provider.organization_name.legal_business_name, is_organization),
+                name_field(&provider.provider_name.last, is_individual),
+                name_field(&provider.provider_name.first, is_individual),
+                name_field(&provider.provider_name.middle, is_individual),
+                copy_text(provider.mailing_address.line_1.as_deref()),
+                copy_text(provider.mailing_address.city.as_deref()),
+                copy_text(provider.mailing_address.state.as_ref().map(|s| s.as_code())),
+                copy_text(self.zip_format.format(&provider.mailing_address).as_deref()),
+                copy_text(provider.enumeration_date.map(|d| d.format("%Y-%m-%d").to_string()).as_deref()),
+                copy_text(provider.last_update_date.map(|d| d.format("%Y-%m-%d").to_string()).as_deref()),
+                if provider.is_active() { "t" } else { "f" }.to_string(),
+            ];
+            writeln!(writer, "{}", fields.join("\t"))?;
+        }
+        
+        writeln!(writer, "\\.")?;
+        Ok(())
+    }
 }
 
-// SQL helper functions
-fn sql_string(opt: &Option<String>) -> String {
-    match opt {
-        Some(s) => format!("'{}'", s.replace('\'', "''")),
-        None => "NULL".to_string(),
+/// NULL marker in PostgreSQL's COPY text format
+const COPY_NULL: &str = "\\N";
+
+/// Escape a value for PostgreSQL's COPY text format
+fn copy_text(value: Option<&str>) -> String {
+    match value {
+        Some(s) => s
+            .replace('\\', "\\\\")
+            .replace('\t', "\\t")
+            .replace('\n', "\\n")
+            .replace('\r', "\\r"),
+        None => COPY_NULL.to_string(),
     }
 }
 
+/// Columns of the exported providers table, in insert order
+const PROVIDER_COLUMNS: [&str; 13] = [
+    "npi", "entity_type", "organization_name", "last_name", "first_name", "middle_name",
+    "mailing_address_line1", "mailing_address_city", "mailing_address_state",
+    "mailing_address_postal_code", "enumeration_date", "last_update_date", "is_active",
+];
+
+// SQL helper functions
+
 fn sql_date(opt: &Option<chrono::NaiveDate>) -> String {
     match opt {
         Some(date) => format!("'{}'", date.format("%Y-%m-%d")),
@@ -425,6 +1197,182 @@ fn sql_date(opt: &Option<chrono::NaiveDate>) -> String {
     }
 }
 
+/// Value extractor for a flattened provider column
+#[cfg(any(feature = "arrow-export", feature = "dataframe", feature = "avro"))]
+#[derive(Clone, Copy)]
+pub(crate) enum FlatValue {
+    Text(fn(&NppesRecord) -> Option<String>),
+    Bool(fn(&NppesRecord) -> Option<bool>),
+}
+
+/// A column in the flattened, one-row-per-provider layout shared by the
+/// columnar exporters (Parquet, Polars, Avro)
+#[cfg(any(feature = "arrow-export", feature = "dataframe", feature = "avro"))]
+#[derive(Clone, Copy)]
+pub(crate) struct FlatField {
+    pub name: &'static str,
+    pub nullable: bool,
+    pub value: FlatValue,
+}
+
+#[cfg(any(feature = "arrow-export", feature = "dataframe", feature = "avro"))]
+const fn text(name: &'static str, extract: fn(&NppesRecord) -> Option<String>) -> FlatField {
+    FlatField { name, nullable: true, value: FlatValue::Text(extract) }
+}
+
+#[cfg(any(feature = "arrow-export", feature = "dataframe", feature = "avro"))]
+const fn boolean(name: &'static str, extract: fn(&NppesRecord) -> Option<bool>) -> FlatField {
+    FlatField { name, nullable: true, value: FlatValue::Bool(extract) }
+}
+
+/// Flattened provider columns, in output order
+#[cfg(any(feature = "arrow-export", feature = "dataframe", feature = "avro"))]
+pub(crate) const FLAT_FIELDS: &[FlatField] = &[
+    FlatField { name: "npi", nullable: false, value: FlatValue::Text(|p| Some(p.npi.to_string())) },
+    FlatField { name: "entity_type", nullable: false, value: FlatValue::Text(|p| p.entity_type.as_ref().map(|e| e.to_code().to_string())) },
+    text("replacement_npi", |p| p.replacement_npi.as_ref().map(|n| n.to_string())),
+    text("ein", |p| p.ein.as_ref().map(|e| e.as_str().to_string())),
+    // ProviderName
+    text("provider_name_prefix", |p| p.provider_name.prefix.as_ref().map(|s| s.as_code().to_string())),
+    text("provider_name_first", |p| p.provider_name.first.clone()),
+    text("provider_name_middle", |p| p.provider_name.middle.clone()),
+    text("provider_name_last", |p| p.provider_name.last.clone()),
+    text("provider_name_suffix", |p| p.provider_name.suffix.as_ref().map(|s| s.as_code().to_string())),
+    text("provider_name_credential", |p| p.provider_name.credential.clone()),
+    // ProviderOtherName
+    text("provider_other_name_prefix", |p| p.provider_other_name.prefix.as_ref().map(|s| s.as_code().to_string())),
+    text("provider_other_name_first", |p| p.provider_other_name.first.clone()),
+    text("provider_other_name_middle", |p| p.provider_other_name.middle.clone()),
+    text("provider_other_name_last", |p| p.provider_other_name.last.clone()),
+    text("provider_other_name_suffix", |p| p.provider_other_name.suffix.as_ref().map(|s| s.as_code().to_string())),
+    text("provider_other_name_credential", |p| p.provider_other_name.credential.clone()),
+    text("provider_other_name_type_code", |p| p.provider_other_name_type.as_ref().map(|t| t.as_code().to_string())),
+    // OrganizationName
+    text("organization_legal_business_name", |p| p.organization_name.legal_business_name.clone()),
+    text("organization_other_name", |p| p.organization_name.other_name.clone()),
+    text("organization_other_name_type_code", |p| p.organization_name.other_name_type.as_ref().map(|t| t.as_code().to_string())),
+    // Mailing Address
+    text("mailing_line_1", |p| p.mailing_address.line_1.clone()),
+    text("mailing_line_2", |p| p.mailing_address.line_2.clone()),
+    text("mailing_city", |p| p.mailing_address.city.clone()),
+    text("mailing_state", |p| p.mailing_address.state.as_ref().map(|s| s.as_code().to_string())),
+    text("mailing_postal_code", |p| p.mailing_address.postal_code.clone()),
+    text("mailing_country_code", |p| p.mailing_address.country.as_ref().map(|c| c.as_code().to_string())),
+    text("mailing_telephone", |p| p.mailing_address.telephone.clone()),
+    text("mailing_fax", |p| p.mailing_address.fax.clone()),
+    // Practice Address
+    text("practice_line_1", |p| p.practice_address.line_1.clone()),
+    text("practice_line_2", |p| p.practice_address.line_2.clone()),
+    text("practice_city", |p| p.practice_address.city.clone()),
+    text("practice_state", |p| p.practice_address.state.as_ref().map(|s| s.as_code().to_string())),
+    text("practice_postal_code", |p| p.practice_address.postal_code.clone()),
+    text("practice_country_code", |p| p.practice_address.country.as_ref().map(|c| c.as_code().to_string())),
+    text("practice_telephone", |p| p.practice_address.telephone.clone()),
+    text("practice_fax", |p| p.practice_address.fax.clone()),
+    // Dates
+    text("enumeration_date", |p| p.enumeration_date.map(|d| d.to_string())),
+    text("last_update_date", |p| p.last_update_date.map(|d| d.to_string())),
+    text("deactivation_date", |p| p.deactivation_date.map(|d| d.to_string())),
+    text("reactivation_date", |p| p.reactivation_date.map(|d| d.to_string())),
+    text("certification_date", |p| p.certification_date.map(|d| d.to_string())),
+    // Status
+    text("deactivation_reason_code", |p| p.deactivation_reason.as_ref().map(|d| d.as_code().to_string())),
+    text("provider_gender_code", |p| p.provider_gender.as_ref().map(|g| g.as_code().to_string())),
+    // Authorized Official
+    text("auth_official_name_prefix", |p| p.authorized_official.as_ref().and_then(|a| a.prefix.as_ref()).map(|s| s.as_code().to_string())),
+    text("auth_official_first_name", |p| p.authorized_official.as_ref().and_then(|a| a.first_name.clone())),
+    text("auth_official_middle_name", |p| p.authorized_official.as_ref().and_then(|a| a.middle_name.clone())),
+    text("auth_official_last_name", |p| p.authorized_official.as_ref().and_then(|a| a.last_name.clone())),
+    text("auth_official_name_suffix", |p| p.authorized_official.as_ref().and_then(|a| a.suffix.as_ref()).map(|s| s.as_code().to_string())),
+    text("auth_official_credential", |p| p.authorized_official.as_ref().and_then(|a| a.credential.clone())),
+    text("auth_official_title", |p| p.authorized_official.as_ref().and_then(|a| a.title.clone())),
+    text("auth_official_telephone", |p| p.authorized_official.as_ref().and_then(|a| a.telephone.clone())),
+    // Taxonomy codes and other identifiers as JSON
+    text("taxonomy_codes_json", |p| serde_json::to_string(&p.taxonomy_codes).ok()),
+    text("other_identifiers_json", |p| serde_json::to_string(&p.other_identifiers).ok()),
+    // Organization flags
+    boolean("is_sole_proprietor", |p| p.sole_proprietor.as_ref().map(|v| *v == SoleProprietorCode::Yes)),
+    boolean("is_organization_subpart", |p| p.organization_subpart.as_ref().map(|v| *v == SubpartCode::Yes)),
+    text("parent_organization_lbn", |p| p.parent_organization_lbn.clone()),
+    text("parent_organization_tin", |p| p.parent_organization_tin.as_ref().map(|e| e.as_str().to_string())),
+];
+
+/// Rebuild a provider from its flattened columns, the inverse of [`FLAT_FIELDS`]
+/// 
+/// `text` and `flag` look up a column's value by name. Sole proprietor and
+/// subpart answers come back as Yes/No, since the flattened booleans don't
+/// distinguish "not answered" from absent.
+#[cfg(any(feature = "arrow-export", feature = "avro"))]
+pub(crate) fn record_from_flat(
+    text: impl Fn(&str) -> Option<String>,
+    flag: impl Fn(&str) -> Option<bool>,
+) -> Result<NppesRecord> {
+    let date = |name: &str| -> Result<Option<chrono::NaiveDate>> {
+        text(name)
+            .map(|value| parse_date_opt(&value).ok_or_else(|| NppesError::date_parse_with_format(&value, "YYYY-MM-DD")))
+            .transpose()
+    };
+    let name = |prefix: &str| ProviderName {
+        prefix: text(&format!("{prefix}_prefix")).as_deref().and_then(NamePrefixCode::from_code),
+        first: text(&format!("{prefix}_first")),
+        middle: text(&format!("{prefix}_middle")),
+        last: text(&format!("{prefix}_last")),
+        suffix: text(&format!("{prefix}_suffix")).as_deref().and_then(NameSuffixCode::from_code),
+        credential: text(&format!("{prefix}_credential")),
+    };
+    let address = |prefix: &str| Address {
+        line_1: text(&format!("{prefix}_line_1")),
+        line_2: text(&format!("{prefix}_line_2")),
+        city: text(&format!("{prefix}_city")),
+        state: text(&format!("{prefix}_state")).as_deref().and_then(StateCode::from_code),
+        postal_code: text(&format!("{prefix}_postal_code")),
+        country: text(&format!("{prefix}_country_code")).as_deref().map(CountryCode::from_code),
+        telephone: text(&format!("{prefix}_telephone")),
+        fax: text(&format!("{prefix}_fax")),
+    };
+    
+    Ok(NppesRecord {
+        npi: Npi::new(text("npi").unwrap_or_default())?,
+        entity_type: text("entity_type").as_deref().map(EntityType::from_code).transpose()?,
+        replacement_npi: text("replacement_npi").map(Npi::new).transpose()?,
+        ein: text("ein").map(|value| Ein::from_raw(&value)),
+        provider_name: name("provider_name"),
+        provider_other_name: name("provider_other_name"),
+        provider_other_name_type: text("provider_other_name_type_code").as_deref().and_then(OtherProviderNameTypeCode::from_code),
+        organization_name: OrganizationName {
+            legal_business_name: text("organization_legal_business_name"),
+            other_name: text("organization_other_name"),
+            other_name_type: text("organization_other_name_type_code").as_deref().and_then(OtherProviderNameTypeCode::from_code),
+        },
+        mailing_address: address("mailing"),
+        practice_address: address("practice"),
+        enumeration_date: date("enumeration_date")?,
+        last_update_date: date("last_update_date")?,
+        deactivation_date: date("deactivation_date")?,
+        reactivation_date: date("reactivation_date")?,
+        certification_date: date("certification_date")?,
+        deactivation_reason: text("deactivation_reason_code").as_deref().and_then(DeactivationReasonCode::from_code),
+        provider_gender: text("provider_gender_code").as_deref().and_then(SexCode::from_code),
+        authorized_official: Some(AuthorizedOfficial {
+            prefix: text("auth_official_name_prefix").as_deref().and_then(NamePrefixCode::from_code),
+            first_name: text("auth_official_first_name"),
+            middle_name: text("auth_official_middle_name"),
+            last_name: text("auth_official_last_name"),
+            suffix: text("auth_official_name_suffix").as_deref().and_then(NameSuffixCode::from_code),
+            credential: text("auth_official_credential"),
+            title: text("auth_official_title"),
+            telephone: text("auth_official_telephone"),
+        })
+        .filter(|official| !official.is_empty()),
+        taxonomy_codes: text("taxonomy_codes_json").map(|json| serde_json::from_str(&json)).transpose()?.unwrap_or_default(),
+        other_identifiers: text("other_identifiers_json").map(|json| serde_json::from_str(&json)).transpose()?.unwrap_or_default(),
+        sole_proprietor: flag("is_sole_proprietor").map(|yes| if yes { SoleProprietorCode::Yes } else { SoleProprietorCode::No }),
+        organization_subpart: flag("is_organization_subpart").map(|yes| if yes { SubpartCode::Yes } else { SubpartCode::No }),
+        parent_organization_lbn: text("parent_organization_lbn"),
+        parent_organization_tin: text("parent_organization_tin").map(|value| Ein::from_raw(&value)),
+    })
+}
+
 /// Parquet exporter (requires "parquet" feature)
 #[cfg(feature = "arrow-export")]
 pub struct ParquetExporter {
@@ -453,231 +1401,198 @@ impl ParquetExporter {
 
 #[cfg(feature = "arrow-export")]
 impl NppesExporter for ParquetExporter {
-    fn export(&self, dataset: &NppesDataset, path: &Path) -> Result<()> {
-        use std::fs::File;
-        use std::io::BufWriter;
-        use arrow::array::*;
-        use arrow::datatypes::{DataType, Field, Schema};
-        use arrow::record_batch::RecordBatch;
-        use std::sync::Arc;
-        // 1. Build Arrow schema (flattened, all fields)
-        let schema = Arc::new(Schema::new(vec![
-            Field::new("npi", DataType::Utf8, false),
-            Field::new("entity_type", DataType::Utf8, false),
-            Field::new("replacement_npi", DataType::Utf8, true),
-            Field::new("ein", DataType::Utf8, true),
-            // ProviderName
-            Field::new("provider_name_prefix", DataType::Utf8, true),
-            Field::new("provider_name_first", DataType::Utf8, true),
-            Field::new("provider_name_middle", DataType::Utf8, true),
-            Field::new("provider_name_last", DataType::Utf8, true),
-            Field::new("provider_name_suffix", DataType::Utf8, true),
-            Field::new("provider_name_credential", DataType::Utf8, true),
-            // ProviderOtherName
-            Field::new("provider_other_name_prefix", DataType::Utf8, true),
-            Field::new("provider_other_name_first", DataType::Utf8, true),
-            Field::new("provider_other_name_middle", DataType::Utf8, true),
-            Field::new("provider_other_name_last", DataType::Utf8, true),
-            Field::new("provider_other_name_suffix", DataType::Utf8, true),
-            Field::new("provider_other_name_credential", DataType::Utf8, true),
-            Field::new("provider_other_name_type_code", DataType::Utf8, true),
-            // OrganizationName
-            Field::new("organization_legal_business_name", DataType::Utf8, true),
-            Field::new("organization_other_name", DataType::Utf8, true),
-            Field::new("organization_other_name_type_code", DataType::Utf8, true),
-            // Mailing Address
-            Field::new("mailing_line_1", DataType::Utf8, true),
-            Field::new("mailing_line_2", DataType::Utf8, true),
-            Field::new("mailing_city", DataType::Utf8, true),
-            Field::new("mailing_state", DataType::Utf8, true),
-            Field::new("mailing_postal_code", DataType::Utf8, true),
-            Field::new("mailing_country_code", DataType::Utf8, true),
-            Field::new("mailing_telephone", DataType::Utf8, true),
-            Field::new("mailing_fax", DataType::Utf8, true),
-            // Practice Address
-            Field::new("practice_line_1", DataType::Utf8, true),
-            Field::new("practice_line_2", DataType::Utf8, true),
-            Field::new("practice_city", DataType::Utf8, true),
-            Field::new("practice_state", DataType::Utf8, true),
-            Field::new("practice_postal_code", DataType::Utf8, true),
-            Field::new("practice_country_code", DataType::Utf8, true),
-            Field::new("practice_telephone", DataType::Utf8, true),
-            Field::new("practice_fax", DataType::Utf8, true),
-            // Dates
-            Field::new("enumeration_date", DataType::Utf8, true),
-            Field::new("last_update_date", DataType::Utf8, true),
-            Field::new("deactivation_date", DataType::Utf8, true),
-            Field::new("reactivation_date", DataType::Utf8, true),
-            Field::new("certification_date", DataType::Utf8, true),
-            // Status
-            Field::new("deactivation_reason_code", DataType::Utf8, true),
-            Field::new("provider_gender_code", DataType::Utf8, true),
-            // Authorized Official
-            Field::new("auth_official_name_prefix", DataType::Utf8, true),
-            Field::new("auth_official_first_name", DataType::Utf8, true),
-            Field::new("auth_official_middle_name", DataType::Utf8, true),
-            Field::new("auth_official_last_name", DataType::Utf8, true),
-            Field::new("auth_official_name_suffix", DataType::Utf8, true),
-            Field::new("auth_official_credential", DataType::Utf8, true),
-            Field::new("auth_official_title", DataType::Utf8, true),
-            Field::new("auth_official_telephone", DataType::Utf8, true),
-            // Taxonomy codes and other identifiers as JSON
-            Field::new("taxonomy_codes_json", DataType::Utf8, true),
-            Field::new("other_identifiers_json", DataType::Utf8, true),
-            // Organization flags
-            Field::new("is_sole_proprietor", DataType::Boolean, true),
-            Field::new("is_organization_subpart", DataType::Boolean, true),
-            Field::new("parent_organization_lbn", DataType::Utf8, true),
-            Field::new("parent_organization_tin", DataType::Utf8, true),
-        ]));
-        // 2. Build Arrow arrays for each field
-        let n = dataset.providers.len();
-        let providers = &dataset.providers;
-        let taxonomy_codes_json: StringArray = StringArray::from((0..n).map(|i| serde_json::to_string(&providers[i].taxonomy_codes).ok()).collect::<Vec<Option<String>>>());
-        let other_identifiers_json: StringArray = StringArray::from((0..n).map(|i| serde_json::to_string(&providers[i].other_identifiers).ok()).collect::<Vec<Option<String>>>());
-        let is_sole_proprietor: BooleanArray = (0..n).map(|i| providers[i].sole_proprietor.as_ref().map(|v| *v == crate::data_types::SoleProprietorCode::Yes)).collect();
-        let is_organization_subpart: BooleanArray = (0..n).map(|i| providers[i].organization_subpart.as_ref().map(|v| *v == crate::data_types::SubpartCode::Yes)).collect();
-        let npi: ArrayRef = Arc::new(StringArray::from((0..n).map(|i| Some(providers[i].npi.as_str())).collect::<Vec<Option<&str>>>())) as ArrayRef;
-        let entity_type = StringArray::from((0..n).map(|i| providers[i].entity_type.as_ref().map(|e| e.to_code())).collect::<Vec<Option<&str>>>());
-        let replacement_npi = StringArray::from((0..n).map(|i| providers[i].replacement_npi.as_ref().map(|n| n.as_str())).collect::<Vec<Option<&str>>>());
-        let ein = StringArray::from((0..n).map(|i| providers[i].ein.as_deref()).collect::<Vec<Option<&str>>>());
-        // ProviderName
-        let provider_name_prefix = StringArray::from((0..n).map(|i| providers[i].provider_name.prefix.as_ref().map(|s| s.as_code())).collect::<Vec<Option<&str>>>());
-        let provider_name_first = StringArray::from((0..n).map(|i| providers[i].provider_name.first.as_deref()).collect::<Vec<Option<&str>>>());
-        let provider_name_middle = StringArray::from((0..n).map(|i| providers[i].provider_name.middle.as_deref()).collect::<Vec<Option<&str>>>());
-        let provider_name_last = StringArray::from((0..n).map(|i| providers[i].provider_name.last.as_deref()).collect::<Vec<Option<&str>>>());
-        let provider_name_suffix = StringArray::from((0..n).map(|i| providers[i].provider_name.suffix.as_ref().map(|s| s.as_code())).collect::<Vec<Option<&str>>>());
-        let provider_name_credential = StringArray::from((0..n).map(|i| providers[i].provider_name.credential.as_deref()).collect::<Vec<Option<&str>>>());
-        // ProviderOtherName
-        let provider_other_name_prefix = StringArray::from((0..n).map(|i| providers[i].provider_other_name.prefix.as_ref().map(|s| s.as_code())).collect::<Vec<Option<&str>>>());
-        let provider_other_name_first = StringArray::from((0..n).map(|i| providers[i].provider_other_name.first.as_deref()).collect::<Vec<Option<&str>>>());
-        let provider_other_name_middle = StringArray::from((0..n).map(|i| providers[i].provider_other_name.middle.as_deref()).collect::<Vec<Option<&str>>>());
-        let provider_other_name_last = StringArray::from((0..n).map(|i| providers[i].provider_other_name.last.as_deref()).collect::<Vec<Option<&str>>>());
-        let provider_other_name_suffix = StringArray::from((0..n).map(|i| providers[i].provider_other_name.suffix.as_ref().map(|s| s.as_code())).collect::<Vec<Option<&str>>>());
-        let provider_other_name_credential = StringArray::from((0..n).map(|i| providers[i].provider_other_name.credential.as_deref()).collect::<Vec<Option<&str>>>());
-        let provider_other_name_type_code = StringArray::from((0..n).map(|i| providers[i].provider_other_name_type.as_ref().map(|t| t.as_code())).collect::<Vec<Option<&str>>>());
-        // OrganizationName
-        let organization_legal_business_name = StringArray::from((0..n).map(|i| providers[i].organization_name.legal_business_name.as_deref()).collect::<Vec<Option<&str>>>());
-        let organization_other_name = StringArray::from((0..n).map(|i| providers[i].organization_name.other_name.as_deref()).collect::<Vec<Option<&str>>>());
-        let organization_other_name_type_code = StringArray::from((0..n).map(|i| providers[i].organization_name.other_name_type.as_ref().map(|t| t.as_code())).collect::<Vec<Option<&str>>>());
-        // Mailing Address
-        let mailing_line_1 = StringArray::from((0..n).map(|i| providers[i].mailing_address.line_1.as_deref()).collect::<Vec<Option<&str>>>());
-        let mailing_line_2 = StringArray::from((0..n).map(|i| providers[i].mailing_address.line_2.as_deref()).collect::<Vec<Option<&str>>>());
-        let mailing_city = StringArray::from((0..n).map(|i| providers[i].mailing_address.city.as_deref()).collect::<Vec<Option<&str>>>());
-        let mailing_state = StringArray::from((0..n).map(|i| providers[i].mailing_address.state.as_ref().map(|s| s.as_code())).collect::<Vec<Option<&str>>>());
-        let mailing_postal_code = StringArray::from((0..n).map(|i| providers[i].mailing_address.postal_code.as_deref()).collect::<Vec<Option<&str>>>());
-        let mailing_country_code = StringArray::from((0..n).map(|i| providers[i].mailing_address.country.as_ref().map(|c| c.as_code())).collect::<Vec<Option<&str>>>());
-        let mailing_telephone = StringArray::from((0..n).map(|i| providers[i].mailing_address.telephone.as_deref()).collect::<Vec<Option<&str>>>());
-        let mailing_fax = StringArray::from((0..n).map(|i| providers[i].mailing_address.fax.as_deref()).collect::<Vec<Option<&str>>>());
-        // Practice Address
-        let practice_line_1 = StringArray::from((0..n).map(|i| providers[i].practice_address.line_1.as_deref()).collect::<Vec<Option<&str>>>());
-        let practice_line_2 = StringArray::from((0..n).map(|i| providers[i].practice_address.line_2.as_deref()).collect::<Vec<Option<&str>>>());
-        let practice_city = StringArray::from((0..n).map(|i| providers[i].practice_address.city.as_deref()).collect::<Vec<Option<&str>>>());
-        let practice_state = StringArray::from((0..n).map(|i| providers[i].practice_address.state.as_ref().map(|s| s.as_code())).collect::<Vec<Option<&str>>>());
-        let practice_postal_code = StringArray::from((0..n).map(|i| providers[i].practice_address.postal_code.as_deref()).collect::<Vec<Option<&str>>>());
-        let practice_country_code = StringArray::from((0..n).map(|i| providers[i].practice_address.country.as_ref().map(|c| c.as_code())).collect::<Vec<Option<&str>>>());
-        let practice_telephone = StringArray::from((0..n).map(|i| providers[i].practice_address.telephone.as_deref()).collect::<Vec<Option<&str>>>());
-        let practice_fax = StringArray::from((0..n).map(|i| providers[i].practice_address.fax.as_deref()).collect::<Vec<Option<&str>>>());
-        // Dates
-        let enumeration_date = StringArray::from((0..n).map(|i| providers[i].enumeration_date.map(|d| d.to_string())).collect::<Vec<Option<String>>>());
-        let last_update_date = StringArray::from((0..n).map(|i| providers[i].last_update_date.map(|d| d.to_string())).collect::<Vec<Option<String>>>());
-        let deactivation_date = StringArray::from((0..n).map(|i| providers[i].deactivation_date.map(|d| d.to_string())).collect::<Vec<Option<String>>>());
-        let reactivation_date = StringArray::from((0..n).map(|i| providers[i].reactivation_date.map(|d| d.to_string())).collect::<Vec<Option<String>>>());
-        let certification_date = StringArray::from((0..n).map(|i| providers[i].certification_date.map(|d| d.to_string())).collect::<Vec<Option<String>>>());
-        // Status
-        let deactivation_reason_code = StringArray::from((0..n).map(|i| providers[i].deactivation_reason.as_ref().map(|d| d.as_code())).collect::<Vec<Option<&str>>>());
-        let provider_gender_code = StringArray::from((0..n).map(|i| providers[i].provider_gender.as_ref().map(|g| g.as_code())).collect::<Vec<Option<&str>>>());
-        // Authorized Official
-        let auth_official_name_prefix = StringArray::from((0..n).map(|i| providers[i].authorized_official.as_ref().and_then(|a| a.prefix.as_ref().map(|s| s.as_code()))).collect::<Vec<Option<&str>>>());
-        let auth_official_first_name = StringArray::from((0..n).map(|i| providers[i].authorized_official.as_ref().and_then(|a| a.first_name.as_deref())).collect::<Vec<Option<&str>>>());
-        let auth_official_middle_name = StringArray::from((0..n).map(|i| providers[i].authorized_official.as_ref().and_then(|a| a.middle_name.as_deref())).collect::<Vec<Option<&str>>>());
-        let auth_official_last_name = StringArray::from((0..n).map(|i| providers[i].authorized_official.as_ref().and_then(|a| a.last_name.as_deref())).collect::<Vec<Option<&str>>>());
-        let auth_official_name_suffix = StringArray::from((0..n).map(|i| providers[i].authorized_official.as_ref().and_then(|a| a.suffix.as_ref().map(|s| s.as_code()))).collect::<Vec<Option<&str>>>());
-        let auth_official_credential = StringArray::from((0..n).map(|i| providers[i].authorized_official.as_ref().and_then(|a| a.credential.as_deref())).collect::<Vec<Option<&str>>>());
-        let auth_official_title = StringArray::from((0..n).map(|i| providers[i].authorized_official.as_ref().and_then(|a| a.title.as_deref())).collect::<Vec<Option<&str>>>());
-        let auth_official_telephone = StringArray::from((0..n).map(|i| providers[i].authorized_official.as_ref().and_then(|a| a.telephone.as_deref())).collect::<Vec<Option<&str>>>());
-        let parent_organization_lbn = StringArray::from((0..n).map(|i| providers[i].parent_organization_lbn.as_deref()).collect::<Vec<Option<&str>>>());
-        let parent_organization_tin: ArrayRef = Arc::new(StringArray::from((0..n).map(|i| providers[i].parent_organization_tin.as_deref()).collect::<Vec<Option<&str>>>())) as ArrayRef;
-        let batch = RecordBatch::try_new(
-            schema.clone(),
-            vec![
-                npi,
-                Arc::new(entity_type),
-                Arc::new(replacement_npi),
-                Arc::new(ein),
-                Arc::new(provider_name_prefix),
-                Arc::new(provider_name_first),
-                Arc::new(provider_name_middle),
-                Arc::new(provider_name_last),
-                Arc::new(provider_name_suffix),
-                Arc::new(provider_name_credential),
-                Arc::new(provider_other_name_prefix),
-                Arc::new(provider_other_name_first),
-                Arc::new(provider_other_name_middle),
-                Arc::new(provider_other_name_last),
-                Arc::new(provider_other_name_suffix),
-                Arc::new(provider_other_name_credential),
-                Arc::new(provider_other_name_type_code),
-                Arc::new(organization_legal_business_name),
-                Arc::new(organization_other_name),
-                Arc::new(organization_other_name_type_code),
-                Arc::new(mailing_line_1),
-                Arc::new(mailing_line_2),
-                Arc::new(mailing_city),
-                Arc::new(mailing_state),
-                Arc::new(mailing_postal_code),
-                Arc::new(mailing_country_code),
-                Arc::new(mailing_telephone),
-                Arc::new(mailing_fax),
-                Arc::new(practice_line_1),
-                Arc::new(practice_line_2),
-                Arc::new(practice_city),
-                Arc::new(practice_state),
-                Arc::new(practice_postal_code),
-                Arc::new(practice_country_code),
-                Arc::new(practice_telephone),
-                Arc::new(practice_fax),
-                Arc::new(enumeration_date),
-                Arc::new(last_update_date),
-                Arc::new(deactivation_date),
-                Arc::new(reactivation_date),
-                Arc::new(certification_date),
-                Arc::new(deactivation_reason_code),
-                Arc::new(provider_gender_code),
-                Arc::new(auth_official_name_prefix),
-                Arc::new(auth_official_first_name),
-                Arc::new(auth_official_middle_name),
-                Arc::new(auth_official_last_name),
-                Arc::new(auth_official_name_suffix),
-                Arc::new(auth_official_credential),
-                Arc::new(auth_official_title),
-                Arc::new(auth_official_telephone),
-                Arc::new(taxonomy_codes_json),
-                Arc::new(other_identifiers_json),
-                Arc::new(is_sole_proprietor),
-                Arc::new(is_organization_subpart),
-                Arc::new(parent_organization_lbn),
-                Arc::new(parent_organization_tin),
-            ],
-        )?;
-        // 3. Write to Parquet
+    fn export(&self, dataset: &NppesDataset, path: &Path) -> Result<ExportSummary> {
+        let batch = providers_record_batch(&dataset.providers)?;
+        let schema = batch.schema();
+        // Write to Parquet
         let file = File::create(path)?;
         let props = parquet::file::properties::WriterProperties::builder().set_compression(self.compression).build();
         let mut writer = ArrowWriter::try_new(BufWriter::new(file), schema, Some(props))?;
         writer.write(&batch)?;
         writer.close()?;
-        Ok(())
+        ExportSummary::from_files(dataset.providers.len(), vec![path.to_path_buf()])
     }
     fn format(&self) -> ExportFormat {
         ExportFormat::Parquet
     }
 }
 
+/// Build a single Arrow record batch of providers using the flattened columns
+#[cfg(feature = "arrow-export")]
+pub(crate) fn providers_record_batch(providers: &[NppesRecord]) -> Result<RecordBatch> {
+    let schema = Arc::new(Schema::new(
+        FLAT_FIELDS.iter()
+            .map(|field| {
+                let data_type = match field.value {
+                    FlatValue::Text(_) => DataType::Utf8,
+                    FlatValue::Bool(_) => DataType::Boolean,
+                };
+                Field::new(field.name, data_type, field.nullable)
+            })
+            .collect::<Vec<_>>(),
+    ));
+    let columns: Vec<ArrayRef> = FLAT_FIELDS.iter()
+        .map(|field| match field.value {
+            FlatValue::Text(extract) => {
+                Arc::new(providers.iter().map(extract).collect::<StringArray>()) as ArrayRef
+            }
+            FlatValue::Bool(extract) => {
+                Arc::new(providers.iter().map(extract).collect::<BooleanArray>()) as ArrayRef
+            }
+        })
+        .collect();
+    Ok(RecordBatch::try_new(schema, columns)?)
+}
+
+/// Avro exporter writing one record per provider (requires "avro" feature)
+/// 
+/// Records follow the flattened provider layout used by the Parquet export;
+/// see [`AvroExporter::schema`].
+#[cfg(feature = "avro")]
+pub struct AvroExporter {
+    /// Block compression codec
+    pub codec: apache_avro::Codec,
+}
+
+#[cfg(feature = "avro")]
+impl Default for AvroExporter {
+    fn default() -> Self {
+        Self {
+            codec: apache_avro::Codec::Deflate(apache_avro::DeflateSettings::default()),
+        }
+    }
+}
+
+#[cfg(feature = "avro")]
+impl AvroExporter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Set the block compression codec
+    pub fn with_codec(mut self, codec: apache_avro::Codec) -> Self {
+        self.codec = codec;
+        self
+    }
+    
+    /// Avro schema of the exported provider records
+    /// 
+    /// A record named `nppes.Provider` with one field per flattened column.
+    /// Nullable columns are `["null", T]` unions defaulting to `null`.
+    pub fn schema() -> Result<apache_avro::Schema> {
+        let fields: Vec<serde_json::Value> = FLAT_FIELDS.iter()
+            .map(|field| {
+                let data_type = match field.value {
+                    FlatValue::Text(_) => "string",
+                    FlatValue::Bool(_) => "boolean",
+                };
+                if field.nullable {
+                    serde_json::json!({ "name": field.name, "type": ["null", data_type], "default": null })
+                } else {
+                    serde_json::json!({ "name": field.name, "type": data_type })
+                }
+            })
+            .collect();
+        let schema = serde_json::json!({
+            "type": "record",
+            "name": "Provider",
+            "namespace": "nppes",
+            "fields": fields,
+        });
+        Ok(apache_avro::Schema::parse(&schema)?)
+    }
+}
+
+#[cfg(feature = "avro")]
+impl NppesExporter for AvroExporter {
+    fn export(&self, dataset: &NppesDataset, path: &Path) -> Result<ExportSummary> {
+        use apache_avro::types::Value;
+        
+        let schema = Self::schema()?;
+        let file = File::create(path)?;
+        let mut writer = apache_avro::Writer::with_codec(&schema, BufWriter::new(file), self.codec);
+        
+        for provider in &dataset.providers {
+            let fields = FLAT_FIELDS.iter()
+                .map(|field| {
+                    let value = match field.value {
+                        FlatValue::Text(extract) => extract(provider).map(Value::String),
+                        FlatValue::Bool(extract) => extract(provider).map(Value::Boolean),
+                    };
+                    let value = match (field.nullable, value) {
+                        (false, value) => value.unwrap_or(Value::String(String::new())),
+                        (true, Some(value)) => Value::Union(1, Box::new(value)),
+                        (true, None) => Value::Union(0, Box::new(Value::Null)),
+                    };
+                    (field.name.to_string(), value)
+                })
+                .collect();
+            writer.append(Value::Record(fields))?;
+        }
+        
+        writer.into_inner()?.flush()?;
+        ExportSummary::from_files(dataset.providers.len(), vec![path.to_path_buf()])
+    }
+    
+    fn format(&self) -> ExportFormat {
+        ExportFormat::Avro
+    }
+}
+
+#[cfg(feature = "avro")]
+impl NppesReader {
+    /// Load providers from an Avro file written by [`AvroExporter`]
+    /// 
+    /// Columns are matched by name, so files with extra or missing fields still
+    /// load; absent columns are left empty.
+    pub fn load_main_data_avro<P: AsRef<Path>>(&self, path: P) -> Result<Vec<NppesRecord>> {
+        use apache_avro::types::Value;
+        use std::collections::HashMap;
+        
+        let path = path.as_ref();
+        if !path.exists() {
+            return Err(NppesError::file_not_found_with_suggestion(path.to_path_buf()));
+        }
+        let reader = apache_avro::Reader::new(std::io::BufReader::new(File::open(path)?))?;
+        
+        let mut records = Vec::new();
+        for value in reader {
+            let Value::Record(fields) = value? else {
+                return Err(NppesError::Custom {
+                    message: "Avro value is not a provider record".to_string(),
+                    suggestion: Some("Use a file written by AvroExporter".to_string()),
+                    source: None,
+                });
+            };
+            let fields: HashMap<String, Value> = fields.into_iter()
+                .map(|(name, value)| match value {
+                    Value::Union(_, inner) => (name, *inner),
+                    value => (name, value),
+                })
+                .collect();
+            let record = record_from_flat(
+                |name| match fields.get(name) {
+                    Some(Value::String(s)) if !s.is_empty() => Some(s.clone()),
+                    _ => None,
+                },
+                |name| match fields.get(name) {
+                    Some(Value::Boolean(b)) => Some(*b),
+                    _ => None,
+                },
+            )?;
+            records.push(record);
+        }
+        Ok(records)
+    }
+}
+
 // Export convenience functions for NppesDataset
+// 
+// These discard the ExportSummary; call an exporter directly to get it.
 impl NppesDataset {
     /// Export to JSON format
     pub fn export_json<P: AsRef<Path>>(&self, path: P) -> Result<()> {
-        JsonExporter::default().export(self, path.as_ref())
+        JsonExporter::default().export(self, path.as_ref()).map(drop)
     }
     
     /// Export to JSON Lines format
@@ -685,11 +1600,17 @@ impl NppesDataset {
         JsonExporter::new()
             .as_json_lines()
             .export(self, path.as_ref())
+            .map(drop)
+    }
+    
+    /// Export search documents as JSON Lines (see [`SearchDocExporter`])
+    pub fn export_search_docs<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        SearchDocExporter::new().export(self, path.as_ref()).map(drop)
     }
     
     /// Export to normalized CSV files
     pub fn export_csv<P: AsRef<Path>>(&self, path: P) -> Result<()> {
-        CsvExporter::default().export(self, path.as_ref())
+        CsvExporter::default().export(self, path.as_ref()).map(drop)
     }
     
     /// Export to SQL insert statements
@@ -697,6 +1618,12 @@ impl NppesDataset {
         SqlExporter::new()
             .with_dialect(dialect)
             .export(self, path.as_ref())
+            .map(drop)
+    }
+    
+    /// Export to an XML document (see [`XmlExporter`])
+    pub fn export_xml<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        XmlExporter::new().export(self, path.as_ref()).map(drop)
     }
     
     /// Export a subset of providers
@@ -721,21 +1648,133 @@ impl NppesDataset {
             None, // taxonomy_index
         );
         
+        subset.export_as(path.as_ref(), format).map(drop)
+    }
+    
+    /// Export the whole dataset with the default exporter for `format`
+    pub(crate) fn export_as(&self, path: &Path, format: ExportFormat) -> Result<ExportSummary> {
         match format {
-            ExportFormat::Json => JsonExporter::default().export(&subset, path.as_ref()),
-            ExportFormat::Csv => CsvExporter::default().export(&subset, path.as_ref()),
-            ExportFormat::Sql => SqlExporter::default().export(&subset, path.as_ref()),
+            ExportFormat::Json => JsonExporter::default().export(self, path),
+            ExportFormat::Csv => CsvExporter::default().export(self, path),
+            ExportFormat::Sql => SqlExporter::default().export(self, path),
+            ExportFormat::Xml => XmlExporter::new().export(self, path),
+            #[cfg(feature = "avro")]
+            ExportFormat::Avro => AvroExporter::default().export(self, path),
             _ => Err(NppesError::Custom {
                 message: format!("Export format {:?} not supported", format),
-                suggestion: Some("Use JSON, CSV, or SQL format".to_string()),
+                suggestion: Some("Use JSON, CSV, SQL, or XML format".to_string()),
+                source: None,
             }),
         }
     }
 
+    /// Export a filtered projection of providers in a single pass
+    /// 
+    /// `project` returns `None` to skip a provider, or the value to write for it.
+    /// `ExportFormat::Json` writes one JSON document per line; `ExportFormat::Csv`
+    /// writes a header derived from `T`'s field names, so `T` must serialize to a
+    /// flat record.
+    /// 
+    /// # Example
+    /// ```no_run
+    /// # use nppes::prelude::*;
+    /// # let dataset = NppesDataset::load_standard("./data")?;
+    /// #[derive(serde::Serialize)]
+    /// struct Row<'a> {
+    ///     npi: &'a str,
+    ///     taxonomy_code: Option<&'a str>,
+    /// }
+    /// 
+    /// dataset.export_projected("active_ca.csv", |p| {
+    ///     let in_ca = p.practice_address.state.as_ref().map(|s| s.as_code()) == Some("CA");
+    ///     (p.is_active() && in_ca).then(|| Row {
+    ///         npi: p.npi.as_str(),
+    ///         taxonomy_code: p.primary_taxonomy().map(|t| t.code.as_str()),
+    ///     })
+    /// }, ExportFormat::Csv)?;
+    /// # Ok::<(), nppes::NppesError>(())
+    /// ```
+    pub fn export_projected<'a, P, T, F>(&'a self, path: P, project: F, format: ExportFormat) -> Result<()>
+    where
+        P: AsRef<Path>,
+        T: serde::Serialize,
+        F: Fn(&'a NppesRecord) -> Option<T>,
+    {
+        let rows = self.providers.iter().filter_map(project);
+        
+        match format {
+            ExportFormat::Json => {
+                let mut writer = BufWriter::new(File::create(path.as_ref())?);
+                for row in rows {
+                    serde_json::to_writer(&mut writer, &row)?;
+                    writeln!(writer)?;
+                }
+                writer.flush()?;
+            }
+            ExportFormat::Csv => {
+                let mut writer = csv::Writer::from_path(path.as_ref())?;
+                for row in rows {
+                    writer.serialize(row)?;
+                }
+                writer.flush()?;
+            }
+            _ => {
+                return Err(NppesError::Custom {
+                    message: format!("Export format {:?} not supported for projected exports", format),
+                    suggestion: Some("Use JSON or CSV format".to_string()),
+                    source: None,
+                });
+            }
+        }
+        
+        Ok(())
+    }
+
+    /// Convert providers to a Polars DataFrame with one row per provider
+    /// 
+    /// Uses the same flattened columns as the Parquet export.
+    /// 
+    /// # Example
+    /// ```no_run
+    /// # use nppes::dataset::NppesDataset;
+    /// use polars::prelude::*;
+    /// 
+    /// let dataset = NppesDataset::load_standard("./data")?;
+    /// let ca = dataset.to_polars()?
+    ///     .lazy()
+    ///     .filter(col("practice_state").eq(lit("CA")))
+    ///     .collect()?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    #[cfg(feature = "dataframe")]
+    pub fn to_polars(&self) -> Result<polars::frame::DataFrame> {
+        use polars::prelude::{Column, DataFrame};
+        
+        let columns: Vec<Column> = FLAT_FIELDS.iter()
+            .map(|field| match field.value {
+                FlatValue::Text(extract) => Column::new(
+                    field.name.into(),
+                    self.providers.iter().map(extract).collect::<Vec<_>>(),
+                ),
+                FlatValue::Bool(extract) => Column::new(
+                    field.name.into(),
+                    self.providers.iter().map(extract).collect::<Vec<_>>(),
+                ),
+            })
+            .collect();
+        Ok(DataFrame::new(columns)?)
+    }
+    
+    /// Export to Avro format
+    #[cfg(feature = "avro")]
+    pub fn export_avro<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        AvroExporter::default().export(self, path.as_ref()).map(drop)
+    }
+    
     /// Export to Parquet format
     #[cfg(feature = "arrow-export")]
     pub fn export_parquet<P: AsRef<Path>>(&self, path: P) -> Result<()> {
-        ParquetExporter::default().export(self, path.as_ref())
+        ParquetExporter::default().export(self, path.as_ref()).map(drop)
     }
 
     #[cfg(feature = "arrow-export")]
@@ -924,6 +1963,47 @@ fn address_from_json(s: &str) -> Option<crate::data_types::Address> {
 
 #[cfg(feature = "arrow-export")]
 impl NppesReader {
+    /// Load providers from a Parquet file written by [`ParquetExporter`]
+    /// 
+    /// Record batches are decoded one at a time and columns are matched by name,
+    /// so files with extra or missing columns still load; absent columns are left
+    /// empty. Names, addresses and enum codes are rebuilt from the flattened
+    /// columns and taxonomies and other identifiers from their JSON columns.
+    #[cfg(feature = "arrow-export")]
+    pub fn load_main_data_parquet<P: AsRef<Path>>(&self, path: P) -> Result<Vec<NppesRecord>> {
+        use std::collections::HashMap;
+        
+        let path = path.as_ref();
+        if !path.exists() {
+            return Err(NppesError::file_not_found_with_suggestion(path.to_path_buf()));
+        }
+        let record_batch_reader = ParquetRecordBatchReaderBuilder::try_new(File::open(path)?)?.build()?;
+        
+        let mut records = Vec::new();
+        for batch in record_batch_reader {
+            let batch = batch?;
+            let text_columns: HashMap<&str, &StringArray> = FLAT_FIELDS.iter()
+                .filter_map(|field| Some((field.name, batch.column_by_name(field.name)?.as_any().downcast_ref::<StringArray>()?)))
+                .collect();
+            let flag_columns: HashMap<&str, &BooleanArray> = FLAT_FIELDS.iter()
+                .filter_map(|field| Some((field.name, batch.column_by_name(field.name)?.as_any().downcast_ref::<BooleanArray>()?)))
+                .collect();
+            
+            records.reserve(batch.num_rows());
+            for row in 0..batch.num_rows() {
+                records.push(record_from_flat(
+                    |name| text_columns.get(name)
+                        .filter(|column| column.is_valid(row))
+                        .and_then(|column| val_or_none(column.value(row))),
+                    |name| flag_columns.get(name)
+                        .filter(|column| column.is_valid(row))
+                        .map(|column| column.value(row)),
+                )?);
+            }
+        }
+        Ok(records)
+    }
+    
     #[cfg(feature = "arrow-export")]
     pub fn load_taxonomy_data_parquet<P: AsRef<Path>>(&self, path: P) -> Result<Vec<TaxonomyReference>> {
         use std::fs::File;
@@ -1028,7 +2108,384 @@ fn val_or_none(s: &str) -> Option<String> {
     if s.is_empty() { None } else { Some(s.to_string()) }
 }
 
-#[cfg(feature = "arrow-export")]
+#[cfg(any(feature = "arrow-export", feature = "avro"))]
 fn parse_date_opt(s: &str) -> Option<chrono::NaiveDate> {
     if s.is_empty() { None } else { chrono::NaiveDate::parse_from_str(s, "%Y-%m-%d").ok() }
-} 
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use crate::reader::NppesReader;
+    
+    fn provider(npi: &str, state: StateCode, postal_code: &str) -> NppesRecord {
+        NppesRecord {
+            npi: Npi::new(npi.to_string()).unwrap(),
+            entity_type: Some(EntityType::Individual),
+            mailing_address: Address {
+                state: Some(state),
+                postal_code: Some(postal_code.to_string()),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+    
+    fn dataset(providers: Vec<NppesRecord>) -> NppesDataset {
+        NppesDataset::new(providers, None, None, None, None, None, None, None)
+    }
+    
+    #[test]
+    fn test_csv_export_codes_or_decoded_labels() {
+        let data = dataset(vec![NppesRecord {
+            provider_gender: Some(SexCode::Male),
+            ..provider("1234567893", StateCode::CA, "90210")
+        }]);
+        let dir = tempfile::tempdir().unwrap();
+        let providers_row = |exporter: CsvExporter| {
+            exporter.export(&data, &dir.path().join("out.csv")).unwrap();
+            std::fs::read_to_string(dir.path().join("out_providers.csv")).unwrap()
+        };
+        
+        assert_eq!(providers_row(CsvExporter::new()), "1234567893,1,,CA,90210\n");
+        assert_eq!(
+            providers_row(CsvExporter::new().with_decoded_labels(true)),
+            "1234567893,Individual,,California,90210,Male\n"
+        );
+        
+        // The main file layout holds codes, so labels are rejected rather than ignored
+        let denormalized = CsvExporter::new().with_normalization(false).with_decoded_labels(true);
+        let err = denormalized.export(&data, &dir.path().join("flat.csv")).unwrap_err();
+        assert!(matches!(err, NppesError::Configuration { .. }), "{:?}", err);
+    }
+    
+    #[test]
+    fn test_csv_export_max_rows_per_file() {
+        let npis = ["1234567893", "1245319599", "1003000126", "1538144910", "1234567893"];
+        let providers = npis.iter()
+            .enumerate()
+            .map(|(i, npi)| NppesRecord {
+                taxonomy_codes: (0..=i % 2)
+                    .map(|_| TaxonomyCode { code: "207Q00000X".to_string(), ..Default::default() })
+                    .collect(),
+                ..provider(npi, StateCode::CA, "90210")
+            })
+            .collect();
+        let data = dataset(providers);
+        let dir = tempfile::tempdir().unwrap();
+        let rows = |path: &std::path::Path| {
+            let contents = std::fs::read_to_string(path).unwrap();
+            assert!(contents.starts_with("npi,"), "{} has no header", path.display());
+            contents.lines().count() - 1
+        };
+        
+        let summary = CsvExporter::new()
+            .with_max_rows_per_file(2)
+            .export(&data, &dir.path().join("out.csv"))
+            .unwrap();
+        let file = |name: &str| dir.path().join(name);
+        assert_eq!(summary.files, [
+            file("out_providers_0001.csv"), file("out_providers_0002.csv"), file("out_providers_0003.csv"),
+            file("out_taxonomies_0001.csv"), file("out_taxonomies_0002.csv"), file("out_taxonomies_0003.csv"),
+        ]);
+        let counts: Vec<usize> = summary.files.iter().map(|f| rows(f)).collect();
+        assert_eq!(counts, [2, 2, 1, 3, 3, 1]);
+        assert_eq!(counts[..3].iter().sum::<usize>(), summary.records);
+        assert!(!file("out_providers.csv").exists());
+        
+        // Each taxonomies file covers the providers in its matching providers file
+        let first_npis = |name: &str| -> std::collections::HashSet<String> {
+            std::fs::read_to_string(file(name)).unwrap()
+                .lines()
+                .skip(1)
+                .map(|line| line.split(',').next().unwrap().to_string())
+                .collect()
+        };
+        assert_eq!(first_npis("out_providers_0002.csv"), first_npis("out_taxonomies_0002.csv"));
+        
+        let summary = CsvExporter::new()
+            .with_max_rows_per_file(2)
+            .export(&dataset(Vec::new()), &dir.path().join("empty.csv"))
+            .unwrap();
+        assert_eq!(summary.files, [file("empty_providers_0001.csv"), file("empty_taxonomies_0001.csv")]);
+        assert_eq!(rows(&summary.files[0]), 0);
+    }
+    
+    #[test]
+    fn test_xml_export_structure() {
+        let mut organization = provider("1245319599", StateCode::NY, "10001");
+        organization.entity_type = Some(EntityType::Organization);
+        organization.organization_name.legal_business_name = Some("SMITH & SONS <CLINIC>".to_string());
+        organization.taxonomy_codes = vec![
+            TaxonomyCode { code: "261QM1300X".to_string(), is_primary: true, ..Default::default() },
+            TaxonomyCode { code: "261QP2300X".to_string(), license_number: Some("A\"1'".to_string()), ..Default::default() },
+        ];
+        organization.other_identifiers = vec![OtherIdentifier {
+            identifier: "MCD-42".to_string(),
+            type_code: Some("05".to_string()),
+            issuer: None,
+            state: Some(StateCode::NY),
+        }];
+        let mut individual = provider("1234567893", StateCode::CA, "90210");
+        individual.provider_name.last = Some("O'BRIEN".to_string());
+        individual.enumeration_date = NaiveDate::from_ymd_opt(2010, 3, 4);
+        let data = dataset(vec![individual, organization]);
+        
+        let file = tempfile::NamedTempFile::new().unwrap();
+        data.export_xml(file.path()).unwrap();
+        let xml = std::fs::read_to_string(file.path()).unwrap();
+        assert!(xml.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>"));
+        
+        let document = roxmltree::Document::parse(&xml).unwrap();
+        let root = document.root_element();
+        assert_eq!(root.tag_name().name(), "providers");
+        let providers: Vec<_> = root.children().filter(|n| n.has_tag_name("provider")).collect();
+        assert_eq!(providers.len(), 2);
+        
+        let text = |node: roxmltree::Node, path: &[&str]| -> Option<String> {
+            let mut node = node;
+            for name in path {
+                node = node.children().find(|n| n.has_tag_name(*name))?;
+            }
+            node.text().map(str::to_string)
+        };
+        assert_eq!(providers[0].attribute("npi"), Some("1234567893"));
+        assert_eq!(text(providers[0], &["provider_name", "last"]).as_deref(), Some("O'BRIEN"));
+        assert_eq!(text(providers[0], &["enumeration_date"]).as_deref(), Some("2010-03-04"));
+        assert_eq!(text(providers[0], &["mailing_address", "state"]).as_deref(), Some("CA"));
+        assert!(!providers[0].children().any(|n| n.has_tag_name("taxonomy")));
+        
+        let organization = providers[1];
+        assert_eq!(text(organization, &["entity_type"]).as_deref(), Some("2"));
+        assert_eq!(
+            text(organization, &["organization_name", "legal_business_name"]).as_deref(),
+            Some("SMITH & SONS <CLINIC>"),
+        );
+        let taxonomies: Vec<_> = organization.children().filter(|n| n.has_tag_name("taxonomy")).collect();
+        assert_eq!(taxonomies.len(), 2);
+        assert_eq!(text(taxonomies[0], &["code"]).as_deref(), Some("261QM1300X"));
+        assert_eq!(text(taxonomies[0], &["primary"]).as_deref(), Some("true"));
+        assert_eq!(text(taxonomies[1], &["license_number"]).as_deref(), Some("A\"1'"));
+        let identifier = organization.children().find(|n| n.has_tag_name("other_identifier")).unwrap();
+        assert_eq!(text(identifier, &["identifier"]).as_deref(), Some("MCD-42"));
+        assert_eq!(text(identifier, &["state"]).as_deref(), Some("NY"));
+    }
+    
+    #[test]
+    fn test_sql_schema_for_each_dialect() {
+        let mut record = provider("1234567893", StateCode::CA, "90210");
+        record.provider_name.last = Some("O'BRIEN".to_string());
+        let data = dataset(vec![record]);
+        let dir = tempfile::tempdir().unwrap();
+        let sql = |dialect: SqlDialect| {
+            let path = dir.path().join("out.sql");
+            data.export_sql(&path, dialect).unwrap();
+            std::fs::read_to_string(path).unwrap()
+        };
+        
+        let postgres = sql(SqlDialect::PostgreSQL);
+        assert!(postgres.contains("CREATE TABLE IF NOT EXISTS \"nppes_providers\" ("));
+        assert!(postgres.contains("\"id\" SERIAL PRIMARY KEY,"));
+        assert!(postgres.contains("'O''BRIEN'"));
+        
+        let mysql = sql(SqlDialect::MySQL);
+        assert!(mysql.contains("CREATE TABLE IF NOT EXISTS `nppes_providers` ("));
+        assert!(mysql.contains("`id` INT AUTO_INCREMENT PRIMARY KEY,"));
+        assert!(mysql.contains("INSERT INTO `nppes_providers` (`npi`,"));
+        
+        let sqlite = sql(SqlDialect::SQLite);
+        assert!(sqlite.contains("\"id\" INTEGER PRIMARY KEY AUTOINCREMENT,"));
+        assert!(sqlite.contains("\"is_active\" INTEGER DEFAULT 1"));
+        assert!(sqlite.trim_end().ends_with(", 1);"));
+        
+        let sql_server = sql(SqlDialect::SqlServer);
+        assert!(sql_server.contains("IF OBJECT_ID(N'nppes_providers', N'U') IS NULL\nCREATE TABLE [nppes_providers] ("));
+        assert!(sql_server.contains("[id] INT IDENTITY(1,1) PRIMARY KEY,"));
+        assert!(sql_server.contains("[is_active] BIT DEFAULT 1"));
+        assert!(sql_server.contains("N'O''BRIEN'"));
+        
+        for output in [postgres, mysql, sqlite, sql_server] {
+            assert!(!output.contains("not yet implemented"));
+        }
+    }
+    
+    #[cfg(feature = "avro")]
+    #[test]
+    fn test_avro_export_round_trips() {
+        let mut individual = provider("1234567893", StateCode::CA, "90210");
+        individual.provider_name.last = Some("SMITH".to_string());
+        individual.enumeration_date = NaiveDate::from_ymd_opt(2010, 5, 17);
+        individual.sole_proprietor = Some(SoleProprietorCode::Yes);
+        individual.taxonomy_codes.push(TaxonomyCode { code: "207Q00000X".to_string(), is_primary: true, ..Default::default() });
+        let organization = NppesRecord {
+            entity_type: Some(EntityType::Organization),
+            organization_name: OrganizationName { legal_business_name: Some("ACME CLINIC".to_string()), ..Default::default() },
+            authorized_official: Some(AuthorizedOfficial { last_name: Some("DOE".to_string()), ..Default::default() }),
+            ..provider("1245319599", StateCode::NY, "10001")
+        };
+        let data = dataset(vec![individual.clone(), organization.clone()]);
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("providers.avro");
+        data.export_avro(&path).unwrap();
+        
+        let schema = AvroExporter::schema().unwrap();
+        assert!(matches!(schema, apache_avro::Schema::Record(ref record) if record.fields.len() == FLAT_FIELDS.len()));
+        let reloaded = NppesReader::new().load_main_data_avro(&path).unwrap();
+        assert_eq!(reloaded, [individual, organization]);
+    }
+    
+    #[cfg(feature = "arrow-export")]
+    #[test]
+    fn test_parquet_export_round_trips() {
+        let mut individual = provider("1234567893", StateCode::CA, "902101234");
+        individual.provider_name = ProviderName {
+            prefix: Some(NamePrefixCode::Dr),
+            first: Some("JANE".to_string()),
+            last: Some("SMITH".to_string()),
+            credential: Some("MD".to_string()),
+            ..Default::default()
+        };
+        individual.enumeration_date = NaiveDate::from_ymd_opt(2010, 5, 17);
+        individual.deactivation_date = NaiveDate::from_ymd_opt(2020, 1, 2);
+        individual.provider_gender = Some(SexCode::Female);
+        individual.taxonomy_codes.push(TaxonomyCode { code: "207Q00000X".to_string(), is_primary: true, ..Default::default() });
+        individual.other_identifiers.push(OtherIdentifier {
+            identifier: "MCD-42".to_string(),
+            type_code: Some("05".to_string()),
+            issuer: None,
+            state: Some(StateCode::CA),
+        });
+        let organization = NppesRecord {
+            entity_type: Some(EntityType::Organization),
+            organization_name: OrganizationName { legal_business_name: Some("ACME CLINIC".to_string()), ..Default::default() },
+            authorized_official: Some(AuthorizedOfficial { last_name: Some("DOE".to_string()), ..Default::default() }),
+            organization_subpart: Some(SubpartCode::No),
+            ..provider("1245319599", StateCode::NY, "10001")
+        };
+        let data = dataset(vec![individual.clone(), organization.clone()]);
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("providers.parquet");
+        data.export_parquet(&path).unwrap();
+        
+        let reloaded = NppesReader::new().load_main_data_parquet(&path).unwrap();
+        assert_eq!(reloaded, [individual, organization]);
+        assert!(NppesReader::new().load_main_data_parquet(dir.path().join("missing.parquet")).is_err());
+    }
+    
+    #[test]
+    fn test_denormalized_csv_export_uses_main_file_layout() {
+        let mut record = provider("1234567893", StateCode::CA, "90210");
+        record.provider_name.last = Some("SMITH".to_string());
+        record.taxonomy_codes = vec![
+            TaxonomyCode { code: "207Q00000X".to_string(), is_primary: true, ..Default::default() },
+            TaxonomyCode { code: "207R00000X".to_string(), ..Default::default() },
+        ];
+        let data = dataset(vec![record, provider("1245319599", StateCode::NY, "10001")]);
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("flat.csv");
+        CsvExporter::new().with_normalization(false).export(&data, &path).unwrap();
+        
+        let mut reader = csv::Reader::from_path(&path).unwrap();
+        let headers = reader.headers().unwrap().clone();
+        let rows: Vec<csv::StringRecord> = reader.records().map(|r| r.unwrap()).collect();
+        assert_eq!(headers.len(), crate::schema::NppesMainSchema::column_count());
+        assert_eq!(rows.len(), 2);
+        let column = |name: &str| headers.iter().position(|h| h == name).unwrap();
+        assert_eq!(&rows[0][column("NPI")], "1234567893");
+        assert_eq!(&rows[0][column("Provider Last Name (Legal Name)")], "SMITH");
+        assert_eq!(&rows[0][column("Healthcare Provider Taxonomy Code_2")], "207R00000X");
+        assert_eq!(&rows[1][column("Provider Business Mailing Address State Name")], "NY");
+        
+        // The flat file is a valid NPPES main file
+        let reloaded = NppesReader::new().load_main_data(&path).unwrap();
+        assert_eq!(reloaded[0].taxonomy_codes.len(), 2);
+    }
+    
+    #[test]
+    fn test_csv_export_summary_lists_files() {
+        let mut record = provider("1234567893", StateCode::CA, "90210");
+        record.taxonomy_codes = vec![
+            TaxonomyCode { code: "207Q00000X".to_string(), is_primary: true, ..Default::default() },
+            TaxonomyCode { code: "207R00000X".to_string(), ..Default::default() },
+        ];
+        let data = dataset(vec![record, provider("1245319599", StateCode::NY, "10001")]);
+        let dir = tempfile::tempdir().unwrap();
+        let size = |path: &std::path::Path| std::fs::metadata(path).unwrap().len();
+        
+        let summary = CsvExporter::new().export(&data, &dir.path().join("out.csv")).unwrap();
+        assert_eq!(summary.records, 2);
+        assert_eq!(summary.files, [dir.path().join("out_providers.csv"), dir.path().join("out_taxonomies.csv")]);
+        assert_eq!(summary.bytes_written, summary.files.iter().map(|f| size(f)).sum::<u64>());
+        assert!(summary.bytes_written > 0);
+        
+        let flat = dir.path().join("flat.csv");
+        let summary = CsvExporter::new().with_normalization(false).export(&data, &flat).unwrap();
+        assert_eq!((summary.records, summary.bytes_written), (2, size(&flat)));
+        assert_eq!(summary.files, [flat]);
+    }
+    
+    #[test]
+    fn test_csv_export_taxonomy_descriptions() {
+        let mut provider = provider("1234567893", StateCode::CA, "90210");
+        provider.taxonomy_codes = vec![
+            TaxonomyCode { code: "207Q00000X".to_string(), is_primary: true, ..Default::default() },
+            TaxonomyCode { code: "999Z00000X".to_string(), ..Default::default() },
+        ];
+        let taxonomy_map = std::collections::HashMap::from([("207Q00000X".to_string(), TaxonomyReference {
+            code: "207Q00000X".to_string(),
+            classification: Some("Family Medicine".to_string()),
+            display_name: Some("Family Medicine Physician".to_string()),
+            ..Default::default()
+        })]);
+        let data = NppesDataset::new(vec![provider], Some(taxonomy_map), None, None, None, None, None, None);
+        let dir = tempfile::tempdir().unwrap();
+        let taxonomies = |exporter: CsvExporter| {
+            exporter.export(&data, &dir.path().join("out.csv")).unwrap();
+            std::fs::read_to_string(dir.path().join("out_taxonomies.csv")).unwrap()
+        };
+        
+        assert_eq!(
+            taxonomies(CsvExporter::new().with_taxonomy_descriptions(true)),
+            "npi,taxonomy_code,is_primary,license_number,license_state,classification,specialization,display_name\n\
+             1234567893,207Q00000X,Y,,,Family Medicine,,Family Medicine Physician\n\
+             1234567893,999Z00000X,N,,,,,\n"
+        );
+        assert!(taxonomies(CsvExporter::new()).starts_with("npi,taxonomy_code,is_primary,license_number,license_state\n"));
+    }
+    
+    #[test]
+    fn test_sex_codes_round_trip_through_exports() {
+        let with_gender = |npi: &str, gender: SexCode| NppesRecord {
+            provider_gender: Some(gender),
+            ..provider(npi, StateCode::CA, "90210")
+        };
+        let providers = vec![
+            with_gender("1234567893", SexCode::Undisclosed),
+            with_gender("1245319599", SexCode::Unspecified),
+            with_gender("1003000126", SexCode::Female),
+        ];
+        let data = dataset(providers.clone());
+        let genders = |records: &[NppesRecord]| -> Vec<Option<SexCode>> {
+            records.iter().map(|p| p.provider_gender.clone()).collect()
+        };
+        let dir = tempfile::tempdir().unwrap();
+        
+        let flat = dir.path().join("flat.csv");
+        CsvExporter::new().with_normalization(false).export(&data, &flat).unwrap();
+        let reloaded = NppesReader::new().load_main_data(&flat).unwrap();
+        assert_eq!(genders(&reloaded), genders(&providers));
+        assert_eq!(reloaded[1].provider_gender.as_ref().map(SexCode::as_code), Some("X"));
+        
+        let jsonl = dir.path().join("providers.jsonl");
+        data.export_json_lines(&jsonl).unwrap();
+        assert_eq!(genders(&NppesReader::new().load_main_data_jsonl(&jsonl).unwrap()), genders(&providers));
+        
+        let normalized = dir.path().join("out.csv");
+        CsvExporter::new().with_decoded_labels(true).export(&data, &normalized).unwrap();
+        let rows = std::fs::read_to_string(dir.path().join("out_providers.csv")).unwrap();
+        let labels: Vec<&str> = rows.lines().map(|line| line.rsplit(',').next().unwrap()).collect();
+        assert_eq!(labels, ["Undisclosed", "Unspecified", "Female"]);
+    }
+}
+ 
\ No newline at end of file
@@ -0,0 +1,248 @@
+/*!
+ * Federated search across sharded NPPES datasets
+ *
+ * When the full provider file doesn't fit in memory, split it into several
+ * [`NppesDataset`]s (for example one per region) and wrap them in a
+ * [`FederatedDataset`]. Queries use the same filters as
+ * [`NppesDataset::query`] and run against every shard, merging the results.
+ */
+
+use crate::data_types::*;
+use crate::dataset::{CompiledQuery, NppesDataset};
+
+/// A set of datasets queried as one
+pub struct FederatedDataset {
+    shards: Vec<NppesDataset>,
+}
+
+impl FederatedDataset {
+    /// Create a federation from its shards
+    pub fn new(shards: Vec<NppesDataset>) -> Self {
+        Self { shards }
+    }
+
+    /// Get the shards
+    pub fn shards(&self) -> &[NppesDataset] {
+        &self.shards
+    }
+
+    /// Take back the shards
+    pub fn into_shards(self) -> Vec<NppesDataset> {
+        self.shards
+    }
+
+    /// Total number of providers across all shards
+    pub fn len(&self) -> usize {
+        self.shards.iter().map(NppesDataset::len).sum()
+    }
+
+    /// Check if every shard is empty
+    pub fn is_empty(&self) -> bool {
+        self.shards.iter().all(NppesDataset::is_empty)
+    }
+
+    /// Get a provider by NPI, checking each shard in turn (fast if shards are indexed)
+    pub fn get_by_npi(&self, npi: &Npi) -> Option<&NppesRecord> {
+        self.shards.iter().find_map(|shard| shard.get_by_npi(npi))
+    }
+
+    /// Start a query across all shards
+    pub fn query(&self) -> FederatedQuery<'_> {
+        FederatedQuery {
+            federation: self,
+            query: CompiledQuery::new(),
+        }
+    }
+
+    /// Run a compiled query against every shard and merge the matches
+    ///
+    /// Shards are searched concurrently with the `parallel` feature. Results are
+    /// grouped by shard, in shard order.
+    pub fn run<'d>(&'d self, query: &CompiledQuery<'_>) -> Vec<&'d NppesRecord> {
+        #[cfg(feature = "parallel")]
+        {
+            use rayon::prelude::*;
+            let per_shard: Vec<Vec<&NppesRecord>> = self.shards.par_iter()
+                .map(|shard| query.run(shard))
+                .collect();
+            per_shard.into_iter().flatten().collect()
+        }
+
+        #[cfg(not(feature = "parallel"))]
+        {
+            self.shards.iter()
+                .flat_map(|shard| query.run(shard))
+                .collect()
+        }
+    }
+}
+
+impl FromIterator<NppesDataset> for FederatedDataset {
+    fn from_iter<I: IntoIterator<Item = NppesDataset>>(iter: I) -> Self {
+        Self::new(iter.into_iter().collect())
+    }
+}
+
+/// Query builder for a [`FederatedDataset`], mirroring [`QueryBuilder`](crate::dataset::QueryBuilder)
+pub struct FederatedQuery<'a> {
+    federation: &'a FederatedDataset,
+    query: CompiledQuery<'a>,
+}
+
+impl<'a> FederatedQuery<'a> {
+    /// Apply the location filters that follow to the practice address
+    pub fn use_practice_address(mut self) -> Self {
+        self.query = self.query.use_practice_address();
+        self
+    }
+
+    /// Apply the location filters that follow to the mailing address (the default)
+    pub fn use_mailing_address(mut self) -> Self {
+        self.query = self.query.use_mailing_address();
+        self
+    }
+
+    /// Filter by state of the selected address (mailing by default)
+    pub fn state(mut self, state: &'a str) -> Self {
+        self.query = self.query.state(state);
+        self
+    }
+
+    /// Filter by multiple states of the selected address
+    pub fn state_in(mut self, states: &'a [&str]) -> Self {
+        self.query = self.query.state_in(states);
+        self
+    }
+
+    /// Filter by country code of the selected address, ignoring case
+    pub fn country(mut self, code: &'a str) -> Self {
+        self.query = self.query.country(code);
+        self
+    }
+    
+    /// Filter by postal code of the selected address, compared on ZIP5
+    pub fn postal_code(mut self, postal_code: &'a str) -> Self {
+        self.query = self.query.postal_code(postal_code);
+        self
+    }
+
+    /// Filter by postal code prefix of the selected address
+    pub fn postal_code_prefix(mut self, prefix: &'a str) -> Self {
+        self.query = self.query.postal_code_prefix(prefix);
+        self
+    }
+
+    /// Filter by city of the selected address, ignoring case and surrounding whitespace
+    pub fn city(mut self, city: &'a str) -> Self {
+        self.query = self.query.city(city);
+        self
+    }
+
+    /// Filter to cities of the selected address containing the text, ignoring case
+    pub fn city_contains(mut self, text: &'a str) -> Self {
+        self.query = self.query.city_contains(text);
+        self
+    }
+
+    /// Filter to providers with a secondary practice location in a state
+    pub fn secondary_location_state(mut self, state: &'a str) -> Self {
+        self.query = self.query.secondary_location_state(state);
+        self
+    }
+
+    /// Filter by specialty (taxonomy display name)
+    pub fn specialty(mut self, specialty: &'a str) -> Self {
+        self.query = self.query.specialty(specialty);
+        self
+    }
+
+    /// Filter by exact NUCC taxonomy code, primary or not
+    pub fn taxonomy_code(mut self, taxonomy_code: &'a str) -> Self {
+        self.query = self.query.taxonomy_code(taxonomy_code);
+        self
+    }
+
+    /// Filter by exact NUCC taxonomy code, only when it is the provider's primary taxonomy
+    pub fn primary_taxonomy_code(mut self, taxonomy_code: &'a str) -> Self {
+        self.query = self.query.primary_taxonomy_code(taxonomy_code);
+        self
+    }
+
+    /// Filter by entity type
+    pub fn entity_type(mut self, entity_type: EntityType) -> Self {
+        self.query = self.query.entity_type(entity_type);
+        self
+    }
+
+    /// Filter by active status
+    pub fn active_only(mut self) -> Self {
+        self.query = self.query.active_only();
+        self
+    }
+
+    /// Filter to inactive providers deactivated for a reason, such as death or fraud
+    pub fn deactivated_for(mut self, reason: DeactivationReasonCode) -> Self {
+        self.query = self.query.deactivated_for(reason);
+        self
+    }
+
+    /// Filter with an arbitrary predicate
+    pub fn custom<F>(mut self, predicate: F) -> Self
+    where
+        F: Fn(&NppesRecord) -> bool + Send + Sync + 'a,
+    {
+        self.query = self.query.custom(predicate);
+        self
+    }
+
+    /// Finish building and return the filters as a [`CompiledQuery`]
+    pub fn build(self) -> CompiledQuery<'a> {
+        self.query
+    }
+
+    /// Execute the query on every shard and return the merged matches
+    pub fn execute(self) -> Vec<&'a NppesRecord> {
+        self.federation.run(&self.query)
+    }
+
+    /// Execute the query and return count only
+    pub fn count(self) -> usize {
+        self.execute().len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shard(providers: &[(&str, StateCode)]) -> NppesDataset {
+        let providers = providers.iter()
+            .map(|(npi, state)| NppesRecord {
+                npi: Npi::new(npi.to_string()).unwrap(),
+                entity_type: Some(EntityType::Individual),
+                mailing_address: Address { state: Some(state.clone()), ..Default::default() },
+                ..Default::default()
+            })
+            .collect();
+        NppesDataset::new(providers, None, None, None, None, None, None, None)
+    }
+
+    #[test]
+    fn test_federated_query_merges_shards() {
+        let mut west = shard(&[("1234567893", StateCode::CA), ("1245319599", StateCode::OR)]);
+        west.build_indexes();
+        let east = shard(&[("1003000126", StateCode::CA)]);
+        let federation: FederatedDataset = vec![west, east].into_iter().collect();
+
+        assert_eq!(federation.len(), 3);
+        let npis: Vec<&str> = federation.query().state("CA").execute()
+            .iter()
+            .map(|p| p.npi.as_str())
+            .collect();
+        assert_eq!(npis, ["1234567893", "1003000126"]);
+
+        let npi = Npi::new("1003000126".to_string()).unwrap();
+        assert_eq!(federation.get_by_npi(&npi).map(|p| &p.npi), Some(&npi));
+        assert!(federation.get_by_npi(&Npi::new("1245319599".to_string()).unwrap()).is_some());
+    }
+}
@@ -0,0 +1,293 @@
+/*!
+ * FHIR R4 resources for NPPES providers
+ *
+ * With the `fhir` feature, [`NppesRecord::to_fhir_practitioner`] and
+ * [`NppesRecord::to_fhir_organization`] render a record as a FHIR R4
+ * `Practitioner` or `Organization` resource in JSON. Absent values are left
+ * out rather than written as nulls or empty arrays, as FHIR requires.
+ */
+
+use serde_json::{json, Map, Value};
+
+use crate::data_types::*;
+
+/// Identifier system for National Provider Identifiers
+pub const NPI_SYSTEM: &str = "http://hl7.org/fhir/sid/us-npi";
+
+/// Identifier system for Employer Identification Numbers
+pub const EIN_SYSTEM: &str = "urn:oid:2.16.840.1.113883.4.4";
+
+/// Code system for NUCC healthcare provider taxonomy codes
+pub const TAXONOMY_SYSTEM: &str = "http://nucc.org/provider-taxonomy";
+
+impl NppesRecord {
+    /// Render an individual provider as a FHIR R4 `Practitioner`
+    ///
+    /// Maps the NPI to an identifier, the provider name to a `HumanName`, the
+    /// mailing and practice addresses to `Address`es, gender from [`SexCode`]
+    /// and taxonomy codes to qualifications. Returns `None` unless the entity
+    /// type is [`EntityType::Individual`].
+    pub fn to_fhir_practitioner(&self) -> Option<Value> {
+        if self.entity_type != Some(EntityType::Individual) {
+            return None;
+        }
+
+        let mut resource = self.base_resource("Practitioner", vec![npi_identifier(&self.npi)]);
+        if let Some(name) = human_name(&self.provider_name, "official") {
+            let mut names = vec![name];
+            names.extend(human_name(&self.provider_other_name, "usual"));
+            resource.insert("name".to_string(), Value::Array(names));
+        }
+        insert_list(&mut resource, "telecom", self.telecoms());
+        insert_list(&mut resource, "address", self.addresses());
+        if let Some(gender) = &self.provider_gender {
+            let gender = match gender {
+                SexCode::Male => "male",
+                SexCode::Female => "female",
+                SexCode::Undisclosed => "unknown",
+                SexCode::Unspecified => "other",
+            };
+            resource.insert("gender".to_string(), json!(gender));
+        }
+        let qualifications = self.taxonomy_codes.iter()
+            .map(|taxonomy| {
+                let mut qualification = Map::new();
+                qualification.insert("code".to_string(), json!({
+                    "coding": [{ "system": TAXONOMY_SYSTEM, "code": taxonomy.code }],
+                }));
+                if let Some(license) = &taxonomy.license_number {
+                    qualification.insert("identifier".to_string(), json!([{ "value": license }]));
+                }
+                Value::Object(qualification)
+            })
+            .collect();
+        insert_list(&mut resource, "qualification", qualifications);
+        Some(Value::Object(resource))
+    }
+
+    /// Render an organization provider as a FHIR R4 `Organization`
+    ///
+    /// Maps the NPI (and a well-formed EIN) to identifiers, the legal business
+    /// name to `name`, the other name to `alias` and the mailing and practice
+    /// addresses to `Address`es. Returns `None` unless the entity type is
+    /// [`EntityType::Organization`].
+    pub fn to_fhir_organization(&self) -> Option<Value> {
+        if self.entity_type != Some(EntityType::Organization) {
+            return None;
+        }
+
+        let mut identifiers = vec![npi_identifier(&self.npi)];
+        if let Some(ein) = self.ein.as_ref().filter(|ein| ein.is_valid()) {
+            identifiers.push(json!({ "system": EIN_SYSTEM, "value": ein.as_str() }));
+        }
+        let mut resource = self.base_resource("Organization", identifiers);
+        if let Some(name) = &self.organization_name.legal_business_name {
+            resource.insert("name".to_string(), json!(name));
+        }
+        if let Some(alias) = &self.organization_name.other_name {
+            resource.insert("alias".to_string(), json!([alias]));
+        }
+        insert_list(&mut resource, "telecom", self.telecoms());
+        insert_list(&mut resource, "address", self.addresses());
+        Some(Value::Object(resource))
+    }
+
+    /// Resource type, identifiers and active flag shared by both resources
+    fn base_resource(&self, resource_type: &str, identifiers: Vec<Value>) -> Map<String, Value> {
+        let mut resource = Map::new();
+        resource.insert("resourceType".to_string(), json!(resource_type));
+        resource.insert("identifier".to_string(), Value::Array(identifiers));
+        resource.insert("active".to_string(), json!(self.is_active()));
+        resource
+    }
+
+    /// Mailing address as `postal` and practice address as `physical`
+    fn addresses(&self) -> Vec<Value> {
+        [(&self.mailing_address, "postal"), (&self.practice_address, "physical")]
+            .into_iter()
+            .filter(|(address, _)| !address.is_empty())
+            .map(|(address, kind)| fhir_address(address, kind))
+            .collect()
+    }
+
+    /// Practice location phone and fax numbers
+    fn telecoms(&self) -> Vec<Value> {
+        [(&self.practice_address.telephone, "phone"), (&self.practice_address.fax, "fax")]
+            .into_iter()
+            .filter_map(|(number, system)| {
+                number.as_ref().map(|number| json!({ "system": system, "value": number, "use": "work" }))
+            })
+            .collect()
+    }
+}
+
+fn npi_identifier(npi: &Npi) -> Value {
+    json!({ "use": "official", "system": NPI_SYSTEM, "value": npi.as_str() })
+}
+
+/// Build a `HumanName`, or `None` when there's no first or last name
+fn human_name(name: &ProviderName, name_use: &str) -> Option<Value> {
+    if name.first.is_none() && name.last.is_none() {
+        return None;
+    }
+    let mut human_name = Map::new();
+    human_name.insert("use".to_string(), json!(name_use));
+    if let Some(last) = &name.last {
+        human_name.insert("family".to_string(), json!(last));
+    }
+    let given: Vec<&String> = name.first.iter().chain(&name.middle).collect();
+    if !given.is_empty() {
+        human_name.insert("given".to_string(), json!(given));
+    }
+    if let Some(prefix) = &name.prefix {
+        human_name.insert("prefix".to_string(), json!([prefix.as_code()]));
+    }
+    let suffixes: Vec<&str> = name.suffix.iter()
+        .map(|suffix| suffix.as_code())
+        .chain(name.credential.as_deref())
+        .collect();
+    if !suffixes.is_empty() {
+        human_name.insert("suffix".to_string(), json!(suffixes));
+    }
+    Some(Value::Object(human_name))
+}
+
+fn fhir_address(address: &Address, kind: &str) -> Value {
+    let mut fhir = Map::new();
+    fhir.insert("use".to_string(), json!("work"));
+    fhir.insert("type".to_string(), json!(kind));
+    let lines: Vec<&String> = address.line_1.iter().chain(&address.line_2).collect();
+    if !lines.is_empty() {
+        fhir.insert("line".to_string(), json!(lines));
+    }
+    if let Some(city) = &address.city {
+        fhir.insert("city".to_string(), json!(city));
+    }
+    if let Some(state) = &address.state {
+        fhir.insert("state".to_string(), json!(state.as_code()));
+    }
+    if let Some(postal_code) = &address.postal_code {
+        fhir.insert("postalCode".to_string(), json!(postal_code));
+    }
+    if let Some(country) = &address.country {
+        fhir.insert("country".to_string(), json!(country.as_code()));
+    }
+    Value::Object(fhir)
+}
+
+/// Insert a list element, leaving it out when empty
+fn insert_list(resource: &mut Map<String, Value>, key: &str, values: Vec<Value>) {
+    if !values.is_empty() {
+        resource.insert(key.to_string(), Value::Array(values));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn address(line_1: &str, city: &str, state: StateCode, postal_code: &str) -> Address {
+        Address {
+            line_1: Some(line_1.to_string()),
+            city: Some(city.to_string()),
+            state: Some(state),
+            postal_code: Some(postal_code.to_string()),
+            country: Some(CountryCode::from_code("US")),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_practitioner_resource() {
+        let record = NppesRecord {
+            npi: Npi::new("1234567893".to_string()).unwrap(),
+            entity_type: Some(EntityType::Individual),
+            provider_name: ProviderName {
+                prefix: Some(NamePrefixCode::Dr),
+                first: Some("JANE".to_string()),
+                middle: Some("Q".to_string()),
+                last: Some("SMITH".to_string()),
+                credential: Some("MD".to_string()),
+                ..Default::default()
+            },
+            mailing_address: address("PO BOX 12", "AUSTIN", StateCode::TX, "78701"),
+            practice_address: Address {
+                telephone: Some("5125550100".to_string()),
+                ..address("100 MAIN ST", "AUSTIN", StateCode::TX, "787011234")
+            },
+            provider_gender: Some(SexCode::Female),
+            taxonomy_codes: vec![TaxonomyCode {
+                code: "207Q00000X".to_string(),
+                license_number: Some("L1234".to_string()),
+                is_primary: true,
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        assert_eq!(record.to_fhir_practitioner().unwrap(), json!({
+            "resourceType": "Practitioner",
+            "identifier": [{ "use": "official", "system": NPI_SYSTEM, "value": "1234567893" }],
+            "active": true,
+            "name": [{
+                "use": "official",
+                "family": "SMITH",
+                "given": ["JANE", "Q"],
+                "prefix": ["Dr."],
+                "suffix": ["MD"],
+            }],
+            "telecom": [{ "system": "phone", "value": "5125550100", "use": "work" }],
+            "address": [
+                {
+                    "use": "work", "type": "postal", "line": ["PO BOX 12"], "city": "AUSTIN",
+                    "state": "TX", "postalCode": "78701", "country": "US",
+                },
+                {
+                    "use": "work", "type": "physical", "line": ["100 MAIN ST"], "city": "AUSTIN",
+                    "state": "TX", "postalCode": "787011234", "country": "US",
+                },
+            ],
+            "gender": "female",
+            "qualification": [{
+                "code": { "coding": [{ "system": TAXONOMY_SYSTEM, "code": "207Q00000X" }] },
+                "identifier": [{ "value": "L1234" }],
+            }],
+        }));
+        assert!(record.to_fhir_organization().is_none());
+        let unknown = NppesRecord { entity_type: None, ..record };
+        assert!(unknown.to_fhir_practitioner().is_none());
+    }
+
+    #[test]
+    fn test_organization_resource() {
+        let record = NppesRecord {
+            npi: Npi::new("1245319599".to_string()).unwrap(),
+            entity_type: Some(EntityType::Organization),
+            ein: Some(Ein::new("12-3456789").unwrap()),
+            organization_name: OrganizationName {
+                legal_business_name: Some("AUSTIN HEALTH LLC".to_string()),
+                other_name: Some("AUSTIN CLINIC".to_string()),
+                ..Default::default()
+            },
+            practice_address: address("100 MAIN ST", "AUSTIN", StateCode::TX, "78701"),
+            deactivation_date: chrono::NaiveDate::from_ymd_opt(2024, 1, 2),
+            ..Default::default()
+        };
+
+        assert_eq!(record.to_fhir_organization().unwrap(), json!({
+            "resourceType": "Organization",
+            "identifier": [
+                { "use": "official", "system": NPI_SYSTEM, "value": "1245319599" },
+                { "system": EIN_SYSTEM, "value": "123456789" },
+            ],
+            "active": false,
+            "name": "AUSTIN HEALTH LLC",
+            "alias": ["AUSTIN CLINIC"],
+            "address": [{
+                "use": "work", "type": "physical", "line": ["100 MAIN ST"], "city": "AUSTIN",
+                "state": "TX", "postalCode": "78701", "country": "US",
+            }],
+        }));
+        assert!(record.to_fhir_practitioner().is_none());
+    }
+}
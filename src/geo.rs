@@ -0,0 +1,296 @@
+/*!
+ * Geographic helpers for NPPES data
+ *
+ * Defines the [`Geocoder`] trait for turning addresses into coordinates and
+ * great-circle distance calculations used by location-based checks.
+ *
+ * A bundled table of approximate US ZIP code centroids (from the free
+ * federalgovernmentzipcodes.us database, primary locations only) backs
+ * [`zip_centroid`], the [`ZipCentroids`] geocoder and radius searches with
+ * [`NppesDataset::query_within_radius`]. Coordinates have two decimal places,
+ * so distances are good to about a mile, and some ZIPs resolve to their
+ * city's center. The table adds about 800KB to the binary, so it's only
+ * compiled in with the `zip-centroids` feature.
+ */
+
+#[cfg(feature = "zip-centroids")]
+use std::collections::HashMap;
+
+use crate::data_types::*;
+use crate::dataset::NppesDataset;
+
+/// Bundled `zip,latitude,longitude` table
+#[cfg(feature = "zip-centroids")]
+const ZIP_CENTROIDS_CSV: &str = include_str!("../data/zip_centroids.csv");
+
+#[cfg(feature = "zip-centroids")]
+lazy_static::lazy_static! {
+    static ref ZIP_CENTROIDS: HashMap<&'static str, Coordinates> = ZIP_CENTROIDS_CSV
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let mut fields = line.split(',');
+            let zip = fields.next()?;
+            let latitude = fields.next()?.parse().ok()?;
+            let longitude = fields.next()?.parse().ok()?;
+            Some((zip, Coordinates::new(latitude, longitude)))
+        })
+        .collect();
+}
+
+/// Mean radius of the Earth in miles
+pub const EARTH_RADIUS_MILES: f64 = 3958.8;
+
+/// A point on the Earth's surface in decimal degrees
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Coordinates {
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+impl Coordinates {
+    /// Create coordinates from latitude and longitude in decimal degrees
+    pub fn new(latitude: f64, longitude: f64) -> Self {
+        Self { latitude, longitude }
+    }
+
+    /// Great-circle distance to another point in miles
+    pub fn distance_miles(&self, other: &Coordinates) -> f64 {
+        haversine_miles(*self, *other)
+    }
+}
+
+/// Great-circle distance between two points in miles, using the haversine formula
+pub fn haversine_miles(a: Coordinates, b: Coordinates) -> f64 {
+    let (lat_a, lat_b) = (a.latitude.to_radians(), b.latitude.to_radians());
+    let delta_lat = lat_b - lat_a;
+    let delta_lon = (b.longitude - a.longitude).to_radians();
+
+    let h = (delta_lat / 2.0).sin().powi(2) + lat_a.cos() * lat_b.cos() * (delta_lon / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS_MILES * h.sqrt().asin()
+}
+
+/// Resolves addresses to coordinates
+///
+/// Implement this over whatever geocoding source you have (a local lookup table,
+/// a cached service). Closures of the form `Fn(&Address) -> Option<Coordinates>`
+/// implement it too.
+pub trait Geocoder {
+    /// Locate an address, or `None` if it can't be resolved
+    fn geocode(&self, address: &Address) -> Option<Coordinates>;
+}
+
+impl<F> Geocoder for F
+where
+    F: Fn(&Address) -> Option<Coordinates>,
+{
+    fn geocode(&self, address: &Address) -> Option<Coordinates> {
+        self(address)
+    }
+}
+
+/// Approximate centroid of a US ZIP code
+///
+/// Accepts ZIP5 or ZIP+4 (see [`normalize_zip5`]). Returns `None` for ZIPs
+/// not in the bundled table.
+#[cfg(feature = "zip-centroids")]
+pub fn zip_centroid(zip: &str) -> Option<Coordinates> {
+    let zip5 = normalize_zip5(zip)?;
+    ZIP_CENTROIDS.get(zip5.as_str()).copied()
+}
+
+/// Miles between the centroids of two US ZIP codes, if both are known
+#[cfg(feature = "zip-centroids")]
+pub fn zip_distance_miles(a: &str, b: &str) -> Option<f64> {
+    Some(haversine_miles(zip_centroid(a)?, zip_centroid(b)?))
+}
+
+/// Miles from a point to a provider's mailing ZIP centroid
+///
+/// `None` when the mailing address is outside the US or its ZIP is unknown.
+/// Combine it with other filters through a custom query predicate:
+///
+/// ```no_run
+/// # use nppes::prelude::*;
+/// use nppes::geo::{mailing_distance_miles, zip_centroid};
+///
+/// # let dataset = NppesDataset::load_standard("./data")?;
+/// let origin = zip_centroid("90210").expect("known ZIP");
+/// let nearby_cardiologists = dataset.query()
+///     .taxonomy_code("207RC0000X")
+///     .active_only()
+///     .custom(move |p| mailing_distance_miles(p, &origin).is_some_and(|miles| miles <= 25.0))
+///     .execute();
+/// # Ok::<(), nppes::NppesError>(())
+/// ```
+#[cfg(feature = "zip-centroids")]
+pub fn mailing_distance_miles(provider: &NppesRecord, origin: &Coordinates) -> Option<f64> {
+    ZipCentroids.geocode(&provider.mailing_address)
+        .map(|location| haversine_miles(*origin, location))
+}
+
+/// Geocoder placing US addresses at their ZIP code's centroid
+///
+/// Addresses with a non-US country code, or a postal code missing from the
+/// bundled table, don't resolve.
+#[cfg(feature = "zip-centroids")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ZipCentroids;
+
+#[cfg(feature = "zip-centroids")]
+impl Geocoder for ZipCentroids {
+    fn geocode(&self, address: &Address) -> Option<Coordinates> {
+        if address.country.as_ref().is_some_and(|country| country.as_code() != "US") {
+            return None;
+        }
+        zip_centroid(address.postal_code.as_deref()?)
+    }
+}
+
+impl NppesDataset {
+    /// Providers whose mailing ZIP centroid lies within `miles` of a ZIP's centroid
+    ///
+    /// Providers with unknown or non-US ZIPs are excluded, and an unknown
+    /// origin ZIP matches nothing. Results are in dataset order; use
+    /// [`mailing_distance_miles`] to combine the radius with other filters.
+    #[cfg(feature = "zip-centroids")]
+    pub fn query_within_radius(&self, zip: &str, miles: f64) -> Vec<&NppesRecord> {
+        let Some(origin) = zip_centroid(zip) else {
+            return Vec::new();
+        };
+        self.providers.iter()
+            .filter(|provider| mailing_distance_miles(provider, &origin).is_some_and(|distance| distance <= miles))
+            .collect()
+    }
+
+    /// Distance from each endpoint's affiliation address to its provider's practice address
+    ///
+    /// Returns one `(npi, miles)` entry per endpoint whose affiliation address and
+    /// provider practice address both geocode, sorted farthest first so implausible
+    /// affiliations surface at the top. Empty when endpoints weren't loaded.
+    pub fn endpoint_affiliation_distances(&self, geocoder: &impl Geocoder) -> Vec<(Npi, f64)> {
+        let Some(endpoints_map) = &self.endpoints_map else {
+            return Vec::new();
+        };
+
+        let mut distances = Vec::new();
+        for (npi, endpoints) in endpoints_map {
+            let Some(practice) = self.get_by_npi(npi).and_then(|p| geocoder.geocode(&p.practice_address)) else {
+                continue;
+            };
+            for endpoint in endpoints {
+                if let Some(affiliation) = endpoint.affiliation_address.as_ref().and_then(|a| geocoder.geocode(a)) {
+                    distances.push((npi.clone(), practice.distance_miles(&affiliation)));
+                }
+            }
+        }
+
+        distances.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.as_str().cmp(b.0.as_str())));
+        distances
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_haversine_known_distance() {
+        let los_angeles = Coordinates::new(34.0522, -118.2437);
+        let new_york = Coordinates::new(40.7128, -74.0060);
+        let miles = los_angeles.distance_miles(&new_york);
+        assert!((miles - 2445.0).abs() < 10.0, "{}", miles);
+        assert_eq!(los_angeles.distance_miles(&los_angeles), 0.0);
+    }
+
+    #[cfg(feature = "zip-centroids")]
+    #[test]
+    fn test_zip_centroid_distances() {
+        let between = |a: &str, b: &str| zip_distance_miles(a, b).unwrap();
+        // Beverly Hills to Manhattan
+        assert!((between("90210", "10001") - 2450.0).abs() < 25.0, "{}", between("90210", "10001"));
+        // Boston to Chicago
+        assert!((between("02115", "60601") - 850.0).abs() < 25.0, "{}", between("02115", "60601"));
+        // Downtown San Francisco to Oakland
+        assert!(between("94103", "94612") < 15.0);
+        assert_eq!(between("90210-1234", "90210"), 0.0);
+        assert!(zip_centroid("00000").is_none());
+        assert!(zip_centroid("ABC").is_none());
+    }
+
+    #[cfg(feature = "zip-centroids")]
+    #[test]
+    fn test_query_within_radius() {
+        let at = |npi: &str, zip: &str, country: Option<&str>| NppesRecord {
+            npi: Npi::new(npi.to_string()).unwrap(),
+            mailing_address: Address {
+                postal_code: Some(zip.to_string()),
+                country: country.map(CountryCode::from_code),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let dataset = NppesDataset::new(
+            vec![
+                at("1234567893", "900241234", Some("US")),
+                at("1245319599", "10001", None),
+                at("1003000126", "90024", Some("CA")),
+            ],
+            None, None, None, None, None, None, None,
+        );
+
+        let nearby = dataset.query_within_radius("90210", 25.0);
+        assert_eq!(nearby.len(), 1);
+        assert_eq!(nearby[0].npi.as_str(), "1234567893");
+        assert_eq!(dataset.query_within_radius("90210", 3000.0).len(), 2);
+        assert!(dataset.query_within_radius("00000", 3000.0).is_empty());
+    }
+
+    #[test]
+    fn test_endpoint_affiliation_distances() {
+        let npi = Npi::new("1234567893".to_string()).unwrap();
+        let address = |city: &str| Address { city: Some(city.to_string()), ..Default::default() };
+        let provider = NppesRecord {
+            npi: npi.clone(),
+            practice_address: address("LOS ANGELES"),
+            ..Default::default()
+        };
+        let endpoint = |city: Option<&str>| EndpointRecord {
+            npi: npi.clone(),
+            endpoint_type: None,
+            endpoint_type_description: None,
+            endpoint: None,
+            affiliation: Some(true),
+            endpoint_description: None,
+            affiliation_legal_business_name: None,
+            use_code: None,
+            use_description: None,
+            other_use_description: None,
+            content_type: None,
+            content_description: None,
+            other_content_description: None,
+            affiliation_address: city.map(address),
+        };
+        let dataset = NppesDataset::new(
+            vec![provider],
+            None,
+            None,
+            None,
+            Some(HashMap::from([(npi.clone(), vec![endpoint(Some("NEW YORK")), endpoint(None)])])),
+            None,
+            None,
+            None,
+        );
+
+        let geocoder = |address: &Address| match address.city.as_deref() {
+            Some("LOS ANGELES") => Some(Coordinates::new(34.0522, -118.2437)),
+            Some("NEW YORK") => Some(Coordinates::new(40.7128, -74.0060)),
+            _ => None,
+        };
+        let distances = dataset.endpoint_affiliation_distances(&geocoder);
+        assert_eq!(distances.len(), 1);
+        assert_eq!(distances[0].0, npi);
+        assert!(distances[0].1 > 2400.0);
+    }
+}
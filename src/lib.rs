@@ -134,10 +134,17 @@
  * // Export to SQL
  * dataset.export_sql("providers.sql", SqlDialect::PostgreSQL)?;
  * 
+ * // Export to XML
+ * dataset.export_xml("providers.xml")?;
+ * 
  * // Export to Parquet (if enabled)
  * #[cfg(feature = "arrow-export")]
  * dataset.export_parquet("providers.parquet")?;
  * 
+ * // Export to Avro (if enabled)
+ * #[cfg(feature = "avro")]
+ * dataset.export_avro("providers.avro")?;
+ * 
  * // Export filtered subset
  * dataset.export_subset(
  *     "texas_organizations.json",
@@ -205,6 +212,14 @@ pub mod dataset;
 pub mod export;
 pub mod config;
 pub mod download;
+pub mod geo;
+pub mod federated;
+pub mod matching;
+pub mod diff;
+#[cfg(feature = "fhir")]
+pub mod fhir;
+#[cfg(feature = "testing")]
+pub mod testing;
 
 /// Prelude module for convenient imports
 /// 
@@ -214,15 +229,23 @@ pub mod download;
 /// ```
 pub mod prelude {
     pub use crate::data_types::*;
-    pub use crate::reader::{NppesReader, ProgressInfo, MemoryEstimate};
+    pub use crate::reader::{NppesReader, ProgressInfo, MemoryEstimate, TextEncoding, LoadReport, NppesFileKind};
     pub use crate::schema::*;
     pub use crate::error::{NppesError, Result};
-    pub use crate::analytics::{NppesAnalytics, DatasetStats};
-    pub use crate::dataset::{NppesDataset, NppesDatasetBuilder, DatasetStatistics};
-    pub use crate::export::{NppesExporter, JsonExporter, CsvExporter, SqlExporter};
+    pub use crate::analytics::{NppesAnalytics, DatasetStats, Quantiles, DateIssue};
+    pub use crate::dataset::{NppesDataset, NppesDatasetBuilder, DatasetStatistics, FullProvider, CompiledQuery, QueryResult, SortKey, ProviderTaxonomyRow, NpiFileFilter, InvalidNpiLine};
+    pub use crate::export::{NppesExporter, ExportSummary, JsonExporter, CsvExporter, SqlExporter, SearchDocExporter, XmlExporter};
     #[cfg(feature = "arrow-export")]
     pub use crate::export::ParquetExporter;
+    #[cfg(feature = "avro")]
+    pub use crate::export::AvroExporter;
     pub use crate::config::{ConfigBuilder, ValidationLevel};
+    pub use crate::geo::{Geocoder, Coordinates};
+    #[cfg(feature = "zip-centroids")]
+    pub use crate::geo::ZipCentroids;
+    pub use crate::federated::FederatedDataset;
+    pub use crate::diff::{DatasetDiff, ProviderChange, ChangedField};
+    pub use crate::matching::{NameMatcher, ExactMatcher, SubstringMatcher, LevenshteinMatcher, MetaphoneMatcher, TokenSetMatcher, MatchConfig, ExternalMatch, MatchCandidate};
     pub use crate::ExportFormat;
 }
 
@@ -294,18 +317,16 @@ pub mod cookbook {
     
     /// Find providers by partial name match
     /// 
-    /// Case-insensitive search across organization and individual names
+    /// Case-insensitive search across every name a provider carries,
+    /// including other names (see [`NppesRecord::all_names`])
     pub fn find_by_partial_name<'a>(
         dataset: &'a NppesDataset,
         name_query: &str
     ) -> Vec<&'a NppesRecord> {
-        let query_lower = name_query.to_lowercase();
+        use crate::matching::{NameMatcher, SubstringMatcher};
         
         dataset.providers.iter()
-            .filter(|p| {
-                let display_name = p.display_name().to_lowercase();
-                display_name.contains(&query_lower)
-            })
+            .filter(|p| p.all_names().iter().any(|name| SubstringMatcher.matches(name_query, name)))
             .collect()
     }
     
@@ -334,7 +355,8 @@ pub mod cookbook {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::data_types::{Npi, EntityType};
+    use crate::data_types::{Npi, Ein, EntityType, NppesRecord, ProviderName, SoleProprietorCode, SubpartCode};
+    use crate::dataset::NppesDataset;
     
     #[test]
     fn test_npi_validation() {
@@ -343,10 +365,123 @@ mod tests {
         assert!(Npi::new("12345678AB".to_string()).is_err());
     }
     
+    #[test]
+    fn test_ein_normalization() {
+        let hyphenated = Ein::new("12-3456789").unwrap();
+        assert_eq!(hyphenated, Ein::new("123456789").unwrap());
+        assert_eq!(hyphenated.as_str(), "123456789");
+        assert_eq!(hyphenated.formatted().as_deref(), Some("12-3456789"));
+        assert!(Ein::new("12-345678").is_err());
+        
+        let raw = Ein::from_raw("<UNAVAIL>");
+        assert!(!raw.is_valid());
+        assert_eq!(raw.formatted(), None);
+        assert_eq!(raw.to_string(), "<UNAVAIL>");
+    }
+    
+    #[test]
+    fn test_is_active_accounts_for_reactivation() {
+        let date = |y, m, d| chrono::NaiveDate::from_ymd_opt(y, m, d);
+        let record = |deactivated, reactivated| NppesRecord {
+            deactivation_date: deactivated,
+            reactivation_date: reactivated,
+            ..Default::default()
+        };
+        
+        assert!(record(None, None).is_active());
+        assert!(!record(date(2020, 1, 1), None).is_active());
+        assert!(record(date(2020, 1, 1), date(2021, 6, 1)).is_active());
+        assert!(record(date(2020, 1, 1), date(2020, 1, 1)).is_active());
+        // A reactivation before the deactivation is a data error, not a reactivation
+        assert!(!record(date(2020, 1, 1), date(2019, 6, 1)).is_active());
+    }
+    
+    #[test]
+    fn test_sole_proprietor_individual() {
+        let record = |entity_type, sole_proprietor| NppesRecord {
+            entity_type,
+            sole_proprietor,
+            ..Default::default()
+        };
+        
+        assert!(record(Some(EntityType::Individual), Some(SoleProprietorCode::Yes)).is_sole_proprietor_individual());
+        assert!(!record(Some(EntityType::Individual), Some(SoleProprietorCode::No)).is_sole_proprietor_individual());
+        assert!(!record(Some(EntityType::Individual), Some(SoleProprietorCode::NotAnswered)).is_sole_proprietor_individual());
+        assert!(!record(Some(EntityType::Individual), None).is_sole_proprietor_individual());
+        assert!(!record(Some(EntityType::Organization), Some(SoleProprietorCode::Yes)).is_sole_proprietor_individual());
+        assert!(!record(None, Some(SoleProprietorCode::Yes)).is_sole_proprietor_individual());
+    }
+    
+    #[test]
+    fn test_subpart_organization() {
+        let record = |entity_type, organization_subpart| NppesRecord {
+            entity_type,
+            organization_subpart,
+            ..Default::default()
+        };
+        
+        assert!(record(Some(EntityType::Organization), Some(SubpartCode::Yes)).is_subpart_organization());
+        assert!(!record(Some(EntityType::Organization), Some(SubpartCode::No)).is_subpart_organization());
+        assert!(!record(Some(EntityType::Organization), Some(SubpartCode::NotAnswered)).is_subpart_organization());
+        assert!(!record(Some(EntityType::Organization), None).is_subpart_organization());
+        assert!(!record(Some(EntityType::Individual), Some(SubpartCode::Yes)).is_subpart_organization());
+        assert!(!record(None, Some(SubpartCode::Yes)).is_subpart_organization());
+    }
+    
     #[test]
     fn test_entity_type() {
         assert_eq!(EntityType::from_code("1").unwrap(), EntityType::Individual);
         assert_eq!(EntityType::from_code("2").unwrap(), EntityType::Organization);
         assert!(EntityType::from_code("3").is_err());
     }
-} 
\ No newline at end of file
+    
+    #[test]
+    fn test_csv_error_source_is_preserved() {
+        let mut reader = csv::Reader::from_reader("a,b\n1,2,3\n".as_bytes());
+        let csv_err = reader.records().next().unwrap().unwrap_err();
+        let err = NppesError::from(csv_err);
+        
+        let source = std::error::Error::source(&err).expect("csv error kept as source");
+        assert!(source.downcast_ref::<csv::Error>().is_some());
+        // Like the other format errors, the message carries the csv error's text
+        assert!(err.to_string().contains("found record with 3 fields"), "{}", err);
+        
+        let mut reader = csv::Reader::from_reader(&b"a,b\n1,\xc9\n"[..]);
+        let utf8_err = reader.records().next().unwrap().unwrap_err();
+        let err = NppesError::from(utf8_err);
+        assert!(matches!(err, NppesError::Encoding { .. }));
+        let source = std::error::Error::source(&err).expect("csv error kept as source");
+        assert!(source.downcast_ref::<csv::Error>().is_some());
+    }
+    
+    #[cfg(feature = "dataframe")]
+    #[test]
+    fn test_polars_error_reports_dataframe_format() {
+        let err = NppesError::from(polars::error::PolarsError::ComputeError("length mismatch".into()));
+        assert!(matches!(err, NppesError::Export { format: ExportFormat::DataFrame, .. }));
+    }
+    
+    #[test]
+    fn test_partial_name_search_matches_other_names() {
+        let provider = NppesRecord {
+            npi: Npi::new("1234567893".to_string()).unwrap(),
+            entity_type: Some(EntityType::Individual),
+            provider_name: ProviderName {
+                first: Some("JANE".to_string()),
+                last: Some("SMITH".to_string()),
+                ..Default::default()
+            },
+            provider_other_name: ProviderName {
+                first: Some("JANE".to_string()),
+                last: Some("DOE".to_string()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        assert_eq!(provider.all_names(), vec!["JANE SMITH", "JANE DOE"]);
+        
+        let dataset = NppesDataset::new(vec![provider], None, None, None, None, None, None, None);
+        assert_eq!(cookbook::find_by_partial_name(&dataset, "doe").len(), 1);
+        assert!(cookbook::find_by_partial_name(&dataset, "brown").is_empty());
+    }
+}
@@ -0,0 +1,747 @@
+/*!
+ * Name matching strategies for entity resolution
+ *
+ * Defines the [`NameMatcher`] trait used to compare a query name with the names
+ * a provider carries, with built-in exact, substring, edit-distance and phonetic
+ * matchers. Pass any of them to [`NppesDataset::find_by_name_with`].
+ *
+ * For bulk entity resolution, [`NppesDataset::match_external`] links each row
+ * of a CSV of external records to its most likely NPIs.
+ */
+
+use std::path::Path;
+
+use crate::data_types::*;
+use crate::dataset::NppesDataset;
+use crate::{NppesError, Result};
+
+/// Compares a query name (`a`) against a candidate name (`b`)
+///
+/// Implementations are expected to ignore case. Scores run from `0.0` (no
+/// resemblance) to `1.0` (identical) and are used to rank matches.
+pub trait NameMatcher {
+    /// Whether the candidate name matches the query
+    fn matches(&self, a: &str, b: &str) -> bool;
+
+    /// Similarity between the query and the candidate, from 0.0 to 1.0
+    fn score(&self, a: &str, b: &str) -> f32;
+}
+
+/// Case-insensitive exact match, ignoring repeated whitespace
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ExactMatcher;
+
+impl NameMatcher for ExactMatcher {
+    fn matches(&self, a: &str, b: &str) -> bool {
+        normalize(a) == normalize(b)
+    }
+
+    fn score(&self, a: &str, b: &str) -> f32 {
+        if self.matches(a, b) { 1.0 } else { 0.0 }
+    }
+}
+
+/// Case-insensitive match when the candidate contains the query, ignoring repeated whitespace
+///
+/// Scores by how much of the candidate the query covers.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SubstringMatcher;
+
+impl NameMatcher for SubstringMatcher {
+    fn matches(&self, a: &str, b: &str) -> bool {
+        normalize(b).contains(&normalize(a))
+    }
+
+    fn score(&self, a: &str, b: &str) -> f32 {
+        let (a, b) = (normalize(a), normalize(b));
+        if b.is_empty() || !b.contains(&a) {
+            return 0.0;
+        }
+        a.chars().count() as f32 / b.chars().count() as f32
+    }
+}
+
+/// Edit-distance match tolerating typos
+#[derive(Debug, Clone, Copy)]
+pub struct LevenshteinMatcher {
+    /// Largest number of single-character edits still considered a match
+    pub max_distance: usize,
+}
+
+impl Default for LevenshteinMatcher {
+    fn default() -> Self {
+        Self { max_distance: 2 }
+    }
+}
+
+impl LevenshteinMatcher {
+    /// Create a matcher allowing up to `max_distance` edits
+    pub fn new(max_distance: usize) -> Self {
+        Self { max_distance }
+    }
+}
+
+impl NameMatcher for LevenshteinMatcher {
+    fn matches(&self, a: &str, b: &str) -> bool {
+        levenshtein(&normalize(a), &normalize(b)) <= self.max_distance
+    }
+
+    fn score(&self, a: &str, b: &str) -> f32 {
+        let (a, b) = (normalize(a), normalize(b));
+        let longest = a.chars().count().max(b.chars().count());
+        if longest == 0 {
+            return 1.0;
+        }
+        1.0 - levenshtein(&a, &b) as f32 / longest as f32
+    }
+}
+
+/// Word-order-insensitive match tolerating typos, using [`token_set_ratio`]
+///
+/// `"Smith, John"` matches `"JOHN SMITH"` exactly, and extra words on either
+/// side cost little.
+#[derive(Debug, Clone, Copy)]
+pub struct TokenSetMatcher {
+    /// Lowest score still considered a match
+    pub min_score: f32,
+}
+
+impl Default for TokenSetMatcher {
+    fn default() -> Self {
+        Self { min_score: 0.8 }
+    }
+}
+
+impl TokenSetMatcher {
+    /// Create a matcher accepting scores of at least `min_score`
+    pub fn new(min_score: f32) -> Self {
+        Self { min_score }
+    }
+}
+
+impl NameMatcher for TokenSetMatcher {
+    fn matches(&self, a: &str, b: &str) -> bool {
+        self.score(a, b) >= self.min_score
+    }
+
+    fn score(&self, a: &str, b: &str) -> f32 {
+        token_set_ratio(a, b) as f32
+    }
+}
+
+/// Phonetic match using Metaphone keys, word by word
+///
+/// Matches when every word of the query sounds like some word of the candidate,
+/// so `"JON SMYTH"` matches `"JOHN SMITH"`. Scores by the share of query words
+/// with a phonetic match.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MetaphoneMatcher;
+
+impl NameMatcher for MetaphoneMatcher {
+    fn matches(&self, a: &str, b: &str) -> bool {
+        self.score(a, b) == 1.0
+    }
+
+    fn score(&self, a: &str, b: &str) -> f32 {
+        let query: Vec<String> = a.split_whitespace().map(metaphone).filter(|k| !k.is_empty()).collect();
+        let candidate: Vec<String> = b.split_whitespace().map(metaphone).collect();
+        if query.is_empty() {
+            return 0.0;
+        }
+        let found = query.iter().filter(|key| candidate.contains(key)).count();
+        found as f32 / query.len() as f32
+    }
+}
+
+/// Similarity of two names' word sets, from 0.0 to 1.0
+///
+/// Both names are normalized with [`normalize_address_text`] (case and
+/// punctuation ignored) and split into unique words. The shared words, and the
+/// shared words followed by each name's remaining words, are compared by edit
+/// distance and the best pairing wins. Word order doesn't matter, a name whose
+/// words are all in the other scores 1.0, and typos lower the score gradually.
+pub fn token_set_ratio(a: &str, b: &str) -> f64 {
+    let words = |name: &str| -> std::collections::BTreeSet<String> {
+        normalize_address_text(name).split(' ').filter(|w| !w.is_empty()).map(str::to_string).collect()
+    };
+    let (a, b) = (words(a), words(b));
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    
+    let join = |words: Vec<&String>| words.into_iter().map(String::as_str).collect::<Vec<_>>().join(" ");
+    let common = join(a.intersection(&b).collect());
+    let with_common = |rest: String| match (common.is_empty(), rest.is_empty()) {
+        (true, _) => rest,
+        (false, true) => common.clone(),
+        (false, false) => format!("{} {}", common, rest),
+    };
+    let full_a = with_common(join(a.difference(&b).collect()));
+    let full_b = with_common(join(b.difference(&a).collect()));
+    
+    let ratio = |x: &str, y: &str| {
+        let longest = x.chars().count().max(y.chars().count());
+        1.0 - levenshtein(x, y) as f64 / longest as f64
+    };
+    let mut best = ratio(&full_a, &full_b);
+    if !common.is_empty() {
+        best = best.max(ratio(&common, &full_a)).max(ratio(&common, &full_b));
+    }
+    best
+}
+
+/// Lowercase and collapse whitespace
+fn normalize(name: &str) -> String {
+    name.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+}
+
+/// Number of single-character insertions, deletions and substitutions between two strings
+pub fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+
+    for (i, ca) in a.chars().enumerate() {
+        current[0] = i + 1;
+        for (j, &cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[b.len()]
+}
+
+/// Metaphone key for a single word (original Lawrence Philips rules)
+pub fn metaphone(word: &str) -> String {
+    let mut chars: Vec<char> = word.chars()
+        .filter(|c| c.is_ascii_alphabetic())
+        .map(|c| c.to_ascii_uppercase())
+        .collect();
+
+    // Initial letter exceptions
+    match chars.as_slice() {
+        ['A', 'E', ..] | ['G', 'N', ..] | ['K', 'N', ..] | ['P', 'N', ..] | ['W', 'R', ..] => {
+            chars.remove(0);
+        }
+        ['X', ..] => chars[0] = 'S',
+        ['W', 'H', ..] => {
+            chars.remove(1);
+        }
+        _ => {}
+    }
+
+    let is_vowel = |c: Option<char>| matches!(c, Some('A' | 'E' | 'I' | 'O' | 'U'));
+    let at = |i: usize| chars.get(i).copied();
+    let mut key = String::new();
+
+    for (i, &c) in chars.iter().enumerate() {
+        let prev = i.checked_sub(1).and_then(at);
+        let next = at(i + 1);
+        let after_next = at(i + 2);
+
+        // Doubled letters sound once, except C
+        if prev == Some(c) && c != 'C' {
+            continue;
+        }
+
+        match c {
+            'A' | 'E' | 'I' | 'O' | 'U' => {
+                if i == 0 {
+                    key.push(c);
+                }
+            }
+            'B' => {
+                if !(prev == Some('M') && next.is_none()) {
+                    key.push('B');
+                }
+            }
+            'C' => {
+                if next == Some('I') && after_next == Some('A') {
+                    key.push('X');
+                } else if next == Some('H') {
+                    key.push(if prev == Some('S') { 'K' } else { 'X' });
+                } else if matches!(next, Some('I' | 'E' | 'Y')) {
+                    if prev != Some('S') {
+                        key.push('S');
+                    }
+                } else {
+                    key.push('K');
+                }
+            }
+            'D' => {
+                if next == Some('G') && matches!(after_next, Some('E' | 'Y' | 'I')) {
+                    key.push('J');
+                } else {
+                    key.push('T');
+                }
+            }
+            'G' => {
+                let silent_gh = next == Some('H') && after_next.is_some() && !is_vowel(after_next);
+                let silent_gn = next == Some('N') && (after_next.is_none()
+                    || (after_next == Some('E') && at(i + 3) == Some('D') && at(i + 4).is_none()));
+                let soft = matches!(next, Some('I' | 'E' | 'Y'));
+                if prev == Some('D') && soft {
+                    // Already sounded as J by the preceding D
+                } else if silent_gh || silent_gn {
+                    // Silent
+                } else if soft {
+                    key.push('J');
+                } else {
+                    key.push('K');
+                }
+            }
+            'H' => {
+                let after_modifier = matches!(prev, Some('C' | 'S' | 'P' | 'T' | 'G'));
+                if !after_modifier && (!is_vowel(prev) || is_vowel(next)) {
+                    key.push('H');
+                }
+            }
+            'K' => {
+                if prev != Some('C') {
+                    key.push('K');
+                }
+            }
+            'P' => key.push(if next == Some('H') { 'F' } else { 'P' }),
+            'Q' => key.push('K'),
+            'S' => {
+                if next == Some('H') || (next == Some('I') && matches!(after_next, Some('O' | 'A'))) {
+                    key.push('X');
+                } else {
+                    key.push('S');
+                }
+            }
+            'T' => {
+                if next == Some('I') && matches!(after_next, Some('O' | 'A')) {
+                    key.push('X');
+                } else if next == Some('H') {
+                    key.push('0');
+                } else if !(next == Some('C') && after_next == Some('H')) {
+                    key.push('T');
+                }
+            }
+            'V' => key.push('F'),
+            'W' | 'Y' => {
+                if is_vowel(next) {
+                    key.push(c);
+                }
+            }
+            'X' => key.push_str("KS"),
+            'Z' => key.push('S'),
+            other => key.push(other),
+        }
+    }
+    key
+}
+
+/// Settings for [`NppesDataset::match_external`]
+///
+/// Column names are matched case-insensitively against the CSV header. The
+/// state and specialty columns are optional: when configured but absent from
+/// the file, or blank in a row, they simply don't contribute to the score.
+#[derive(Debug, Clone)]
+pub struct MatchConfig {
+    /// Column holding the person or organization name, in any word order
+    pub name_column: String,
+    /// Column holding a two-letter state code
+    pub state_column: Option<String>,
+    /// Column holding a taxonomy code or specialty name
+    pub specialty_column: Option<String>,
+    /// Weight of name similarity in the confidence score
+    pub name_weight: f32,
+    /// Weight of state agreement in the confidence score
+    pub state_weight: f32,
+    /// Weight of specialty agreement in the confidence score
+    pub specialty_weight: f32,
+    /// Smallest name similarity for a provider to be considered at all
+    pub min_name_score: f32,
+    /// Smallest confidence for a candidate to be reported
+    pub min_confidence: f32,
+    /// Largest number of candidates reported per row
+    pub max_candidates: usize,
+    /// Only consider providers in the row's state, when it has one
+    pub require_state: bool,
+}
+
+impl Default for MatchConfig {
+    fn default() -> Self {
+        Self {
+            name_column: "name".to_string(),
+            state_column: Some("state".to_string()),
+            specialty_column: Some("specialty".to_string()),
+            name_weight: 0.6,
+            state_weight: 0.25,
+            specialty_weight: 0.15,
+            min_name_score: 0.75,
+            min_confidence: 0.7,
+            max_candidates: 3,
+            require_state: false,
+        }
+    }
+}
+
+impl MatchConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the name column
+    pub fn with_name_column(mut self, column: &str) -> Self {
+        self.name_column = column.to_string();
+        self
+    }
+
+    /// Set the state column, or `None` to ignore state
+    pub fn with_state_column(mut self, column: Option<&str>) -> Self {
+        self.state_column = column.map(str::to_string);
+        self
+    }
+
+    /// Set the specialty column, or `None` to ignore specialty
+    pub fn with_specialty_column(mut self, column: Option<&str>) -> Self {
+        self.specialty_column = column.map(str::to_string);
+        self
+    }
+
+    /// Set the smallest confidence reported
+    pub fn with_min_confidence(mut self, min_confidence: f32) -> Self {
+        self.min_confidence = min_confidence;
+        self
+    }
+
+    /// Set the number of candidates reported per row
+    pub fn with_max_candidates(mut self, max_candidates: usize) -> Self {
+        self.max_candidates = max_candidates;
+        self
+    }
+
+    /// Restrict candidates to the row's state
+    pub fn with_require_state(mut self, require_state: bool) -> Self {
+        self.require_state = require_state;
+        self
+    }
+}
+
+/// Candidate NPIs for one row of an external file
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExternalMatch {
+    /// 1-based data row number, not counting the header
+    pub row: usize,
+    /// Name as given in the file
+    pub name: String,
+    /// Candidates above the confidence threshold, best first
+    pub candidates: Vec<MatchCandidate>,
+}
+
+impl ExternalMatch {
+    /// The highest-confidence candidate, if any
+    pub fn best(&self) -> Option<&MatchCandidate> {
+        self.candidates.first()
+    }
+}
+
+/// A provider proposed as the match for an external record
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatchCandidate {
+    pub npi: Npi,
+    /// The provider name that matched best
+    pub matched_name: String,
+    /// Weighted confidence from 0.0 to 1.0
+    pub confidence: f32,
+    /// Name similarity from 0.0 to 1.0
+    pub name_score: f32,
+    /// Whether the provider's mailing state equals the row's state
+    pub state_match: bool,
+    /// Whether one of the provider's taxonomies agrees with the row's specialty
+    pub specialty_match: bool,
+}
+
+/// Uppercase words of a name in sorted order, so "Smith, John" equals "JOHN SMITH"
+fn token_sort(name: &str) -> String {
+    let mut words: Vec<String> = name
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_uppercase)
+        .collect();
+    words.sort();
+    words.join(" ")
+}
+
+/// An external record read from the input CSV
+struct ExternalRecord {
+    row: usize,
+    name: String,
+    state: Option<String>,
+    specialty: Option<String>,
+}
+
+impl NppesDataset {
+    /// Link each row of a CSV of external records to its most likely NPIs
+    ///
+    /// Every row is compared with every provider (or only those in the row's
+    /// state with [`MatchConfig::require_state`]). Names are compared ignoring
+    /// word order and punctuation, by edit distance. A candidate's confidence
+    /// is the weighted average of name similarity, state agreement and
+    /// specialty agreement, counting only the fields the row fills in. A
+    /// specialty agrees when it equals one of the provider's taxonomy codes or
+    /// appears in the taxonomy's display name, classification or specialization.
+    ///
+    /// Returns one [`ExternalMatch`] per row, in file order; rows without a
+    /// convincing match have no candidates. Rows are matched in parallel with
+    /// the `parallel` feature.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use nppes::prelude::*;
+    /// # use nppes::matching::MatchConfig;
+    /// # let dataset = NppesDataset::load_standard("./data")?;
+    /// let config = MatchConfig::new().with_name_column("full_name").with_require_state(true);
+    /// for row in dataset.match_external("roster.csv".as_ref(), config)? {
+    ///     match row.best() {
+    ///         Some(best) => println!("{} -> {} ({:.2})", row.name, best.npi, best.confidence),
+    ///         None => println!("{} -> no match", row.name),
+    ///     }
+    /// }
+    /// # Ok::<(), nppes::NppesError>(())
+    /// ```
+    pub fn match_external(&self, path: &Path, config: MatchConfig) -> Result<Vec<ExternalMatch>> {
+        if !path.exists() {
+            return Err(NppesError::file_not_found_with_suggestion(path.to_path_buf()));
+        }
+        let mut reader = csv::Reader::from_path(path)?;
+        let headers = reader.headers()?.clone();
+        let column = |name: &str| headers.iter().position(|header| header.trim().eq_ignore_ascii_case(name));
+        let name_column = column(&config.name_column).ok_or_else(|| NppesError::Configuration {
+            message: format!("Column '{}' not found in {}", config.name_column, path.display()),
+            suggestion: Some("Set MatchConfig::name_column to the header of the name column".to_string()),
+        })?;
+        let state_column = config.state_column.as_deref().and_then(column);
+        let specialty_column = config.specialty_column.as_deref().and_then(column);
+
+        let mut records = Vec::new();
+        for (i, row) in reader.records().enumerate() {
+            let row = row?;
+            let field = |index: Option<usize>| {
+                index.and_then(|index| row.get(index))
+                    .map(str::trim)
+                    .filter(|value| !value.is_empty())
+                    .map(str::to_string)
+            };
+            records.push(ExternalRecord {
+                row: i + 1,
+                name: field(Some(name_column)).unwrap_or_default(),
+                state: field(state_column).map(|state| state.to_uppercase()),
+                specialty: field(specialty_column),
+            });
+        }
+
+        #[cfg(feature = "parallel")]
+        let matches = {
+            use rayon::prelude::*;
+            records.par_iter().map(|record| self.match_record(record, &config)).collect()
+        };
+
+        #[cfg(not(feature = "parallel"))]
+        let matches = records.iter().map(|record| self.match_record(record, &config)).collect();
+
+        Ok(matches)
+    }
+
+    /// Rank the candidates for one external record
+    fn match_record(&self, record: &ExternalRecord, config: &MatchConfig) -> ExternalMatch {
+        let query = token_sort(&record.name);
+        let specialty = record.specialty.as_deref().map(str::to_lowercase);
+        let matcher = LevenshteinMatcher::default();
+
+        let pool: Vec<&NppesRecord> = match &record.state {
+            Some(state) if config.require_state => self.get_by_state(state),
+            _ => self.providers.iter().collect(),
+        };
+
+        let mut candidates: Vec<MatchCandidate> = if query.is_empty() {
+            Vec::new()
+        } else {
+            pool.into_iter()
+                .filter_map(|provider| {
+                    let (matched_name, name_score) = provider.all_names().into_iter()
+                        .map(|name| {
+                            let score = matcher.score(&query, &token_sort(&name));
+                            (name, score)
+                        })
+                        .max_by(|a, b| a.1.total_cmp(&b.1))
+                        .filter(|(_, score)| *score >= config.min_name_score)?;
+
+                    let state_match = record.state.as_deref().is_some_and(|state| {
+                        provider.mailing_address.state.as_ref().is_some_and(|s| s.as_code() == state)
+                    });
+                    let specialty_match = specialty.as_deref().is_some_and(|wanted| {
+                        provider.taxonomy_codes.iter().any(|taxonomy| {
+                            taxonomy.code.eq_ignore_ascii_case(wanted)
+                                || self.get_taxonomy_description(&taxonomy.code).is_some_and(|reference| {
+                                    [&reference.display_name, &reference.classification, &reference.specialization]
+                                        .into_iter()
+                                        .flatten()
+                                        .any(|label| label.to_lowercase().contains(wanted))
+                                })
+                        })
+                    });
+
+                    let mut weighted = config.name_weight * name_score;
+                    let mut total = config.name_weight;
+                    if record.state.is_some() {
+                        weighted += if state_match { config.state_weight } else { 0.0 };
+                        total += config.state_weight;
+                    }
+                    if specialty.is_some() {
+                        weighted += if specialty_match { config.specialty_weight } else { 0.0 };
+                        total += config.specialty_weight;
+                    }
+                    let confidence = if total > 0.0 { weighted / total } else { 0.0 };
+
+                    (confidence >= config.min_confidence).then(|| MatchCandidate {
+                        npi: provider.npi.clone(),
+                        matched_name,
+                        confidence,
+                        name_score,
+                        state_match,
+                        specialty_match,
+                    })
+                })
+                .collect()
+        };
+
+        candidates.sort_by(|a, b| b.confidence.total_cmp(&a.confidence).then_with(|| a.npi.as_str().cmp(b.npi.as_str())));
+        candidates.truncate(config.max_candidates);
+
+        ExternalMatch {
+            row: record.row,
+            name: record.name.clone(),
+            candidates,
+        }
+    }
+
+    /// Find providers with any name matching the query under the given strategy
+    ///
+    /// Every name a provider carries is compared (see [`NppesRecord::all_names`]).
+    /// Results are ranked by their best [`score`](NameMatcher::score), highest
+    /// first, with ties kept in dataset order.
+    pub fn find_by_name_with(&self, query: &str, matcher: &impl NameMatcher) -> Vec<&NppesRecord> {
+        let mut matches: Vec<(&NppesRecord, f32)> = self.providers.iter()
+            .filter_map(|provider| {
+                provider.all_names().iter()
+                    .filter(|name| matcher.matches(query, name))
+                    .map(|name| matcher.score(query, name))
+                    .reduce(f32::max)
+                    .map(|score| (provider, score))
+            })
+            .collect();
+        matches.sort_by(|a, b| b.1.total_cmp(&a.1));
+        matches.into_iter().map(|(provider, _)| provider).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_matchers() {
+        assert!(ExactMatcher.matches("john  smith", "JOHN SMITH"));
+        assert!(!ExactMatcher.matches("JOHN", "JOHN SMITH"));
+
+        assert!(SubstringMatcher.matches("smith", "JOHN SMITH"));
+        assert!(SubstringMatcher.matches("john smith", "JOHN  SMITH"));
+        assert!(SubstringMatcher.score("john smith", "JOHN  SMITH") > 0.0);
+        assert!(SubstringMatcher.score("JOHN SMITH", "JOHN SMITH") > SubstringMatcher.score("SMITH", "JOHN SMITH"));
+
+        assert_eq!(levenshtein("kitten", "sitting"), 3);
+        assert!(LevenshteinMatcher::new(1).matches("SMYTH", "SMITH"));
+        assert!(!LevenshteinMatcher::new(1).matches("SMYTHE", "SMITH"));
+
+        assert_eq!(metaphone("SMITH"), "SM0");
+        assert_eq!(metaphone("KNIGHT"), "NT");
+        assert_eq!(metaphone("PHILLIPS"), "FLPS");
+        assert!(MetaphoneMatcher.matches("JON SMYTH", "JOHN SMITH"));
+        assert!(!MetaphoneMatcher.matches("JOHN BROWN", "JOHN SMITH"));
+
+        assert_eq!(token_set_ratio("Smith, John", "JOHN SMITH"), 1.0);
+        assert_eq!(token_set_ratio("JOHN SMITH", "JOHN A SMITH"), 1.0);
+        assert!(token_set_ratio("JONH SMITH", "JOHN SMITH") > token_set_ratio("JANE DOE", "JOHN SMITH"));
+        assert_eq!(token_set_ratio("", "JOHN SMITH"), 0.0);
+        assert!(TokenSetMatcher::default().matches("SMITH JOHN", "JOHN SMITH"));
+        assert!(!TokenSetMatcher::default().matches("JOHN BROWN", "JANE SMITH"));
+    }
+
+    #[test]
+    fn test_find_by_name_with_ranks_matches() {
+        let person = |npi: &str, last: &str| NppesRecord {
+            npi: Npi::new(npi.to_string()).unwrap(),
+            entity_type: Some(EntityType::Individual),
+            provider_name: ProviderName { last: Some(last.to_string()), ..Default::default() },
+            ..Default::default()
+        };
+        let dataset = NppesDataset::new(
+            vec![person("1234567893", "SMITHERS"), person("1245319599", "SMITH"), person("1003000126", "JONES")],
+            None, None, None, None, None, None, None,
+        );
+
+        let found: Vec<&str> = dataset.find_by_name_with("smith", &SubstringMatcher)
+            .iter()
+            .map(|p| p.npi.as_str())
+            .collect();
+        assert_eq!(found, ["1245319599", "1234567893"]);
+        assert_eq!(dataset.find_by_name_with("SMYTH", &MetaphoneMatcher).len(), 1);
+    }
+
+    #[test]
+    fn test_match_external_scores_name_state_and_specialty() {
+        let person = |npi: &str, first: &str, last: &str, state: StateCode, taxonomy: &str| NppesRecord {
+            npi: Npi::new(npi.to_string()).unwrap(),
+            entity_type: Some(EntityType::Individual),
+            provider_name: ProviderName { first: Some(first.to_string()), last: Some(last.to_string()), ..Default::default() },
+            mailing_address: Address { state: Some(state), ..Default::default() },
+            taxonomy_codes: vec![TaxonomyCode { code: taxonomy.to_string(), ..Default::default() }],
+            ..Default::default()
+        };
+        let family_medicine = TaxonomyReference {
+            code: "207Q00000X".to_string(),
+            display_name: Some("Family Medicine Physician".to_string()),
+            ..Default::default()
+        };
+        let dataset = NppesDataset::new(
+            vec![
+                person("1234567893", "JOHN", "SMITH", StateCode::CA, "207Q00000X"),
+                person("1245319599", "JOHN", "SMITH", StateCode::NY, "363L00000X"),
+                person("1003000126", "MARY", "JONES", StateCode::TX, "363L00000X"),
+            ],
+            Some([("207Q00000X".to_string(), family_medicine)].into_iter().collect()),
+            None, None, None, None, None, None,
+        );
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("roster.csv");
+        std::fs::write(&path, "Name,State,Specialty\n\"Smith, John\",ca,family medicine\nJon Smyth,NY,\nNobody Here,TX,\n").unwrap();
+
+        let matches = dataset.match_external(&path, MatchConfig::new()).unwrap();
+        assert_eq!(matches.len(), 3);
+
+        let first = &matches[0];
+        assert_eq!(first.row, 1);
+        let npis: Vec<&str> = first.candidates.iter().map(|c| c.npi.as_str()).collect();
+        assert_eq!(npis, ["1234567893"]);
+        let best = first.best().unwrap();
+        assert!(best.state_match && best.specialty_match);
+        assert_eq!(best.confidence, 1.0);
+
+        let second = matches[1].best().unwrap();
+        assert_eq!(second.npi.as_str(), "1245319599");
+        assert!(second.name_score < 1.0 && second.state_match);
+        assert!(matches[2].candidates.is_empty());
+
+        // Without state and specialty, both namesakes are equally likely
+        let names_only = MatchConfig::new().with_state_column(None).with_specialty_column(None);
+        assert_eq!(dataset.match_external(&path, names_only).unwrap()[0].candidates.len(), 2);
+
+        let missing_column = MatchConfig::new().with_name_column("full_name");
+        assert!(dataset.match_external(&path, missing_column).is_err());
+    }
+}
@@ -8,15 +8,21 @@
 
 use std::path::Path;
 use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::cell::Cell;
+use std::rc::Rc;
 use std::time::{Duration, Instant};
-use csv::ReaderBuilder;
+use csv::{ReaderBuilder, Terminator};
 use chrono::NaiveDate;
 
 #[cfg(feature = "progress")]
 use indicatif::{ProgressBar, ProgressStyle};
+#[cfg(feature = "compression")]
+use flate2::read::MultiGzDecoder;
 
 use crate::{
     Result, NppesError, ErrorContext,
+    config::{NppesConfig, ValidationLevel},
     data_types::*,
     schema::*,
     constants::*,
@@ -42,7 +48,7 @@ pub struct ProgressInfo {
 /// Memory usage estimation
 #[derive(Debug, Clone)]
 pub struct MemoryEstimate {
-    /// Size of the source file in bytes
+    /// Size of the source file in bytes, compressed if the file is gzipped
     pub file_size: u64,
     /// Estimated number of records
     pub estimated_records: u64,
@@ -50,14 +56,126 @@ pub struct MemoryEstimate {
     pub estimated_memory_bytes: usize,
     /// Human-readable memory estimate
     pub estimated_memory_human: String,
+    /// Estimated in-memory size of one parsed record
+    pub bytes_per_record: usize,
+    /// Number of records sampled from the start of the file
+    pub sampled_records: u64,
+    /// Average length of a sampled record in the file, in bytes
+    pub average_record_bytes: u64,
+    /// Average number of non-empty fields in a sampled record
+    pub average_populated_fields: f64,
+    /// Multiplier applied to each record's text to account for heap overhead
+    pub memory_multiplier: f64,
+    /// Inline size of the parsed record type
+    record_size: usize,
+}
+
+impl MemoryEstimate {
+    /// Recompute the estimate with a different in-memory multiplier for record text
+    /// 
+    /// The default comes from the sampled field counts: each non-empty field is
+    /// stored in its own heap allocation, which costs more than its text.
+    pub fn with_memory_multiplier(mut self, multiplier: f64) -> Self {
+        self.memory_multiplier = multiplier;
+        self.bytes_per_record = self.record_size + (self.average_record_bytes as f64 * multiplier) as usize;
+        self.estimated_memory_bytes = (self.estimated_records as usize).saturating_mul(self.bytes_per_record);
+        self.estimated_memory_human = format_bytes(self.estimated_memory_bytes);
+        self
+    }
+}
+
+/// The kind of NPPES file, which determines the in-memory record type
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NppesFileKind {
+    /// Main provider file (`npidata_pfile`)
+    Main,
+    /// Other names reference file (`othername_pfile`)
+    OtherName,
+    /// Practice locations reference file (`pl_pfile`)
+    PracticeLocation,
+    /// Endpoints reference file (`endpoint_pfile`)
+    Endpoint,
+    /// NUCC taxonomy reference file
+    Taxonomy,
+}
+
+impl NppesFileKind {
+    /// Guess the file kind from the number of header columns, defaulting to `Main`
+    pub fn from_column_count(columns: usize) -> Self {
+        if columns == OtherNameSchema::column_count() {
+            NppesFileKind::OtherName
+        } else if columns == PracticeLocationSchema::column_count() {
+            NppesFileKind::PracticeLocation
+        } else if columns == EndpointSchema::column_count() {
+            NppesFileKind::Endpoint
+        } else if columns == TaxonomySchema::column_count() {
+            NppesFileKind::Taxonomy
+        } else {
+            NppesFileKind::Main
+        }
+    }
+    
+    /// Inline size of the parsed record type, before its text is counted
+    fn record_size(&self) -> usize {
+        match self {
+            NppesFileKind::Main => size_of::<NppesRecord>(),
+            NppesFileKind::OtherName => size_of::<OtherNameRecord>(),
+            NppesFileKind::PracticeLocation => size_of::<PracticeLocationRecord>(),
+            NppesFileKind::Endpoint => size_of::<EndpointRecord>(),
+            NppesFileKind::Taxonomy => size_of::<TaxonomyReference>(),
+        }
+    }
+}
+
+/// Summary of a main data load
+#[derive(Debug, Clone, Default)]
+pub struct LoadReport {
+    /// Number of records successfully parsed
+    pub records_loaded: usize,
+    /// Number of records skipped as invalid
+    pub invalid_records: usize,
+    /// Number of records whose blank entity type code was inferred
+    pub entity_types_inferred: usize,
+    /// Byte offset just past the last record read
+    pub end_offset: u64,
+    /// Original CSV rows, parallel to the loaded records
+    /// 
+    /// Empty unless [`NppesReader::with_raw_capture`] is enabled.
+    pub raw_records: Vec<csv::StringRecord>,
+}
+
+/// Text encoding of NPPES input files
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TextEncoding {
+    /// UTF-8 (the format CMS publishes)
+    #[default]
+    Utf8,
+    /// ISO-8859-1, for files re-saved by legacy tools
+    Latin1,
 }
 
 /// Enhanced NPPES data reader with CSV parsing capabilities
 pub struct NppesReader {
     /// Whether to validate CSV headers against expected schema
     validate_headers: bool,
+    /// Text encoding of input files
+    encoding: TextEncoding,
+    /// Whether to infer blank entity type codes from the populated name fields
+    infer_entity_type: bool,
     /// Whether to skip invalid records (true) or fail on first error (false)
     skip_invalid_records: bool,
+    /// Which main record fields are checked while parsing
+    validation_level: ValidationLevel,
+    /// `chrono` formats tried in order when parsing dates
+    date_formats: Vec<String>,
+    /// Whether to keep each main record's original CSV row in the [`LoadReport`]
+    capture_raw: bool,
+    /// Whether to parse main records on the rayon thread pool (`parallel` feature)
+    parallel_parsing: bool,
+    /// Whether to skip the taxonomy and other identifier columns of main records
+    minimal_parse: bool,
+    /// Whether main loads fail up front when the records won't fit in memory
+    memory_check: bool,
     /// Progress callback function
     #[cfg(feature = "progress")]
     progress_callback: Option<Box<dyn Fn(ProgressInfo) + Send + Sync>>,
@@ -74,10 +192,21 @@ impl Default for NppesReader {
 
 impl NppesReader {
     /// Create a new NPPES reader with default settings
+    /// 
+    /// The validation level comes from the global configuration (see
+    /// [`crate::config::global_config`]).
     pub fn new() -> Self {
         Self {
             validate_headers: true,
+            encoding: TextEncoding::default(),
+            infer_entity_type: false,
             skip_invalid_records: false,
+            validation_level: crate::config::global_config().validation_level,
+            date_formats: vec![DEFAULT_DATE_FORMAT.to_string()],
+            capture_raw: false,
+            parallel_parsing: true,
+            minimal_parse: false,
+            memory_check: true,
             #[cfg(feature = "progress")]
             progress_callback: None,
             #[cfg(feature = "progress")]
@@ -91,12 +220,107 @@ impl NppesReader {
         self
     }
     
+    /// Set the text encoding of input files
+    pub fn with_encoding(mut self, encoding: TextEncoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+    
+    /// Enable or disable inference of blank entity type codes
+    /// 
+    /// When enabled, a record with an empty `Entity Type Code` is treated as an
+    /// organization if it has a legal business name, or as an individual if it
+    /// has a last name. Inferred records are counted in [`LoadReport`].
+    pub fn with_entity_type_inference(mut self, infer: bool) -> Self {
+        self.infer_entity_type = infer;
+        self
+    }
+    
     /// Enable or disable skipping invalid records
     pub fn with_skip_invalid_records(mut self, skip: bool) -> Self {
         self.skip_invalid_records = skip;
         self
     }
     
+    /// Set how thoroughly main record fields are validated (default: [`ValidationLevel::Standard`])
+    /// 
+    /// Records failing validation are errors, or skipped with
+    /// [`with_skip_invalid_records`](Self::with_skip_invalid_records). Below the
+    /// level that checks a field, a malformed value is dropped instead: an
+    /// unparseable date becomes `None`, for example. The NPI can't be dropped, so
+    /// at [`ValidationLevel::None`] records with an invalid NPI are skipped.
+    /// 
+    /// Note that the default, Standard, rejects unknown entity type codes, which
+    /// earlier versions silently left as `None`. Use [`ValidationLevel::None`]
+    /// for the old behavior.
+    pub fn with_validation_level(mut self, level: ValidationLevel) -> Self {
+        self.validation_level = level;
+        self
+    }
+    
+    /// Apply the validation level from a configuration
+    pub fn with_config(self, config: &NppesConfig) -> Self {
+        self.with_validation_level(config.validation_level)
+    }
+    
+    /// Set the date formats to accept, tried in order (default: `%m/%d/%Y`)
+    /// 
+    /// Formats use `chrono` syntax, so `"%Y-%m-%d"` reads ISO dates and
+    /// `"%m-%d-%Y"` reads dashed US dates. List the NPPES format as well to
+    /// accept files mixing both. An empty list restores the default.
+    pub fn with_date_formats(mut self, formats: Vec<String>) -> Self {
+        self.date_formats = if formats.is_empty() {
+            vec![DEFAULT_DATE_FORMAT.to_string()]
+        } else {
+            formats
+        };
+        self
+    }
+    
+    /// Enable or disable capturing the original CSV row of each main record
+    /// 
+    /// Captured rows are returned in [`LoadReport::raw_records`] for diagnosing
+    /// field mapping problems. This roughly doubles memory use, so leave it off
+    /// for production loads.
+    pub fn with_raw_capture(mut self, capture: bool) -> Self {
+        self.capture_raw = capture;
+        self
+    }
+    
+    /// Enable or disable parsing main records in parallel
+    /// 
+    /// On by default. Rows are still read sequentially, in chunks, and records are
+    /// returned in file order either way. Without the `parallel` feature this
+    /// setting has no effect and parsing is always sequential.
+    pub fn with_parallel_parsing(mut self, parallel: bool) -> Self {
+        self.parallel_parsing = parallel;
+        self
+    }
+    
+    /// Enable or disable skipping taxonomy and other identifier columns
+    /// 
+    /// Off by default. When on, `taxonomy_codes` and `other_identifiers` are
+    /// left empty on every main record, which saves parsing the 60 taxonomy and
+    /// 200 identifier columns when only names, addresses and dates are needed.
+    /// Taxonomy queries, specialty filters and analytics on the loaded dataset
+    /// will then find nothing.
+    pub fn with_minimal_parse(mut self, minimal: bool) -> Self {
+        self.minimal_parse = minimal;
+        self
+    }
+    
+    /// Enable or disable the available memory check before loading main data
+    /// 
+    /// On by default. Main loads estimate the memory the records they will read
+    /// need (only the first `max_records` for bounded loads) and fail with
+    /// [`NppesError::Memory`] if that plus 1GB of headroom exceeds the memory
+    /// available. Turn it off to load anyway, for example when swap is
+    /// available or the estimate is too pessimistic.
+    pub fn with_memory_check(mut self, check: bool) -> Self {
+        self.memory_check = check;
+        self
+    }
+    
     #[cfg(feature = "progress")]
     /// Set a progress callback function
     pub fn with_progress<F>(mut self, callback: F) -> Self 
@@ -114,184 +338,384 @@ impl NppesReader {
         self
     }
     
-    /// Estimate memory usage for a file
+    /// Whether loads draw a progress bar or call a progress callback
+    /// 
+    /// Neither says which file it reports on, so files loaded concurrently
+    /// would interleave their progress.
+    #[cfg(feature = "parallel")]
+    pub(crate) fn reports_progress(&self) -> bool {
+        #[cfg(feature = "progress")]
+        return self.show_progress_bar || self.progress_callback.is_some();
+        
+        #[cfg(not(feature = "progress"))]
+        false
+    }
+    
+    /// Estimate memory usage for a file, detecting its kind from the header
     pub fn estimate_memory_usage<P: AsRef<Path>>(path: P) -> Result<MemoryEstimate> {
         let path = path.as_ref();
-        let metadata = std::fs::metadata(path)?;
-        let file_size = metadata.len();
+        let mut reader = csv_reader(open_input(path, Rc::default())?);
+        let kind = NppesFileKind::from_column_count(reader.byte_headers()?.len());
+        Self::estimate_memory_usage_for(path, kind)
+    }
+    
+    /// Estimate memory usage for a file of a known kind
+    /// 
+    /// Samples the first records of the file to find the average record length,
+    /// which gives the record count and the text each record carries, and the
+    /// average number of populated fields, which sets the default
+    /// [`memory_multiplier`](MemoryEstimate::memory_multiplier) for that text. The
+    /// scaled text is added to the inline size of the record type the file parses
+    /// into. Use [`MemoryEstimate::with_memory_multiplier`] to override the multiplier.
+    /// 
+    /// For a gzip-compressed file the record count is extrapolated from the
+    /// compressed size, so it underestimates the records (and memory) by roughly
+    /// the compression ratio.
+    pub fn estimate_memory_usage_for<P: AsRef<Path>>(path: P, kind: NppesFileKind) -> Result<MemoryEstimate> {
+        let path = path.as_ref();
+        let file_size = std::fs::metadata(path)?.len();
+        let mut reader = csv_reader(open_input(path, Rc::default())?);
+        
+        reader.byte_headers()?;
+        let header_bytes = reader.position().byte();
+        let mut record = csv::ByteRecord::new();
+        let mut sampled_records = 0u64;
+        let mut populated_fields = 0u64;
+        // A malformed row ends the sample early rather than failing the estimate
+        while sampled_records < MEMORY_ESTIMATE_SAMPLE_LINES && reader.read_byte_record(&mut record).unwrap_or(false) {
+            sampled_records += 1;
+            populated_fields += record.iter().filter(|field| !field.trim_ascii().is_empty()).count() as u64;
+        }
+        let sampled_bytes = reader.position().byte().saturating_sub(header_bytes);
         
-        // Estimate based on typical compression ratio and record size
-        // NPPES records average about 2KB in CSV, 500 bytes in memory
-        let estimated_records = file_size / 2000;
-        let estimated_memory_bytes = (estimated_records as usize) * 500;
+        let data_bytes = file_size.saturating_sub(header_bytes);
+        let average_record_bytes = sampled_bytes.checked_div(sampled_records).unwrap_or(0);
+        let estimated_records = if sampled_bytes >= data_bytes {
+            sampled_records
+        } else {
+            data_bytes / average_record_bytes.max(1)
+        };
         
-        let estimated_memory_human = format_bytes(estimated_memory_bytes);
+        let average_populated_fields = if sampled_records == 0 {
+            0.0
+        } else {
+            populated_fields as f64 / sampled_records as f64
+        };
+        let memory_multiplier = if average_record_bytes == 0 {
+            1.0
+        } else {
+            1.0 + average_populated_fields * HEAP_ALLOCATION_OVERHEAD as f64 / average_record_bytes as f64
+        };
         
         Ok(MemoryEstimate {
             file_size,
             estimated_records,
-            estimated_memory_bytes,
-            estimated_memory_human,
-        })
+            estimated_memory_bytes: 0,
+            estimated_memory_human: String::new(),
+            bytes_per_record: 0,
+            sampled_records,
+            average_record_bytes,
+            average_populated_fields,
+            memory_multiplier,
+            record_size: kind.record_size(),
+        }
+        .with_memory_multiplier(memory_multiplier))
     }
     
     /// Check if there's enough memory to load a file
+    /// 
+    /// Fails with [`NppesError::Memory`] when the estimated footprint plus 1GB of
+    /// headroom exceeds the memory currently available. Available memory is read
+    /// from `/proc/meminfo` on Linux, `vm_stat` on macOS and
+    /// `GlobalMemoryStatusEx` on Windows; elsewhere, or if detection fails, the
+    /// check passes.
     pub fn check_memory_availability<P: AsRef<Path>>(path: P) -> Result<bool> {
         let estimate = Self::estimate_memory_usage(path)?;
-        
-        // Get available system memory (platform-specific)
-        #[cfg(target_os = "windows")]
-        let available_memory = get_available_memory_windows();
-        
-        #[cfg(not(target_os = "windows"))]
-        let available_memory = get_available_memory_unix();
-        
-        if let Some(available) = available_memory {
-            // Leave at least 1GB free
-            let buffer = 1_073_741_824;
-            if estimate.estimated_memory_bytes + buffer > available {
-                return Err(NppesError::insufficient_memory(
-                    estimate.estimated_memory_bytes,
-                    Some(available)
-                ));
-            }
-        }
-        
+        ensure_memory_fits(estimate.estimated_memory_bytes, get_available_memory())?;
         Ok(true)
     }
     
     /// Load the main NPPES provider data from CSV file
     pub fn load_main_data<P: AsRef<Path>>(&self, path: P) -> Result<Vec<NppesRecord>> {
+        let (records, _) = self.load_main_data_from_offset(path, 0, None)?;
+        Ok(records)
+    }
+    
+    /// Load the main NPPES provider data along with a [`LoadReport`] summarizing the load
+    pub fn load_main_data_with_report<P: AsRef<Path>>(&self, path: P) -> Result<(Vec<NppesRecord>, LoadReport)> {
+        self.load_main_records(path.as_ref(), 0, None)
+    }
+    
+    /// Stream the main NPPES provider data one record at a time
+    /// 
+    /// Unlike [`load_main_data`](Self::load_main_data), records are parsed lazily and
+    /// never collected, so memory use stays flat regardless of file size. Filter or
+    /// aggregate as you go and drop the records you don't need.
+    /// 
+    /// The file is opened and its header row read and validated eagerly, so a missing
+    /// file or malformed header is reported by this call rather than by the iterator.
+    /// Each record is then parsed as it is pulled. With
+    /// [`with_skip_invalid_records`](Self::with_skip_invalid_records) enabled,
+    /// unparseable rows are skipped with a warning; otherwise they are yielded as `Err`
+    /// items and iteration can continue past them. Progress reporting and raw record
+    /// capture don't apply to streaming.
+    /// 
+    /// # Example
+    /// ```no_run
+    /// # use nppes::reader::NppesReader;
+    /// # use nppes::data_types::StateCode;
+    /// let mut in_california = 0;
+    /// for record in NppesReader::new().stream_main_data("npidata.csv")? {
+    ///     if record?.mailing_address.state == Some(StateCode::CA) {
+    ///         in_california += 1;
+    ///     }
+    /// }
+    /// # Ok::<(), nppes::NppesError>(())
+    /// ```
+    pub fn stream_main_data<P: AsRef<Path>>(&self, path: P) -> Result<impl Iterator<Item = Result<NppesRecord>> + '_> {
+        let path = path.as_ref().to_path_buf();
+        if !path.exists() {
+            return Err(NppesError::file_not_found_with_suggestion(path));
+        }
+        
+        let mut reader = csv_reader(open_input(&path, Rc::default())?);
+        let headers = self.read_headers(&mut reader)?;
+        if self.validate_headers {
+            NppesMainSchema::validate_headers(&headers)?;
+        }
+        
+        let mut csv_record = csv::StringRecord::new();
+        let mut byte_record = csv::ByteRecord::new();
+        let mut record_count = 0;
+        let mut invalid_count = 0;
+        
+        Ok(std::iter::from_fn(move || loop {
+            let read = self.read_record(&mut reader, &mut csv_record, &mut byte_record);
+            record_count += 1;
+            
+            let result = match read {
+                Ok(false) => return None,
+                Ok(true) => self.parse_main_record(&csv_record, record_count),
+                Err(e) => Err(NppesError::from_csv_error(e, Some(record_count), ErrorContext {
+                    file_path: Some(path.clone()),
+                    line_number: Some(record_count),
+                    ..Default::default()
+                })),
+            };
+            
+            match result {
+                Err(e) if self.skips(&e) => {
+                    invalid_count += 1;
+                    if invalid_count <= 10 {
+                        eprintln!("Warning: Skipping invalid record {}: {}", record_count, e);
+                    }
+                }
+                result => return Some(result),
+            }
+        }))
+    }
+    
+    /// Load providers from a JSON Lines file written by [`JsonExporter::as_json_lines`]
+    /// 
+    /// Each non-blank line holds one serialized [`NppesRecord`]. Deserializing is
+    /// much faster than re-parsing the NPPES CSV, so exporting once and reloading
+    /// the JSONL is a cheap way to cache a parsed file. Malformed lines are skipped
+    /// with a warning when [`with_skip_invalid_records`](Self::with_skip_invalid_records)
+    /// is enabled and fail the load otherwise.
+    /// 
+    /// [`JsonExporter::as_json_lines`]: crate::export::JsonExporter::as_json_lines
+    pub fn load_main_data_jsonl<P: AsRef<Path>>(&self, path: P) -> Result<Vec<NppesRecord>> {
+        use std::io::BufRead;
+        
         let path = path.as_ref();
+        if !path.exists() {
+            return Err(NppesError::file_not_found_with_suggestion(path.to_path_buf()));
+        }
+        let reader = std::io::BufReader::new(open_input(path, Rc::default())?);
+        
+        let mut records = Vec::new();
+        let mut invalid_count = 0;
+        for (index, line) in reader.lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str::<NppesRecord>(&line) {
+                Ok(record) => records.push(record),
+                Err(e) => {
+                    let error = NppesError::DataValidation {
+                        message: format!("Invalid JSON provider record: {}", e),
+                        field: None,
+                        value: None,
+                        context: ErrorContext {
+                            file_path: Some(path.to_path_buf()),
+                            line_number: Some(index + 1),
+                            ..Default::default()
+                        },
+                    };
+                    if !self.skip_invalid_records {
+                        return Err(error);
+                    }
+                    invalid_count += 1;
+                    if invalid_count <= 10 {
+                        eprintln!("Warning: Skipping invalid record {}: {}", index + 1, error);
+                    }
+                }
+            }
+        }
+        Ok(records)
+    }
+    
+    /// Load main provider data starting at a byte offset, returning the offset reached
+    /// 
+    /// Pass `0` to start from the first record. The returned offset points just past the
+    /// last record read and can be passed back in to resume an interrupted load; with
+    /// `max_records` set, this allows ingesting a large file in checkpointed chunks.
+    /// Offsets are only meaningful for the exact file they were produced from, so
+    /// resumption assumes the file has not changed between runs. Line numbers in error
+    /// messages are relative to the starting offset. Offsets into a gzip-compressed
+    /// file count decompressed bytes, and resuming decompresses up to the offset again.
+    /// 
+    /// # Example
+    /// ```no_run
+    /// # use nppes::reader::NppesReader;
+    /// let reader = NppesReader::new();
+    /// let mut offset = 0;
+    /// loop {
+    ///     let (chunk, next_offset) = reader.load_main_data_from_offset("npidata.csv", offset, Some(100_000))?;
+    ///     if chunk.is_empty() {
+    ///         break;
+    ///     }
+    ///     // ... persist `chunk`, then checkpoint `next_offset` ...
+    ///     offset = next_offset;
+    /// }
+    /// # Ok::<(), nppes::NppesError>(())
+    /// ```
+    pub fn load_main_data_from_offset<P: AsRef<Path>>(
+        &self,
+        path: P,
+        start_offset: u64,
+        max_records: Option<usize>,
+    ) -> Result<(Vec<NppesRecord>, u64)> {
+        let (records, report) = self.load_main_records(path.as_ref(), start_offset, max_records)?;
+        Ok((records, report.end_offset))
+    }
+    
+    fn load_main_records(
+        &self,
+        path: &Path,
+        start_offset: u64,
+        max_records: Option<usize>,
+    ) -> Result<(Vec<NppesRecord>, LoadReport)> {
         
         if !path.exists() {
             return Err(NppesError::file_not_found_with_suggestion(path.to_path_buf()));
         }
         
         // Check memory availability
-        let memory_estimate = Self::estimate_memory_usage(path)?;
+        let memory_estimate = Self::estimate_memory_usage_for(path, NppesFileKind::Main)?;
         println!("Estimated memory usage: {}", memory_estimate.estimated_memory_human);
         
-        Self::check_memory_availability(path)?;
+        if self.memory_check {
+            ensure_memory_fits(required_memory(&memory_estimate, max_records), get_available_memory())?;
+        }
         
-        let file = File::open(path)?;
-        let file_size = file.metadata()?.len();
+        let file_size = std::fs::metadata(path)?.len();
         
-        let mut reader = ReaderBuilder::new()
-            .has_headers(true)
-            .from_reader(file);
+        let bytes_read = Rc::new(Cell::new(0));
+        let mut reader = csv_reader(open_input(path, Rc::clone(&bytes_read))?);
         
-        // Validate headers if enabled
+        // Always consume the header row so record positions are reported correctly
+        let headers = self.read_headers(&mut reader)?;
         if self.validate_headers {
-            let headers: Vec<String> = reader.headers()?.iter().map(|s| s.to_string()).collect();
             NppesMainSchema::validate_headers(&headers)?;
         }
         
-        let mut records = Vec::with_capacity(memory_estimate.estimated_records as usize);
+        // Resume from the checkpoint; offsets inside the header row mean "from the top"
+        if start_offset > reader.position().byte() {
+            let mut position = csv::Position::new();
+            position.set_byte(start_offset);
+            reader.seek(position)?;
+        }
+        
+        let capacity = memory_estimate.estimated_records as usize;
+        let mut records = Vec::with_capacity(max_records.map_or(capacity, |max| max.min(capacity)));
         let mut record_count = 0;
-        let mut bytes_processed = 0;
         let mut invalid_count = 0;
-        let start_time = Instant::now();
-        
-        #[cfg(feature = "progress")]
-        let progress_bar = if self.show_progress_bar {
-            let pb = ProgressBar::new(file_size);
-            pb.set_style(
-                ProgressStyle::default_bar()
-                    .template("[{elapsed_precise}] [{bar:40.cyan/blue}] {bytes}/{total_bytes} ({eta})")
-                    .unwrap()
-                    .progress_chars("#>-")
-            );
-            Some(pb)
-        } else {
-            None
-        };
-        
-        for result in reader.records() {
-            record_count += 1;
-            
-            // Update progress
-            let elapsed = start_time.elapsed();
-            let records_per_second = if elapsed.as_secs() > 0 {
-                record_count as f64 / elapsed.as_secs_f64()
-            } else {
-                0.0
-            };
-            
-            // Estimate bytes processed (rough approximation)
-            bytes_processed = (record_count * 2000).min(file_size as usize);
+        let mut inferred_count = 0;
+        let mut raw_records = Vec::new();
+        let progress = LoadProgress::new(self, file_size, Some(memory_estimate.estimated_records as usize), bytes_read);
+        
+        let mut byte_record = csv::ByteRecord::new();
+        let mut exhausted = false;
+        while !exhausted {
+            let wanted = max_records.map_or(PARSE_CHUNK_SIZE, |max| max.saturating_sub(records.len()).min(PARSE_CHUNK_SIZE));
+            if wanted == 0 {
+                break;
+            }
             
-            #[cfg(feature = "progress")]
-            {
-                if let Some(ref pb) = progress_bar {
-                    pb.set_position(bytes_processed as u64);
-                }
-                
-                if let Some(ref callback) = self.progress_callback {
-                    if record_count % 1000 == 0 {
-                        let progress = ProgressInfo {
-                            current_records: record_count,
-                            estimated_total: Some(memory_estimate.estimated_records as usize),
-                            bytes_processed,
-                            elapsed_time: elapsed,
-                            estimated_remaining: estimate_remaining_time(
-                                record_count,
-                                memory_estimate.estimated_records as usize,
-                                elapsed
-                            ),
-                            records_per_second,
-                        };
-                        callback(progress);
+            // Read a chunk of rows sequentially, then parse it (in parallel if enabled)
+            let mut chunk = Vec::with_capacity(wanted);
+            while chunk.len() < wanted {
+                let mut csv_record = csv::StringRecord::new();
+                let result = match self.read_record(&mut reader, &mut csv_record, &mut byte_record) {
+                    Ok(true) => Ok(csv_record),
+                    Ok(false) => {
+                        exhausted = true;
+                        break;
                     }
-                }
+                    Err(e) => Err(e),
+                };
+                record_count += 1;
+                chunk.push((record_count, result));
+                
+                progress.update(record_count);
             }
             
-            match result {
-                Ok(csv_record) => {
-                    match self.parse_main_record(&csv_record, record_count) {
-                        Ok(record) => records.push(record),
-                        Err(e) => {
-                            invalid_count += 1;
-                            if self.skip_invalid_records {
-                                if invalid_count <= 10 {
-                                    eprintln!("Warning: Skipping invalid record {}: {}", record_count, e);
-                                }
-                            } else {
-                                return Err(e);
+            // Results come back in input order, so skipping and error reporting
+            // behave exactly as in a sequential load
+            for (line_number, result) in self.parse_main_chunk(chunk) {
+                match result {
+                    Ok((csv_record, Ok(record))) => {
+                        if record.entity_type.is_some() && csv_record.get(1).is_none_or(|s| s.trim().is_empty()) {
+                            inferred_count += 1;
+                        }
+                        if self.capture_raw {
+                            raw_records.push(csv_record);
+                        }
+                        records.push(record);
+                    }
+                    Ok((_, Err(e))) => {
+                        invalid_count += 1;
+                        if self.skips(&e) {
+                            if invalid_count <= 10 {
+                                eprintln!("Warning: Skipping invalid record {}: {}", line_number, e);
                             }
+                        } else {
+                            return Err(e);
                         }
                     }
-                }
-                Err(e) => {
-                    let error = NppesError::CsvParse {
-                        message: format!("CSV error: {}", e),
-                        line: Some(record_count),
-                        column: None,
-                        context: ErrorContext {
+                    Err(e) => {
+                        let error = NppesError::from_csv_error(e, Some(line_number), ErrorContext {
                             file_path: Some(path.to_path_buf()),
-                            line_number: Some(record_count),
+                            line_number: Some(line_number),
                             ..Default::default()
-                        },
-                    };
-                    
-                    if self.skip_invalid_records {
-                        invalid_count += 1;
-                        if invalid_count <= 10 {
-                            eprintln!("Warning: {}", error);
+                        });
+                        
+                        if self.skip_invalid_records {
+                            invalid_count += 1;
+                            if invalid_count <= 10 {
+                                eprintln!("Warning: {}", error);
+                            }
+                        } else {
+                            return Err(error);
                         }
-                    } else {
-                        return Err(error);
                     }
                 }
             }
         }
         
-        #[cfg(feature = "progress")]
-        if let Some(pb) = progress_bar {
-            pb.finish_with_message("Loading complete");
-        }
-        
-        let elapsed = start_time.elapsed();
+        let elapsed = progress.finish();
         
         #[cfg(feature = "progress")]
         if self.show_progress_bar {
@@ -321,244 +745,196 @@ impl NppesReader {
             }
         }
         
-        Ok(records)
+        let report = LoadReport {
+            records_loaded: records.len(),
+            invalid_records: invalid_count,
+            entity_types_inferred: inferred_count,
+            end_offset: reader.position().byte(),
+            raw_records,
+        };
+        Ok((records, report))
     }
     
     /// Load taxonomy reference data from CSV file
     pub fn load_taxonomy_data<P: AsRef<Path>>(&self, path: P) -> Result<Vec<TaxonomyReference>> {
-        let path = path.as_ref();
-        
-        if !path.exists() {
-            return Err(NppesError::file_not_found_with_suggestion(path.to_path_buf()));
-        }
-        
-        let file = File::open(path)?;
-        let mut reader = ReaderBuilder::new()
-            .has_headers(true)
-            .from_reader(file);
-        
-        // Validate headers if enabled
-        if self.validate_headers {
-            let headers: Vec<String> = reader.headers()?.iter().map(|s| s.to_string()).collect();
-            TaxonomySchema::validate_headers(&headers)?;
-        }
-        
-        let mut records = Vec::new();
-        let start_time = Instant::now();
-        
-        for (idx, result) in reader.records().enumerate() {
-            let csv_record = result.map_err(|e| NppesError::CsvParse {
-                message: e.to_string(),
-                line: Some(idx + 2), // +2 for header and 0-based index
-                column: None,
-                context: ErrorContext {
-                    file_path: Some(path.to_path_buf()),
-                    line_number: Some(idx + 2),
-                    ..Default::default()
-                },
-            })?;
-            
-            let record = self.parse_taxonomy_record(&csv_record)?;
-            records.push(record);
-        }
-        
-        let elapsed = start_time.elapsed();
-        
-        #[cfg(feature = "progress")]
-        if self.show_progress_bar {
-            println!(
-                "Successfully loaded {} taxonomy reference records in {:.2}s",
-                records.len(),
-                elapsed.as_secs_f64()
-            );
-        }
-        
-        #[cfg(not(feature = "progress"))]
-        println!(
-            "Successfully loaded {} taxonomy reference records in {:.2}s",
-            records.len(),
-            elapsed.as_secs_f64()
-        );
-        
-        Ok(records)
+        self.load_reference_data(
+            path.as_ref(),
+            NppesFileKind::Taxonomy,
+            "taxonomy reference",
+            TaxonomySchema::validate_headers,
+            Self::parse_taxonomy_record,
+        )
     }
     
     /// Load other name reference data from CSV file
     pub fn load_other_name_data<P: AsRef<Path>>(&self, path: P) -> Result<Vec<OtherNameRecord>> {
-        let path = path.as_ref();
-        
-        if !path.exists() {
-            return Err(NppesError::file_not_found_with_suggestion(path.to_path_buf()));
-        }
-        
-        let file = File::open(path)?;
-        let mut reader = ReaderBuilder::new()
-            .has_headers(true)
-            .from_reader(file);
-        
-        if self.validate_headers {
-            let headers: Vec<String> = reader.headers()?.iter().map(|s| s.to_string()).collect();
-            OtherNameSchema::validate_headers(&headers)?;
-        }
-        
-        let mut records = Vec::new();
-        let start_time = Instant::now();
-        
-        for (idx, result) in reader.records().enumerate() {
-            let csv_record = result.map_err(|e| NppesError::CsvParse {
-                message: e.to_string(),
-                line: Some(idx + 2),
-                column: None,
-                context: ErrorContext {
-                    file_path: Some(path.to_path_buf()),
-                    line_number: Some(idx + 2),
-                    ..Default::default()
-                },
-            })?;
-            
-            let record = self.parse_other_name_record(&csv_record)?;
-            records.push(record);
-        }
-        
-        let elapsed = start_time.elapsed();
-        
-        #[cfg(feature = "progress")]
-        if self.show_progress_bar {
-            println!(
-                "Successfully loaded {} other name records in {:.2}s",
-                records.len(),
-                elapsed.as_secs_f64()
-            );
-        }
-        
-        #[cfg(not(feature = "progress"))]
-        println!(
-            "Successfully loaded {} other name records in {:.2}s",
-            records.len(),
-            elapsed.as_secs_f64()
-        );
-        
-        Ok(records)
+        self.load_reference_data(
+            path.as_ref(),
+            NppesFileKind::OtherName,
+            "other name",
+            OtherNameSchema::validate_headers,
+            Self::parse_other_name_record,
+        )
     }
     
     /// Load practice location reference data from CSV file
     pub fn load_practice_location_data<P: AsRef<Path>>(&self, path: P) -> Result<Vec<PracticeLocationRecord>> {
-        let path = path.as_ref();
-        
+        self.load_reference_data(
+            path.as_ref(),
+            NppesFileKind::PracticeLocation,
+            "practice location",
+            PracticeLocationSchema::validate_headers,
+            Self::parse_practice_location_record,
+        )
+    }
+    
+    /// Load endpoint reference data from CSV file
+    pub fn load_endpoint_data<P: AsRef<Path>>(&self, path: P) -> Result<Vec<EndpointRecord>> {
+        self.load_reference_data(
+            path.as_ref(),
+            NppesFileKind::Endpoint,
+            "endpoint",
+            EndpointSchema::validate_headers,
+            Self::parse_endpoint_record,
+        )
+    }
+    
+    /// Load every row of a reference file, reporting progress like the main file
+    /// 
+    /// `label` names the records in the completion message.
+    fn load_reference_data<T>(
+        &self,
+        path: &Path,
+        kind: NppesFileKind,
+        label: &str,
+        validate_headers: fn(&[String]) -> Result<()>,
+        parse: fn(&Self, &csv::StringRecord) -> Result<T>,
+    ) -> Result<Vec<T>> {
         if !path.exists() {
             return Err(NppesError::file_not_found_with_suggestion(path.to_path_buf()));
         }
         
-        let file = File::open(path)?;
-        let mut reader = ReaderBuilder::new()
-            .has_headers(true)
-            .from_reader(file);
+        let estimate = Self::estimate_memory_usage_for(path, kind)?;
+        let file_size = std::fs::metadata(path)?.len();
+        
+        let bytes_read = Rc::new(Cell::new(0));
+        let mut reader = csv_reader(open_input(path, Rc::clone(&bytes_read))?);
         
+        // Always consume the header row so record positions are reported correctly
+        let headers = self.read_headers(&mut reader)?;
         if self.validate_headers {
-            let headers: Vec<String> = reader.headers()?.iter().map(|s| s.to_string()).collect();
-            PracticeLocationSchema::validate_headers(&headers)?;
+            validate_headers(&headers)?;
         }
         
-        let mut records = Vec::new();
-        let start_time = Instant::now();
-        
-        for (idx, result) in reader.records().enumerate() {
-            let csv_record = result.map_err(|e| NppesError::CsvParse {
-                message: e.to_string(),
-                line: Some(idx + 2),
-                column: None,
-                context: ErrorContext {
+        let mut records = Vec::with_capacity(estimate.estimated_records as usize);
+        let progress = LoadProgress::new(self, file_size, Some(estimate.estimated_records as usize), bytes_read);
+        
+        let mut csv_record = csv::StringRecord::new();
+        let mut byte_record = csv::ByteRecord::new();
+        let mut idx = 0;
+        loop {
+            // +2 for header and 0-based index
+            let more = self.read_record(&mut reader, &mut csv_record, &mut byte_record)
+                .map_err(|e| NppesError::from_csv_error(e, Some(idx + 2), ErrorContext {
                     file_path: Some(path.to_path_buf()),
                     line_number: Some(idx + 2),
                     ..Default::default()
-                },
-            })?;
+                }))?;
+            if !more {
+                break;
+            }
+            idx += 1;
             
-            let record = self.parse_practice_location_record(&csv_record)?;
-            records.push(record);
+            records.push(parse(self, &csv_record)?);
+            progress.update(idx);
         }
         
-        let elapsed = start_time.elapsed();
+        let elapsed = progress.finish();
         
         #[cfg(feature = "progress")]
         if self.show_progress_bar {
             println!(
-                "Successfully loaded {} practice location records in {:.2}s",
+                "Successfully loaded {} {} records in {:.2}s",
                 records.len(),
+                label,
                 elapsed.as_secs_f64()
             );
         }
         
         #[cfg(not(feature = "progress"))]
         println!(
-            "Successfully loaded {} practice location records in {:.2}s",
+            "Successfully loaded {} {} records in {:.2}s",
             records.len(),
+            label,
             elapsed.as_secs_f64()
         );
         
         Ok(records)
     }
     
-    /// Load endpoint reference data from CSV file
-    pub fn load_endpoint_data<P: AsRef<Path>>(&self, path: P) -> Result<Vec<EndpointRecord>> {
-        let path = path.as_ref();
-        
-        if !path.exists() {
-            return Err(NppesError::file_not_found_with_suggestion(path.to_path_buf()));
-        }
-        
-        let file = File::open(path)?;
-        let mut reader = ReaderBuilder::new()
-            .has_headers(true)
-            .from_reader(file);
-        
-        if self.validate_headers {
-            let headers: Vec<String> = reader.headers()?.iter().map(|s| s.to_string()).collect();
-            EndpointSchema::validate_headers(&headers)?;
-        }
+    /// Whether a main record that failed to parse is skipped rather than returned
+    /// 
+    /// Records with an invalid NPI are always skipped at [`ValidationLevel::None`].
+    fn skips(&self, error: &NppesError) -> bool {
+        self.skip_invalid_records
+            || (self.validation_level == ValidationLevel::None && matches!(error, NppesError::InvalidNpi { .. }))
+    }
+    
+    /// Parse a chunk of numbered rows, keeping each row alongside its parse result
+    /// 
+    /// Output order matches input order.
+    fn parse_main_chunk(&self, chunk: Vec<(usize, csv::Result<csv::StringRecord>)>) -> Vec<ParsedRow> {
+        let parse = |(line_number, row): (usize, csv::Result<csv::StringRecord>)| {
+            let parsed = row.map(|row| {
+                let record = self.parse_main_record(&row, line_number);
+                (row, record)
+            });
+            (line_number, parsed)
+        };
         
-        let mut records = Vec::new();
-        let start_time = Instant::now();
-        
-        for (idx, result) in reader.records().enumerate() {
-            let csv_record = result.map_err(|e| NppesError::CsvParse {
-                message: e.to_string(),
-                line: Some(idx + 2),
-                column: None,
-                context: ErrorContext {
-                    file_path: Some(path.to_path_buf()),
-                    line_number: Some(idx + 2),
-                    ..Default::default()
-                },
-            })?;
-            
-            let record = self.parse_endpoint_record(&csv_record)?;
-            records.push(record);
+        #[cfg(feature = "parallel")]
+        if self.parallel_parsing {
+            use rayon::prelude::*;
+            return chunk.into_par_iter().map(parse).collect();
         }
         
-        let elapsed = start_time.elapsed();
-        
-        #[cfg(feature = "progress")]
-        if self.show_progress_bar {
-            println!(
-                "Successfully loaded {} endpoint records in {:.2}s",
-                records.len(),
-                elapsed.as_secs_f64()
-            );
+        chunk.into_iter().map(parse).collect()
+    }
+    
+    /// Read the header row, decoding it with the configured encoding
+    fn read_headers<R: std::io::Read>(&self, reader: &mut csv::Reader<R>) -> Result<Vec<String>> {
+        Ok(match self.encoding {
+            TextEncoding::Utf8 => reader.headers()?.iter().map(|s| s.to_string()).collect(),
+            TextEncoding::Latin1 => reader.byte_headers()?.iter().map(decode_latin1).collect(),
+        })
+    }
+    
+    /// Read the next record into `record`, decoding it with the configured encoding
+    /// 
+    /// `buffer` is scratch space reused across calls for non-UTF-8 input.
+    fn read_record<R: std::io::Read>(
+        &self,
+        reader: &mut csv::Reader<R>,
+        record: &mut csv::StringRecord,
+        buffer: &mut csv::ByteRecord,
+    ) -> csv::Result<bool> {
+        match self.encoding {
+            TextEncoding::Utf8 => reader.read_record(record),
+            TextEncoding::Latin1 => {
+                if !reader.read_byte_record(buffer)? {
+                    return Ok(false);
+                }
+                record.clear();
+                for field in buffer.iter() {
+                    record.push_field(&decode_latin1(field));
+                }
+                Ok(true)
+            }
         }
-        
-        #[cfg(not(feature = "progress"))]
-        println!(
-            "Successfully loaded {} endpoint records in {:.2}s",
-            records.len(),
-            elapsed.as_secs_f64()
-        );
-        
-        Ok(records)
     }
     
     /// Parse a main NPPES record from CSV row
-    fn parse_main_record(&self, record: &csv::StringRecord, line_number: usize) -> Result<NppesRecord> {
+    pub(crate) fn parse_main_record(&self, record: &csv::StringRecord, line_number: usize) -> Result<NppesRecord> {
         let get_field = |index: usize| -> Option<String> {
             record.get(index)
                 .filter(|s| !s.trim().is_empty())
@@ -579,44 +955,80 @@ impl NppesReader {
             })
         };
         
+        let level = self.validation_level;
+        let invalid = |field: &str, value: &str, message: String| NppesError::DataValidation {
+            message,
+            field: Some(field.to_string()),
+            value: Some(value.to_string()),
+            context: ErrorContext {
+                line_number: Some(line_number),
+                ..Default::default()
+            },
+        };
+        let get_date = |index: usize| -> Result<Option<NaiveDate>> {
+            match get_field(index) {
+                Some(s) if level >= ValidationLevel::Standard => self.parse_date(&s).map(Some),
+                Some(s) => Ok(self.parse_date(&s).ok()),
+                None => Ok(None),
+            }
+        };
+        
         // Core identifiers
         let npi_str = get_required_field(0, "NPI")?;
         let npi = Npi::new(npi_str.clone()).map_err(|_| NppesError::invalid_npi(&npi_str))?;
         
         let entity_type_str = get_field(1);
         let entity_type = match entity_type_str {
+            Some(ref s) if level >= ValidationLevel::Basic => Some(EntityType::from_code(s)?),
             Some(ref s) => EntityType::from_code(s).ok(),
             None => None,
         };
         
-        let replacement_npi = get_field(2).map(|s| Npi::new(s)).transpose()
-            .map_err(|e| e)?;
-        let ein = get_field(3);
+        let replacement_npi = match get_field(2) {
+            Some(s) if level >= ValidationLevel::Standard => Some(Npi::new(s)?),
+            Some(s) => Npi::new(s).ok(),
+            None => None,
+        };
+        let ein = get_field(3).map(|value| Ein::from_raw(&value));
         
         // Provider names
         let provider_name = ProviderName {
-            prefix: get_field(9).as_deref().and_then(NamePrefixCode::from_code),
-            first: get_field(7),
-            middle: get_field(8),
-            last: get_field(6),
-            suffix: get_field(10).as_deref().and_then(NameSuffixCode::from_code),
-            credential: get_field(11),
+            prefix: get_field(8).as_deref().and_then(NamePrefixCode::from_code),
+            first: get_field(6),
+            middle: get_field(7),
+            last: get_field(5),
+            suffix: get_field(9).as_deref().and_then(NameSuffixCode::from_code),
+            credential: get_field(10),
         };
         
         let provider_other_name = ProviderName {
-            prefix: get_field(17).as_deref().and_then(NamePrefixCode::from_code),
-            first: get_field(15),
-            middle: get_field(16),
-            last: get_field(14),
-            suffix: get_field(18).as_deref().and_then(NameSuffixCode::from_code),
-            credential: get_field(19),
+            prefix: get_field(16).as_deref().and_then(NamePrefixCode::from_code),
+            first: get_field(14),
+            middle: get_field(15),
+            last: get_field(13),
+            suffix: get_field(17).as_deref().and_then(NameSuffixCode::from_code),
+            credential: get_field(18),
         };
         
         // Organization information
         let organization_name = OrganizationName {
             legal_business_name: get_field(4),
-            other_name: get_field(12),
-            other_name_type: get_field(13).as_deref().and_then(OtherProviderNameTypeCode::from_code),
+            other_name: get_field(11),
+            other_name_type: get_field(12).as_deref().and_then(OtherProviderNameTypeCode::from_code),
+        };
+        
+        // Infer a blank entity type from whichever name block is populated
+        let entity_type = match entity_type {
+            None if self.infer_entity_type && entity_type_str.is_none() => {
+                if organization_name.legal_business_name.is_some() {
+                    Some(EntityType::Organization)
+                } else if provider_name.last.is_some() {
+                    Some(EntityType::Individual)
+                } else {
+                    None
+                }
+            }
+            entity_type => entity_type,
         };
         
         // Addresses
@@ -642,27 +1054,44 @@ impl NppesReader {
             country: get_field(33).as_deref().map(CountryCode::from_code),
         };
         
+        if level >= ValidationLevel::Strict {
+            // Foreign addresses carry provinces or nothing in the state column
+            for (address, state_index, field) in [
+                (&mailing_address, 23, "Provider Business Mailing Address State Name"),
+                (&practice_address, 31, "Provider Business Practice Location Address State Name"),
+            ] {
+                let domestic = address.country.as_ref().is_none_or(|c| c.as_code() == "US");
+                if let Some(state) = get_field(state_index).filter(|_| domestic && address.state.is_none()) {
+                    return Err(invalid(field, &state, format!("Unknown state code '{}'", state)));
+                }
+            }
+        }
+        
         // Dates
-        let enumeration_date = get_field(36).map(|s| self.parse_date(&s)).transpose()?;
-        let last_update_date = get_field(37).map(|s| self.parse_date(&s)).transpose()?;
-        let deactivation_date = get_field(39).map(|s| self.parse_date(&s)).transpose()?;
-        let reactivation_date = get_field(40).map(|s| self.parse_date(&s)).transpose()?;
+        let enumeration_date = get_date(36)?;
+        let last_update_date = get_date(37)?;
+        let deactivation_date = get_date(39)?;
+        let reactivation_date = get_date(40)?;
         
-        // Parse taxonomy codes (starting from column 47)
+        // Parse taxonomy codes (starting from column 47, groups from column 314)
         let mut taxonomy_codes = Vec::new();
-        for i in 0..MAX_TAXONOMY_CODES {
+        let taxonomy_sets = if self.minimal_parse { 0 } else { MAX_TAXONOMY_CODES };
+        for i in 0..taxonomy_sets {
             let base_index = 47 + (i * 4);
             if let Some(code) = get_field(base_index) {
-                let group_taxonomy_code = get_field(307 + i).as_deref().and_then(GroupTaxonomyCode::from_code);
+                let group_taxonomy_code = get_field(314 + i).as_deref().and_then(GroupTaxonomyCode::from_code);
                 let primary_switch = get_field(base_index + 3).as_deref().and_then(PrimaryTaxonomySwitch::from_code);
+                let license_state = get_field(base_index + 2);
+                let license_state_code = license_state.as_deref().and_then(StateCode::from_code);
                 let taxonomy_code = TaxonomyCode {
                     code,
                     license_number: get_field(base_index + 1),
-                    license_state: get_field(base_index + 2),
+                    license_state,
+                    license_state_code,
                     is_primary: get_field(base_index + 3)
                         .map(|s| s == "Y")
                         .unwrap_or(false),
-                    taxonomy_group: get_field(307 + i),
+                    taxonomy_group: get_field(314 + i),
                     group_taxonomy_code,
                     primary_switch,
                 };
@@ -670,9 +1099,22 @@ impl NppesReader {
             }
         }
         
+        if level >= ValidationLevel::Strict && taxonomy_codes.iter().filter(|t| t.is_primary).count() > 1 {
+            let primaries: Vec<&str> = taxonomy_codes.iter()
+                .filter(|t| t.is_primary)
+                .map(|t| t.code.as_str())
+                .collect();
+            return Err(invalid(
+                "Healthcare Provider Primary Taxonomy Switch",
+                &primaries.join(","),
+                format!("{} taxonomy codes are marked primary: {}", primaries.len(), primaries.join(", ")),
+            ));
+        }
+        
         // Parse other identifiers (starting from column 107)
         let mut other_identifiers = Vec::new();
-        for i in 0..MAX_OTHER_IDENTIFIERS {
+        let identifier_sets = if self.minimal_parse { 0 } else { MAX_OTHER_IDENTIFIERS };
+        for i in 0..identifier_sets {
             let base_index = 107 + (i * 4);
             if let Some(identifier) = get_field(base_index) {
                 let state = get_field(base_index + 2).as_deref().and_then(StateCode::from_code);
@@ -687,30 +1129,27 @@ impl NppesReader {
             }
         }
         
-        // Authorized official (for organizations)
-        let authorized_official = if entity_type == Some(EntityType::Organization) {
-            Some(AuthorizedOfficial {
-                prefix: get_field(308).as_deref().and_then(NamePrefixCode::from_code),
-                first_name: get_field(43),
-                middle_name: get_field(44),
-                last_name: get_field(42),
-                suffix: get_field(309).as_deref().and_then(NameSuffixCode::from_code),
-                credential: get_field(310),
-                title: get_field(45),
-                telephone: get_field(46),
-            })
-        } else {
-            None
-        };
+        // Authorized official, for any entity type that populates the columns
+        let authorized_official = Some(AuthorizedOfficial {
+            prefix: get_field(311).as_deref().and_then(NamePrefixCode::from_code),
+            first_name: get_field(43),
+            middle_name: get_field(44),
+            last_name: get_field(42),
+            suffix: get_field(312).as_deref().and_then(NameSuffixCode::from_code),
+            credential: get_field(313),
+            title: get_field(45),
+            telephone: get_field(46),
+        })
+        .filter(|official| !official.is_empty());
         
         // Organization flags and parent info (near the end)
         let sole_proprietor = get_field(307).as_deref().and_then(SoleProprietorCode::from_code);
         let organization_subpart = get_field(308).as_deref().and_then(SubpartCode::from_code);
         let parent_organization_lbn = get_field(309);
-        let parent_organization_tin = get_field(310);
+        let parent_organization_tin = get_field(310).map(|value| Ein::from_raw(&value));
         
         // Certification date (last column)
-        let certification_date = get_field(329).map(|s| self.parse_date(&s)).transpose()?;
+        let certification_date = get_date(329)?;
         
         // Deactivation reason and gender codes
         let deactivation_reason_code = get_field(38);
@@ -718,7 +1157,7 @@ impl NppesReader {
         let provider_gender_code = get_field(41);
         let provider_gender = provider_gender_code.as_deref().and_then(SexCode::from_code);
         // Provider other name type code
-        let provider_other_name_type_code = get_field(20);
+        let provider_other_name_type_code = get_field(19);
         let provider_other_name_type = provider_other_name_type_code.as_deref().and_then(OtherProviderNameTypeCode::from_code);
         
         Ok(NppesRecord {
@@ -881,15 +1320,266 @@ impl NppesReader {
         })
     }
     
-    /// Parse a date string in MM/DD/YYYY format
+    /// Parse a date string with the first configured format that matches
     fn parse_date(&self, date_str: &str) -> Result<NaiveDate> {
-        NaiveDate::parse_from_str(date_str, "%m/%d/%Y")
-            .map_err(|_| NppesError::date_parse_with_format(date_str, "MM/DD/YYYY"))
+        self.date_formats.iter()
+            .find_map(|format| NaiveDate::parse_from_str(date_str, format).ok())
+            .ok_or_else(|| {
+                let expected = if self.date_formats == [DEFAULT_DATE_FORMAT] {
+                    "MM/DD/YYYY".to_string()
+                } else {
+                    self.date_formats.join(" or ")
+                };
+                NppesError::date_parse_with_format(date_str, &expected)
+            })
     }
 }
 
 // Helper functions
 
+/// Date format of NPPES files (MM/DD/YYYY)
+const DEFAULT_DATE_FORMAT: &str = "%m/%d/%Y";
+
+/// Number of main file rows read before each (possibly parallel) parse step
+const PARSE_CHUNK_SIZE: usize = 10_000;
+
+/// A numbered main file row with its parse result, or the error reading it
+type ParsedRow = (usize, csv::Result<(csv::StringRecord, Result<NppesRecord>)>);
+
+/// Number of records sampled to estimate the average record length
+const MEMORY_ESTIMATE_SAMPLE_LINES: u64 = 1000;
+
+/// Approximate allocator overhead of each heap-allocated field, in bytes
+const HEAP_ALLOCATION_OVERHEAD: usize = 16;
+
+/// Build a CSV reader for NPPES files
+/// 
+/// Records may end in either `\n` or `\r\n`, even within one file, and quoted
+/// fields may span several lines. Spelling this out keeps a stray line break
+/// inside an address from shifting every record that follows it.
+fn csv_reader<R: std::io::Read>(source: R) -> csv::Reader<R> {
+    ReaderBuilder::new()
+        .has_headers(true)
+        .terminator(Terminator::CRLF)
+        .quoting(true)
+        .quote(b'"')
+        .double_quote(true)
+        .from_reader(source)
+}
+
+/// Check for a `.gz` extension or the gzip magic bytes, leaving the file at its start
+fn is_gzip(path: &Path, file: &mut File) -> std::io::Result<bool> {
+    if path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("gz")) {
+        return Ok(true);
+    }
+    let mut magic = [0u8; 2];
+    let read = file.read(&mut magic)?;
+    file.seek(SeekFrom::Start(0))?;
+    Ok(read == magic.len() && magic == [0x1f, 0x8b])
+}
+
+/// Open a data file, decompressing it on the fly if it is gzipped
+/// 
+/// `bytes_read` counts bytes of the file itself, so progress through a
+/// compressed file is measured against its compressed size.
+fn open_input(path: &Path, bytes_read: Rc<Cell<u64>>) -> Result<InputFile> {
+    let mut file = File::open(path)?;
+    if is_gzip(path, &mut file)? {
+        #[cfg(feature = "compression")]
+        return Ok(InputFile::Gzip {
+            path: path.to_path_buf(),
+            decoder: Box::new(MultiGzDecoder::new(CountingReader::new(file, bytes_read))),
+            position: 0,
+        });
+        
+        #[cfg(not(feature = "compression"))]
+        return Err(NppesError::Custom {
+            message: format!("'{}' is gzip-compressed", path.display()),
+            suggestion: Some("Enable the `compression` feature or decompress the file first".to_string()),
+            source: None,
+        });
+    }
+    Ok(InputFile::Plain(CountingReader::new(file, bytes_read)))
+}
+
+/// A data file opened by [`open_input`]
+enum InputFile {
+    Plain(CountingReader<File>),
+    /// Positions are offsets into the decompressed data
+    #[cfg(feature = "compression")]
+    Gzip {
+        path: std::path::PathBuf,
+        decoder: Box<MultiGzDecoder<CountingReader<File>>>,
+        position: u64,
+    },
+}
+
+impl Read for InputFile {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        match self {
+            InputFile::Plain(file) => file.read(buf),
+            #[cfg(feature = "compression")]
+            InputFile::Gzip { decoder, position, .. } => {
+                let read = decoder.read(buf)?;
+                *position += read as u64;
+                Ok(read)
+            }
+        }
+    }
+}
+
+impl Seek for InputFile {
+    /// Gzip streams can't seek, so they decompress forward to the target,
+    /// starting over from the beginning of the file to go backwards
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        match self {
+            InputFile::Plain(file) => file.seek(pos),
+            #[cfg(feature = "compression")]
+            InputFile::Gzip { path, decoder, position } => {
+                let target = match pos {
+                    SeekFrom::Start(offset) => offset,
+                    SeekFrom::Current(offset) => position.checked_add_signed(offset).ok_or_else(|| {
+                        std::io::Error::new(std::io::ErrorKind::InvalidInput, "seek before start of file")
+                    })?,
+                    SeekFrom::End(_) => return Err(std::io::Error::new(
+                        std::io::ErrorKind::Unsupported,
+                        "can't seek from the end of a gzip stream",
+                    )),
+                };
+                if target < *position {
+                    let bytes_read = Rc::clone(&decoder.get_ref().bytes_read);
+                    bytes_read.set(0);
+                    **decoder = MultiGzDecoder::new(CountingReader::new(File::open(&*path)?, bytes_read));
+                    *position = 0;
+                }
+                *position += std::io::copy(&mut decoder.by_ref().take(target - *position), &mut std::io::sink())?;
+                Ok(*position)
+            }
+        }
+    }
+}
+
+/// Counts the bytes read from the underlying file, for byte-accurate progress
+/// 
+/// The count is shared so it can be read while the CSV reader owns the wrapper.
+/// Seeking moves the count to the new position.
+struct CountingReader<R> {
+    inner: R,
+    bytes_read: Rc<Cell<u64>>,
+}
+
+impl<R> CountingReader<R> {
+    fn new(inner: R, bytes_read: Rc<Cell<u64>>) -> Self {
+        Self { inner, bytes_read }
+    }
+}
+
+impl<R: Read> Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.bytes_read.set(self.bytes_read.get() + read as u64);
+        Ok(read)
+    }
+}
+
+impl<R: Seek> Seek for CountingReader<R> {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        let position = self.inner.seek(pos)?;
+        self.bytes_read.set(position);
+        Ok(position)
+    }
+}
+
+/// Progress bar and callback updates while one file loads
+#[cfg_attr(not(feature = "progress"), allow(dead_code))]
+struct LoadProgress<'a> {
+    reader: &'a NppesReader,
+    bytes_read: Rc<Cell<u64>>,
+    file_size: u64,
+    estimated_total: Option<usize>,
+    start_time: Instant,
+    #[cfg(feature = "progress")]
+    progress_bar: Option<ProgressBar>,
+}
+
+impl<'a> LoadProgress<'a> {
+    /// Start timing, showing a progress bar if the reader asks for one
+    fn new(reader: &'a NppesReader, file_size: u64, estimated_total: Option<usize>, bytes_read: Rc<Cell<u64>>) -> Self {
+        #[cfg(feature = "progress")]
+        let progress_bar = if reader.show_progress_bar {
+            let pb = ProgressBar::new(file_size);
+            pb.set_style(
+                ProgressStyle::default_bar()
+                    .template("[{elapsed_precise}] [{bar:40.cyan/blue}] {bytes}/{total_bytes} ({eta})")
+                    .unwrap()
+                    .progress_chars("#>-")
+            );
+            Some(pb)
+        } else {
+            None
+        };
+        
+        Self {
+            reader,
+            bytes_read,
+            file_size,
+            estimated_total,
+            start_time: Instant::now(),
+            #[cfg(feature = "progress")]
+            progress_bar,
+        }
+    }
+    
+    /// Advance the bar, and call the progress callback every 1000 records
+    #[cfg_attr(not(feature = "progress"), allow(unused_variables))]
+    fn update(&self, record_count: usize) {
+        #[cfg(feature = "progress")]
+        {
+            let bytes_processed = self.bytes_read.get() as usize;
+            if let Some(ref pb) = self.progress_bar {
+                pb.set_position(bytes_processed as u64);
+            }
+            
+            if let Some(ref callback) = self.reader.progress_callback {
+                if record_count.is_multiple_of(1000) {
+                    let elapsed = self.start_time.elapsed();
+                    let records_per_second = if elapsed.as_secs() > 0 {
+                        record_count as f64 / elapsed.as_secs_f64()
+                    } else {
+                        0.0
+                    };
+                    callback(ProgressInfo {
+                        current_records: record_count,
+                        estimated_total: self.estimated_total,
+                        bytes_processed,
+                        elapsed_time: elapsed,
+                        estimated_remaining: estimate_remaining_time(
+                            bytes_processed,
+                            self.file_size as usize,
+                            elapsed
+                        ),
+                        records_per_second,
+                    });
+                }
+            }
+        }
+    }
+    
+    /// Finish the progress bar and return the time taken
+    fn finish(self) -> Duration {
+        #[cfg(feature = "progress")]
+        if let Some(pb) = self.progress_bar {
+            pb.finish_with_message("Loading complete");
+        }
+        self.start_time.elapsed()
+    }
+}
+
+/// Decode ISO-8859-1 bytes; every byte maps to the code point of the same value
+fn decode_latin1(bytes: &[u8]) -> String {
+    bytes.iter().map(|&b| b as char).collect()
+}
+
 /// Format bytes into human-readable string
 fn format_bytes(bytes: usize) -> String {
     const UNITS: &[&str] = &["B", "KB", "MB", "GB", "TB"];
@@ -917,18 +1607,752 @@ fn estimate_remaining_time(current: usize, total: usize, elapsed: Duration) -> O
     Some(Duration::from_secs_f64(remaining_secs))
 }
 
+/// Memory left free when loading a file (1GB)
+const MEMORY_HEADROOM_BYTES: usize = 1_073_741_824;
+
+/// Memory needed for the records a load reads, at most `max_records` of them
+fn required_memory(estimate: &MemoryEstimate, max_records: Option<usize>) -> usize {
+    match max_records {
+        Some(max) if (max as u64) < estimate.estimated_records => max.saturating_mul(estimate.bytes_per_record),
+        _ => estimate.estimated_memory_bytes,
+    }
+}
+
+/// Fail if `required` bytes plus headroom don't fit in `available`; unknown availability passes
+fn ensure_memory_fits(required: usize, available: Option<usize>) -> Result<()> {
+    match available {
+        Some(available) if required.checked_add(MEMORY_HEADROOM_BYTES).is_none_or(|needed| needed > available) => {
+            Err(NppesError::insufficient_memory(required, Some(available)))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Get available system memory for the current platform
+fn get_available_memory() -> Option<usize> {
+    #[cfg(target_os = "windows")]
+    return get_available_memory_windows();
+    
+    #[cfg(not(target_os = "windows"))]
+    get_available_memory_unix()
+}
+
 /// Get available system memory on Windows
 #[cfg(target_os = "windows")]
 fn get_available_memory_windows() -> Option<usize> {
-    // Windows-specific implementation would go here
-    // For now, return None to indicate unknown
-    None
+    /// `MEMORYSTATUSEX` from the Win32 API
+    #[repr(C)]
+    struct MemoryStatusEx {
+        length: u32,
+        memory_load: u32,
+        total_phys: u64,
+        avail_phys: u64,
+        total_page_file: u64,
+        avail_page_file: u64,
+        total_virtual: u64,
+        avail_virtual: u64,
+        avail_extended_virtual: u64,
+    }
+    
+    #[link(name = "kernel32")]
+    unsafe extern "system" {
+        fn GlobalMemoryStatusEx(buffer: *mut MemoryStatusEx) -> i32;
+    }
+    
+    let mut status = MemoryStatusEx {
+        length: std::mem::size_of::<MemoryStatusEx>() as u32,
+        memory_load: 0,
+        total_phys: 0,
+        avail_phys: 0,
+        total_page_file: 0,
+        avail_page_file: 0,
+        total_virtual: 0,
+        avail_virtual: 0,
+        avail_extended_virtual: 0,
+    };
+    // SAFETY: `status` is a properly sized MEMORYSTATUSEX with `length` set, as the API requires
+    let ok = unsafe { GlobalMemoryStatusEx(&mut status) };
+    (ok != 0).then(|| usize::try_from(status.avail_phys).unwrap_or(usize::MAX))
 }
 
 /// Get available system memory on Unix-like systems
 #[cfg(not(target_os = "windows"))]
 fn get_available_memory_unix() -> Option<usize> {
-    // Unix-specific implementation would go here
-    // For now, return None to indicate unknown
-    None
-} 
\ No newline at end of file
+    #[cfg(target_os = "linux")]
+    {
+        parse_meminfo_available(&std::fs::read_to_string("/proc/meminfo").ok()?)
+    }
+    
+    #[cfg(target_os = "macos")]
+    {
+        let output = std::process::Command::new("vm_stat").output().ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        parse_vm_stat_available(&String::from_utf8_lossy(&output.stdout))
+    }
+    
+    #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+    {
+        None
+    }
+}
+
+/// Read `MemAvailable` (in kB) from the contents of `/proc/meminfo`, in bytes
+#[cfg(any(target_os = "linux", test))]
+fn parse_meminfo_available(meminfo: &str) -> Option<usize> {
+    let line = meminfo.lines().find(|line| line.starts_with("MemAvailable:"))?;
+    let kilobytes: usize = line.split_whitespace().nth(1)?.parse().ok()?;
+    kilobytes.checked_mul(1024)
+}
+
+/// Add up free, inactive and speculative pages from `vm_stat` output, in bytes
+/// 
+/// These are the pages macOS can hand to a new allocation without swapping.
+#[cfg(any(target_os = "macos", test))]
+fn parse_vm_stat_available(vm_stat: &str) -> Option<usize> {
+    let mut lines = vm_stat.lines();
+    // "Mach Virtual Memory Statistics: (page size of 16384 bytes)"
+    let page_size: usize = lines.next()?
+        .split("page size of ")
+        .nth(1)?
+        .split_whitespace()
+        .next()?
+        .parse()
+        .ok()?;
+    
+    let mut pages = 0usize;
+    let mut found = false;
+    for line in lines {
+        let Some((name, value)) = line.split_once(':') else { continue };
+        if matches!(name.trim(), "Pages free" | "Pages inactive" | "Pages speculative") {
+            pages += value.trim().trim_end_matches('.').parse::<usize>().ok()?;
+            found = true;
+        }
+    }
+    found.then(|| pages.saturating_mul(page_size))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    /// Build a main-file row with every column empty except the given ones
+    fn main_row(fields: &[(usize, &str)]) -> csv::StringRecord {
+        let mut columns = vec![String::new(); NppesMainSchema::column_count()];
+        for &(index, value) in fields {
+            columns[index] = value.to_string();
+        }
+        csv::StringRecord::from(columns)
+    }
+    
+    #[test]
+    fn test_license_state_resolves_to_state_code() {
+        let row = main_row(&[(0, "1234567893"), (1, "1"), (47, "207Q00000X"), (49, "ca")]);
+        let record = NppesReader::new().parse_main_record(&row, 1).unwrap();
+        
+        let taxonomy = &record.taxonomy_codes[0];
+        assert_eq!(taxonomy.license_state.as_deref(), Some("ca"));
+        assert_eq!(taxonomy.license_state_code, Some(StateCode::CA));
+    }
+    
+    #[test]
+    fn test_columns_map_to_schema_positions() {
+        let names = NppesMainSchema::column_names();
+        let column = |name: &str| names.iter().position(|c| *c == name).unwrap();
+        let row = main_row(&[
+            (column("NPI"), "1234567893"),
+            (column("Entity Type Code"), "2"),
+            (column("Provider Organization Name (Legal Business Name)"), "ACME HEALTH"),
+            (column("Provider Other Organization Name"), "ACME CLINIC"),
+            (column("Provider Other Organization Name Type Code"), "3"),
+            (column("Authorized Official Last Name"), "SMITH"),
+            (column("Authorized Official Name Prefix Text"), "Dr."),
+            (column("Authorized Official Name Suffix Text"), "Jr."),
+            (column("Authorized Official Credential Text"), "MD"),
+            (column("Is Organization Subpart"), "Y"),
+            (column("Parent Organization LBN"), "ACME PARENT"),
+            (column("Parent Organization TIN"), "123456789"),
+            (column("Healthcare Provider Taxonomy Code_1"), "261QP2300X"),
+            (column("Healthcare Provider Taxonomy Group_1"), "193200000X"),
+        ]);
+        let record = NppesReader::new().parse_main_record(&row, 1).unwrap();
+        
+        let official = record.authorized_official.as_ref().unwrap();
+        assert_eq!(official.last_name.as_deref(), Some("SMITH"));
+        assert_eq!(official.prefix, Some(NamePrefixCode::Dr));
+        assert_eq!(official.suffix, Some(NameSuffixCode::Jr));
+        assert_eq!(official.credential.as_deref(), Some("MD"));
+        assert_eq!(record.organization_subpart, Some(SubpartCode::Yes));
+        assert_eq!(record.parent_organization_lbn.as_deref(), Some("ACME PARENT"));
+        assert_eq!(record.parent_organization_tin.as_ref().map(Ein::as_str), Some("123456789"));
+        assert_eq!(record.organization_name.other_name.as_deref(), Some("ACME CLINIC"));
+        assert_eq!(record.organization_name.other_name_type, Some(OtherProviderNameTypeCode::DoingBusinessAs));
+        assert_eq!(record.taxonomy_codes[0].group_taxonomy_code, Some(GroupTaxonomyCode::MultiSpecialtyGroup));
+        
+        let row = main_row(&[
+            (column("NPI"), "1245319599"),
+            (column("Entity Type Code"), "1"),
+            (column("Provider Last Name (Legal Name)"), "JONES"),
+            (column("Provider First Name"), "ANN"),
+            (column("Provider Middle Name"), "B"),
+            (column("Provider Name Prefix Text"), "Ms."),
+            (column("Provider Credential Text"), "RN"),
+            (column("Provider Other Last Name"), "JOHNSON"),
+            (column("Provider Other First Name"), "ANNE"),
+        ]);
+        let record = NppesReader::new().parse_main_record(&row, 1).unwrap();
+        
+        assert_eq!(record.provider_name.last.as_deref(), Some("JONES"));
+        assert_eq!(record.provider_name.first.as_deref(), Some("ANN"));
+        assert_eq!(record.provider_name.middle.as_deref(), Some("B"));
+        assert_eq!(record.provider_name.prefix, Some(NamePrefixCode::Ms));
+        assert_eq!(record.provider_name.credential.as_deref(), Some("RN"));
+        assert_eq!(record.provider_other_name.last.as_deref(), Some("JOHNSON"));
+        assert_eq!(record.provider_other_name.first.as_deref(), Some("ANNE"));
+    }
+    
+    #[test]
+    fn test_other_last_name_type_code_is_read() {
+        let row = main_row(&[
+            (0, "1234567893"),
+            (1, "1"),
+            (13, "JOHNSON"),
+            (19, "1"),
+            (20, "123 MAIN ST"),
+        ]);
+        let record = NppesReader::new().parse_main_record(&row, 1).unwrap();
+        assert_eq!(record.provider_other_name_type, Some(OtherProviderNameTypeCode::FormerName));
+        assert_eq!(record.mailing_address.line_1.as_deref(), Some("123 MAIN ST"));
+        
+        let row = main_row(&[(0, "1234567893"), (1, "1"), (19, "2")]);
+        let record = NppesReader::new().parse_main_record(&row, 1).unwrap();
+        assert_eq!(record.provider_other_name_type, Some(OtherProviderNameTypeCode::ProfessionalName));
+    }
+    
+    #[test]
+    fn test_stream_main_data_parses_lazily() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("npidata.csv");
+        let mut writer = csv::Writer::from_path(&path).unwrap();
+        writer.write_record(NppesMainSchema::column_names()).unwrap();
+        for npi in ["1234567893", "123", "1245319599"] {
+            let mut fields = vec![""; NppesMainSchema::column_count()];
+            fields[0] = npi;
+            fields[1] = "1";
+            writer.write_record(&fields).unwrap();
+        }
+        writer.flush().unwrap();
+        
+        let results: Vec<Result<NppesRecord>> = NppesReader::new().stream_main_data(&path).unwrap().collect();
+        assert_eq!(results.len(), 3);
+        assert!(results[1].is_err());
+        
+        let skipping = NppesReader::new().with_skip_invalid_records(true);
+        let npis: Vec<String> = skipping.stream_main_data(&path).unwrap()
+            .map(|record| record.unwrap().npi.as_str().to_string())
+            .collect();
+        assert_eq!(npis, ["1234567893", "1245319599"]);
+        
+        // Header problems surface before iteration
+        std::fs::write(&path, "NPI,Other\n1234567893,x\n").unwrap();
+        assert!(NppesReader::new().stream_main_data(&path).is_err());
+        assert!(NppesReader::new().stream_main_data(dir.path().join("missing.csv")).is_err());
+    }
+    
+    #[cfg(feature = "compression")]
+    #[test]
+    fn test_gzip_input_is_decompressed() {
+        use flate2::{Compression, write::GzEncoder};
+        
+        let gzip = |path: &Path, contents: &str| {
+            let mut encoder = GzEncoder::new(File::create(path).unwrap(), Compression::default());
+            std::io::Write::write_all(&mut encoder, contents.as_bytes()).unwrap();
+            encoder.finish().unwrap();
+        };
+        let dir = tempfile::tempdir().unwrap();
+        
+        let mut main = NppesMainSchema::column_names().join(",") + "\n";
+        for npi in ["1234567893", "1245319599", "1003000126"] {
+            let mut fields = vec![""; NppesMainSchema::column_count()];
+            fields[0] = npi;
+            fields[1] = "1";
+            main.push_str(&(fields.join(",") + "\n"));
+        }
+        // No extension, so only the magic bytes mark it as gzip
+        let main_path = dir.path().join("npidata");
+        gzip(&main_path, &main);
+        
+        let reader = NppesReader::new().with_progress_bar(false);
+        let records = reader.load_main_data(&main_path).unwrap();
+        assert_eq!(records.len(), 3);
+        
+        let (first, offset) = reader.load_main_data_from_offset(&main_path, 0, Some(1)).unwrap();
+        let (rest, _) = reader.load_main_data_from_offset(&main_path, offset, None).unwrap();
+        assert_eq!(first[0].npi.as_str(), "1234567893");
+        assert_eq!(rest.iter().map(|p| p.npi.as_str()).collect::<Vec<_>>(), ["1245319599", "1003000126"]);
+        
+        let other_names_path = dir.path().join("othername_pfile.csv.gz");
+        gzip(&other_names_path, "NPI,Provider Other Organization Name,Provider Other Organization Name Type Code\n1234567893,CLINIC,3\n");
+        let other_names = reader.load_other_name_data(&other_names_path).unwrap();
+        assert_eq!(other_names[0].provider_other_organization_name, "CLINIC");
+    }
+    
+    #[test]
+    fn test_load_main_data_jsonl_round_trips() {
+        let dir = tempfile::tempdir().unwrap();
+        let csv_path = dir.path().join("npidata.csv");
+        let mut writer = csv::Writer::from_path(&csv_path).unwrap();
+        writer.write_record(NppesMainSchema::column_names()).unwrap();
+        for (npi, entity_type, name) in [("1234567893", "1", "SMITH"), ("1245319599", "2", "ACME CLINIC")] {
+            let mut fields = vec![""; NppesMainSchema::column_count()];
+            fields[0] = npi;
+            fields[1] = entity_type;
+            fields[if entity_type == "1" { 5 } else { 4 }] = name;
+            fields[47] = "207Q00000X";
+            fields[50] = "Y";
+            writer.write_record(&fields).unwrap();
+        }
+        writer.flush().unwrap();
+        let records = NppesReader::new().load_main_data(&csv_path).unwrap();
+        
+        let dataset = crate::dataset::NppesDataset::new(records.clone(), None, None, None, None, None, None, None);
+        let jsonl_path = dir.path().join("providers.jsonl");
+        dataset.export_json_lines(&jsonl_path).unwrap();
+        assert_eq!(NppesReader::new().load_main_data_jsonl(&jsonl_path).unwrap(), records);
+        
+        // Blank lines are ignored; malformed lines fail or are skipped
+        let mut text = std::fs::read_to_string(&jsonl_path).unwrap();
+        text.push_str("\n{\"npi\": 42}\n\n");
+        std::fs::write(&jsonl_path, text).unwrap();
+        assert!(NppesReader::new().load_main_data_jsonl(&jsonl_path).is_err());
+        let skipped = NppesReader::new()
+            .with_skip_invalid_records(true)
+            .load_main_data_jsonl(&jsonl_path)
+            .unwrap();
+        assert_eq!(skipped, records);
+    }
+    
+    #[test]
+    fn test_parallel_and_sequential_parsing_agree() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("npidata.csv");
+        let mut writer = csv::Writer::from_path(&path).unwrap();
+        writer.write_record(NppesMainSchema::column_names()).unwrap();
+        let npis = ["1234567893", "1245319599", "1003000126", "123"];
+        let rows = PARSE_CHUNK_SIZE * 2 + 123;
+        for i in 0..rows {
+            let city = format!("CITY {}", i);
+            let mut fields = vec![""; NppesMainSchema::column_count()];
+            fields[0] = npis[i % npis.len()];
+            fields[1] = "1";
+            fields[22] = &city;
+            writer.write_record(&fields).unwrap();
+        }
+        writer.flush().unwrap();
+        
+        let load = |parallel: bool| {
+            NppesReader::new()
+                .with_skip_invalid_records(true)
+                .with_parallel_parsing(parallel)
+                .load_main_data_with_report(&path)
+                .unwrap()
+        };
+        let (sequential, sequential_report) = load(false);
+        let (parallel, parallel_report) = load(true);
+        
+        assert_eq!(sequential.len(), rows - rows / npis.len());
+        assert_eq!(parallel_report.invalid_records, sequential_report.invalid_records);
+        assert_eq!(parallel, sequential);
+        assert_eq!(parallel[5].mailing_address.city.as_deref(), Some("CITY 6"));
+        
+        // Chunked reads still stop exactly at max_records
+        let (chunk, _) = NppesReader::new()
+            .with_skip_invalid_records(true)
+            .load_main_data_from_offset(&path, 0, Some(PARSE_CHUNK_SIZE + 1))
+            .unwrap();
+        assert_eq!(chunk.len(), PARSE_CHUNK_SIZE + 1);
+    }
+    
+    #[test]
+    fn test_counting_reader_tracks_file_position() {
+        let bytes_read = Rc::new(Cell::new(0));
+        let data = "NPI,Name\n1234567893,A\n1245319599,B\n";
+        let mut reader = csv_reader(CountingReader::new(std::io::Cursor::new(data), Rc::clone(&bytes_read)));
+        assert_eq!(reader.records().count(), 2);
+        assert_eq!(bytes_read.get(), data.len() as u64);
+        
+        let mut position = csv::Position::new();
+        position.set_byte(9);
+        reader.seek(position).unwrap();
+        assert_eq!(bytes_read.get(), 9);
+    }
+    
+    #[test]
+    fn test_invalid_utf8_reports_encoding_error() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        std::io::Write::write_all(&mut file, b"NPI,Name,Type\n1234567893,CL\xc9MENT CLINIC,3\n").unwrap();
+        
+        let err = NppesReader::new()
+            .with_header_validation(false)
+            .load_other_name_data(file.path())
+            .unwrap_err();
+        match err {
+            NppesError::Encoding { line, byte_offset, .. } => {
+                assert_eq!(line, Some(2));
+                assert_eq!(byte_offset, Some(14));
+            }
+            other => panic!("expected encoding error, got {:?}", other),
+        }
+        
+        let records = NppesReader::new()
+            .with_header_validation(false)
+            .with_encoding(TextEncoding::Latin1)
+            .load_other_name_data(file.path())
+            .unwrap();
+        assert_eq!(records[0].provider_other_organization_name, "CL\u{c9}MENT CLINIC");
+    }
+    
+    #[cfg(feature = "progress")]
+    #[test]
+    fn test_reference_loaders_report_progress() {
+        use std::sync::Arc;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        let mut contents = OtherNameSchema::column_names().join(",") + "\n";
+        for i in 0..3000 {
+            contents.push_str(&format!("1234567893,CLINIC {},3\n", i));
+        }
+        std::io::Write::write_all(&mut file, contents.as_bytes()).unwrap();
+        
+        let calls = Arc::new(AtomicUsize::new(0));
+        let last_count = Arc::new(AtomicUsize::new(0));
+        let (calls_seen, count_seen) = (Arc::clone(&calls), Arc::clone(&last_count));
+        let records = NppesReader::new()
+            .with_progress_bar(false)
+            .with_progress(move |info| {
+                calls_seen.fetch_add(1, Ordering::SeqCst);
+                count_seen.store(info.current_records, Ordering::SeqCst);
+                assert!(info.estimated_total.is_some());
+            })
+            .load_other_name_data(file.path())
+            .unwrap();
+        
+        assert_eq!(records.len(), 3000);
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+        assert_eq!(last_count.load(Ordering::SeqCst), 3000);
+    }
+    
+    #[test]
+    fn test_configured_date_formats() {
+        let iso = main_row(&[(0, "1234567893"), (1, "1"), (36, "2005-05-23"), (37, "07/08/2007")]);
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d);
+        
+        match NppesReader::new().parse_main_record(&iso, 2).unwrap_err() {
+            NppesError::DateParse { value, expected_format, .. } => {
+                assert_eq!(value, "2005-05-23");
+                assert_eq!(expected_format, "MM/DD/YYYY");
+            }
+            other => panic!("expected date error, got {:?}", other),
+        }
+        
+        let formats = vec!["%Y-%m-%d".to_string(), "%m/%d/%Y".to_string()];
+        let reader = NppesReader::new().with_date_formats(formats);
+        let record = reader.parse_main_record(&iso, 2).unwrap();
+        assert_eq!(record.enumeration_date, date(2005, 5, 23));
+        assert_eq!(record.last_update_date, date(2007, 7, 8));
+        
+        let dashed = main_row(&[(0, "1234567893"), (1, "1"), (36, "05-23-2005")]);
+        let err = reader.parse_main_record(&dashed, 2).unwrap_err();
+        assert!(err.user_message().contains("Expected format: %Y-%m-%d or %m/%d/%Y"), "{}", err.user_message());
+        
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        let mut writer = csv::Writer::from_writer(&mut file);
+        writer.write_record(NppesMainSchema::column_names()).unwrap();
+        writer.write_record(&iso).unwrap();
+        drop(writer);
+        let records = NppesReader::new()
+            .with_date_formats(vec!["%Y-%m-%d".to_string()])
+            .with_validation_level(ValidationLevel::Basic)
+            .load_main_data(file.path())
+            .unwrap();
+        assert_eq!(records[0].enumeration_date, date(2005, 5, 23));
+        // Dates in the other format are dropped below Standard validation
+        assert_eq!(records[0].last_update_date, None);
+    }
+    
+    #[test]
+    fn test_minimal_parse_skips_taxonomies_and_identifiers() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        let mut writer = csv::Writer::from_writer(&mut file);
+        writer.write_record(NppesMainSchema::column_names()).unwrap();
+        for i in 0..500 {
+            let last_name = format!("SMITH{}", i);
+            writer.write_record(&main_row(&[
+                (0, "1234567893"), (1, "1"), (5, &last_name), (23, "TX"), (36, "05/23/2005"),
+                (47, "207Q00000X"), (48, "L1234"), (49, "TX"), (50, "Y"), (51, "208D00000X"),
+                (107, "ID-1"), (108, "05"), (109, "TX"), (110, "MEDICAID"),
+            ])).unwrap();
+        }
+        drop(writer);
+        
+        let full = NppesReader::new().load_main_data(file.path()).unwrap();
+        let minimal = NppesReader::new()
+            .with_minimal_parse(true)
+            .load_main_data(file.path())
+            .unwrap();
+        assert_eq!(full.len(), 500);
+        assert_eq!(minimal.len(), 500);
+        assert_eq!(full[0].taxonomy_codes.len(), 2);
+        assert_eq!(full[0].other_identifiers.len(), 1);
+        for (record, parsed) in minimal.iter().zip(&full) {
+            assert_eq!(record.npi, parsed.npi);
+            assert_eq!(record.provider_name, parsed.provider_name);
+            assert_eq!(record.mailing_address.state, Some(StateCode::TX));
+            assert_eq!(record.enumeration_date, NaiveDate::from_ymd_opt(2005, 5, 23));
+            assert!(record.taxonomy_codes.is_empty());
+            assert!(record.other_identifiers.is_empty());
+        }
+    }
+    
+    #[test]
+    fn test_validation_levels() {
+        // Unknown mailing state and two primary taxonomies
+        let row = main_row(&[
+            (0, "1234567893"), (1, "1"), (23, "XX"), (36, "05/23/2005"),
+            (47, "207Q00000X"), (50, "Y"), (51, "208D00000X"), (54, "Y"),
+        ]);
+        let reader = |level| NppesReader::new().with_validation_level(level);
+        
+        for level in [ValidationLevel::None, ValidationLevel::Basic, ValidationLevel::Standard] {
+            let record = reader(level).parse_main_record(&row, 2).unwrap();
+            assert_eq!(record.mailing_address.state, None);
+            assert_eq!(record.taxonomy_codes.iter().filter(|t| t.is_primary).count(), 2);
+        }
+        match reader(ValidationLevel::Strict).parse_main_record(&row, 2).unwrap_err() {
+            NppesError::DataValidation { field, value, context, .. } => {
+                assert_eq!(field.as_deref(), Some("Provider Business Mailing Address State Name"));
+                assert_eq!(value.as_deref(), Some("XX"));
+                assert_eq!(context.line_number, Some(2));
+            }
+            other => panic!("expected validation error, got {:?}", other),
+        }
+        let mut fixed_state = row.clone().iter().map(str::to_string).collect::<Vec<_>>();
+        fixed_state[23] = "TX".to_string();
+        let err = reader(ValidationLevel::Strict).parse_main_record(&csv::StringRecord::from(fixed_state), 2).unwrap_err();
+        assert!(err.to_string().contains("2 taxonomy codes are marked primary"), "{}", err);
+        
+        // A foreign province isn't a bad state code
+        let foreign = main_row(&[(0, "1234567893"), (1, "1"), (23, "ONTARIO"), (25, "CA")]);
+        assert!(reader(ValidationLevel::Strict).parse_main_record(&foreign, 2).is_ok());
+        
+        // Lower levels drop what they don't check
+        let sloppy = main_row(&[(0, "1234567893"), (1, "9"), (36, "2005-05-23")]);
+        let record = reader(ValidationLevel::None).parse_main_record(&sloppy, 2).unwrap();
+        assert_eq!((record.entity_type, record.enumeration_date), (None, None));
+        assert!(reader(ValidationLevel::Basic).parse_main_record(&sloppy, 2).is_err());
+        let bad_date = main_row(&[(0, "1234567893"), (1, "1"), (36, "2005-05-23")]);
+        assert_eq!(reader(ValidationLevel::Basic).parse_main_record(&bad_date, 2).unwrap().enumeration_date, None);
+        assert!(reader(ValidationLevel::Standard).parse_main_record(&bad_date, 2).is_err());
+        let bad_npi = main_row(&[(0, "123"), (1, "1")]);
+        for level in [ValidationLevel::None, ValidationLevel::Basic] {
+            assert!(matches!(reader(level).parse_main_record(&bad_npi, 2), Err(NppesError::InvalidNpi { .. })));
+        }
+        
+        // Without validation, records with an invalid NPI are skipped rather than kept
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        let mut writer = csv::Writer::from_writer(&mut file);
+        writer.write_record(NppesMainSchema::column_names()).unwrap();
+        writer.write_record(&bad_npi).unwrap();
+        writer.write_record(&sloppy).unwrap();
+        drop(writer);
+        let loaded = reader(ValidationLevel::None).load_main_data(file.path()).unwrap();
+        assert_eq!(loaded.iter().map(|r| r.npi.as_str()).collect::<Vec<_>>(), ["1234567893"]);
+        assert!(reader(ValidationLevel::Basic).load_main_data(file.path()).is_err());
+        
+        let config = crate::config::ConfigBuilder::new().validation_level(ValidationLevel::None).build();
+        assert_eq!(NppesReader::new().with_config(&config).validation_level, ValidationLevel::None);
+        
+        // Strict violations are skipped like any other invalid record
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        let mut writer = csv::Writer::from_writer(&mut file);
+        writer.write_record(NppesMainSchema::column_names()).unwrap();
+        writer.write_record(&row).unwrap();
+        writer.write_record(&main_row(&[(0, "1245319599"), (1, "1"), (23, "TX")])).unwrap();
+        drop(writer);
+        let strict = reader(ValidationLevel::Strict);
+        assert!(strict.load_main_data(file.path()).is_err());
+        let loaded = strict.with_skip_invalid_records(true).load_main_data(file.path()).unwrap();
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded[0].npi.as_str(), "1245319599");
+    }
+    
+    #[test]
+    fn test_blank_entity_type_is_inferred_when_enabled() {
+        let row = main_row(&[(0, "1234567893"), (4, "ACME HEALTH LLC"), (42, "DOE")]);
+        
+        let record = NppesReader::new().parse_main_record(&row, 1).unwrap();
+        assert_eq!(record.entity_type, None);
+        
+        let record = NppesReader::new()
+            .with_entity_type_inference(true)
+            .parse_main_record(&row, 1)
+            .unwrap();
+        assert_eq!(record.entity_type, Some(EntityType::Organization));
+        assert!(record.authorized_official.is_some());
+    }
+    
+    #[test]
+    fn test_authorized_official_read_for_any_entity_type() {
+        let reader = NppesReader::new();
+        
+        let individual = reader.parse_main_record(&main_row(&[(0, "1234567893"), (1, "1"), (45, "OWNER"), (46, "5551234567")]), 1).unwrap();
+        let official = individual.authorized_official.unwrap();
+        assert_eq!(official.title.as_deref(), Some("OWNER"));
+        assert_eq!(official.telephone.as_deref(), Some("5551234567"));
+        
+        let organization = reader.parse_main_record(&main_row(&[(0, "1245319599"), (1, "2")]), 1).unwrap();
+        assert_eq!(organization.authorized_official, None);
+    }
+    
+    #[test]
+    fn test_quoted_multiline_address_keeps_record_boundaries() {
+        let mut writer = csv::WriterBuilder::new()
+            .terminator(csv::Terminator::CRLF)
+            .from_writer(Vec::new());
+        writer.write_record(NppesMainSchema::column_names()).unwrap();
+        writer.write_record(&main_row(&[(0, "1234567893"), (1, "1"), (28, "123 MAIN ST\r\nBUILDING 2\nSUITE 4"), (31, "TX")])).unwrap();
+        let mut bytes = writer.into_inner().unwrap();
+        
+        // Second record uses a bare `\n` terminator
+        let mut writer = csv::Writer::from_writer(Vec::new());
+        writer.write_record(&main_row(&[(0, "1245319599"), (1, "2"), (4, "ACME CLINIC"), (31, "CA")])).unwrap();
+        bytes.extend(writer.into_inner().unwrap());
+        
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        std::io::Write::write_all(&mut file, &bytes).unwrap();
+        
+        let records = NppesReader::new().load_main_data(file.path()).unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(
+            records[0].practice_address.line_1.as_deref(),
+            Some("123 MAIN ST\r\nBUILDING 2\nSUITE 4")
+        );
+        assert_eq!(records[0].practice_address.state, Some(StateCode::TX));
+        assert_eq!(records[1].npi.as_str(), "1245319599");
+        assert_eq!(records[1].practice_address.state, Some(StateCode::CA));
+    }
+    
+    #[test]
+    fn test_memory_estimate_samples_record_lengths() {
+        let mut writer = csv::Writer::from_writer(Vec::new());
+        writer.write_record(NppesMainSchema::column_names()).unwrap();
+        for npi in ["1234567893", "1245319599", "1003000126"] {
+            writer.write_record(&main_row(&[(0, npi), (1, "2"), (4, "ACME HEALTH SYSTEM")])).unwrap();
+        }
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        std::io::Write::write_all(&mut file, &writer.into_inner().unwrap()).unwrap();
+        
+        let estimate = NppesReader::estimate_memory_usage(file.path()).unwrap();
+        assert_eq!(estimate.estimated_records, 3);
+        assert_eq!(estimate.sampled_records, 3);
+        assert_eq!(estimate.average_populated_fields, 3.0);
+        assert!(estimate.memory_multiplier > 1.0);
+        assert!(estimate.bytes_per_record > size_of::<NppesRecord>() + estimate.average_record_bytes as usize);
+        assert_eq!(estimate.estimated_memory_bytes, 3 * estimate.bytes_per_record);
+        
+        let plain = estimate.with_memory_multiplier(1.0);
+        assert_eq!(plain.bytes_per_record, size_of::<NppesRecord>() + plain.average_record_bytes as usize);
+    }
+    
+    #[test]
+    fn test_memory_fits_with_headroom() {
+        let gb = MEMORY_HEADROOM_BYTES;
+        assert!(ensure_memory_fits(gb, Some(2 * gb)).is_ok());
+        assert!(ensure_memory_fits(gb, None).is_ok());
+        assert!(ensure_memory_fits(usize::MAX, None).is_ok());
+        match ensure_memory_fits(gb + 1, Some(2 * gb)).unwrap_err() {
+            NppesError::Memory { .. } => {}
+            other => panic!("expected memory error, got {:?}", other),
+        }
+        assert!(ensure_memory_fits(usize::MAX, Some(usize::MAX)).is_err());
+    }
+    
+    #[test]
+    fn test_bounded_load_of_oversized_file() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        let mut writer = csv::Writer::from_writer(&mut file);
+        writer.write_record(NppesMainSchema::column_names()).unwrap();
+        for npi in ["1234567893", "1245319599", "1003000126"] {
+            writer.write_record(&main_row(&[(0, npi), (1, "1"), (5, "SMITH")])).unwrap();
+        }
+        drop(writer);
+        
+        // Room for one record's worth of memory, not the whole file
+        let estimate = NppesReader::estimate_memory_usage(file.path()).unwrap();
+        let available = Some(MEMORY_HEADROOM_BYTES + estimate.bytes_per_record);
+        assert!(ensure_memory_fits(required_memory(&estimate, None), available).is_err());
+        assert!(ensure_memory_fits(required_memory(&estimate, Some(1)), available).is_ok());
+        assert_eq!(required_memory(&estimate, Some(10)), estimate.estimated_memory_bytes);
+        
+        let (records, offset) = NppesReader::new()
+            .with_memory_check(false)
+            .load_main_data_from_offset(file.path(), 0, Some(1))
+            .unwrap();
+        assert_eq!(records.len(), 1);
+        let (rest, _) = NppesReader::new().load_main_data_from_offset(file.path(), offset, Some(2)).unwrap();
+        assert_eq!(rest.len(), 2);
+    }
+    
+    #[test]
+    fn test_memory_estimate_record_count_from_sample() {
+        let taxonomies = ["207Q00000X", "208D00000X", "207R00000X", "363L00000X"];
+        let mut writer = csv::Writer::from_writer(Vec::new());
+        writer.write_record(NppesMainSchema::column_names()).unwrap();
+        let total = 5_000;
+        for i in 0..total {
+            // Vary the record length with the number of taxonomy codes
+            let mut fields = vec![(0, "1234567893"), (1, "1"), (5, "SMITH")];
+            for (slot, code) in taxonomies.iter().take(i % 4 + 1).enumerate() {
+                fields.push((47 + slot * 4, code));
+            }
+            writer.write_record(&main_row(&fields)).unwrap();
+        }
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        std::io::Write::write_all(&mut file, &writer.into_inner().unwrap()).unwrap();
+        
+        let estimate = NppesReader::estimate_memory_usage(file.path()).unwrap();
+        assert_eq!(estimate.sampled_records, MEMORY_ESTIMATE_SAMPLE_LINES);
+        let error = (estimate.estimated_records as f64 - total as f64).abs() / total as f64;
+        assert!(error < 0.05, "estimated {} records, actual {}", estimate.estimated_records, total);
+        assert_eq!(estimate.average_populated_fields, 5.5);
+    }
+    
+    #[test]
+    fn test_available_memory_parsing() {
+        let meminfo = "MemTotal:       16318412 kB\nMemFree:          512000 kB\nMemAvailable:    8000000 kB\n";
+        assert_eq!(parse_meminfo_available(meminfo), Some(8_000_000 * 1024));
+        assert_eq!(parse_meminfo_available("MemTotal: 16318412 kB\n"), None);
+        
+        let vm_stat = "Mach Virtual Memory Statistics: (page size of 16384 bytes)\n\
+            Pages free:                               10000.\n\
+            Pages active:                            500000.\n\
+            Pages inactive:                           20000.\n\
+            Pages speculative:                         3000.\n";
+        assert_eq!(parse_vm_stat_available(vm_stat), Some(33_000 * 16_384));
+        assert_eq!(parse_vm_stat_available("Pages free: 10."), None);
+    }
+    
+    #[cfg(any(target_os = "linux", target_os = "macos"))]
+    #[test]
+    fn test_available_memory_detected() {
+        assert!(get_available_memory_unix().is_some_and(|bytes| bytes > 0));
+    }
+    
+    #[cfg(target_os = "windows")]
+    #[test]
+    fn test_available_memory_detected() {
+        assert!(get_available_memory_windows().is_some_and(|bytes| bytes > 0));
+    }
+}
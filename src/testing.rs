@@ -0,0 +1,356 @@
+/*!
+ * Property-testing support for NPPES records
+ *
+ * Exposes `proptest` strategies that generate valid-ish `NppesRecord`s
+ * (check-digit valid NPIs, real state codes, plausible taxonomy codes) and a
+ * CSV round-trip helper for checking parse/export symmetry downstream.
+ *
+ * Enabled with the `testing` feature.
+ */
+
+use chrono::NaiveDate;
+use proptest::option;
+use proptest::prelude::*;
+use proptest::sample::select;
+
+use crate::{Result, NppesError};
+use crate::data_types::*;
+use crate::export::denormalized_row;
+use crate::reader::NppesReader;
+
+const STATE_CODES: &[&str] = &[
+    "AK", "AL", "AR", "AS", "AZ", "CA", "CO", "CT", "DC", "DE", "FL", "FM", "GA", "GU", "HI",
+    "IA", "ID", "IL", "IN", "KS", "KY", "LA", "MA", "MD", "ME", "MH", "MI", "MN", "MO", "MP",
+    "MS", "MT", "NC", "ND", "NE", "NH", "NJ", "NM", "NV", "NY", "OH", "OK", "OR", "PA", "PR",
+    "PW", "RI", "SC", "SD", "TN", "TX", "UT", "VA", "VI", "VT", "WA", "WI", "WV", "WY", "ZZ",
+];
+
+const TAXONOMY_CODES: &[&str] = &[
+    "207Q00000X", // Family Medicine
+    "207R00000X", // Internal Medicine
+    "208D00000X", // General Practice
+    "207RC0000X", // Cardiovascular Disease
+    "363L00000X", // Nurse Practitioner
+    "1223G0001X", // General Practice Dentistry
+    "261QP2300X", // Primary Care Clinic
+    "282N00000X", // General Acute Care Hospital
+    "333600000X", // Pharmacy
+];
+
+/// Compute the NPI check digit for a 9-digit base using the Luhn algorithm
+/// with the `80840` card issuer prefix
+fn npi_check_digit(base: &str) -> u32 {
+    let sum: u32 = format!("80840{}", base)
+        .chars()
+        .rev()
+        .filter_map(|c| c.to_digit(10))
+        .enumerate()
+        .map(|(i, d)| {
+            if i % 2 == 0 {
+                let doubled = d * 2;
+                if doubled > 9 { doubled - 9 } else { doubled }
+            } else {
+                d
+            }
+        })
+        .sum();
+    (10 - sum % 10) % 10
+}
+
+/// Strategy for NPIs with a valid check digit
+pub fn arb_npi() -> impl Strategy<Value = Npi> {
+    (1u32..=2, 0u32..100_000_000).prop_map(|(first, rest)| {
+        let base = format!("{}{:08}", first, rest);
+        let check = npi_check_digit(&base);
+        Npi(format!("{}{}", base, check))
+    })
+}
+
+/// Strategy for canonical state codes
+pub fn arb_state() -> impl Strategy<Value = StateCode> {
+    select(STATE_CODES).prop_map(|code| StateCode::from_code(code).expect("known state code"))
+}
+
+/// Strategy for dates within the lifetime of NPPES
+pub fn arb_date() -> impl Strategy<Value = NaiveDate> {
+    (2005i32..=2025, 1u32..=12, 1u32..=28)
+        .prop_map(|(y, m, d)| NaiveDate::from_ymd_opt(y, m, d).expect("valid calendar date"))
+}
+
+prop_compose! {
+    /// Strategy for US-style addresses
+    pub fn arb_address()(
+        line_1 in "[1-9][0-9]{0,4} [A-Z]{3,10} (ST|AVE|RD|BLVD)",
+        line_2 in option::of("(STE|APT) [1-9][0-9]{0,3}"),
+        city in "[A-Z]{3,12}",
+        state in option::of(arb_state()),
+        postal_code in option::of("[0-9]{5}([0-9]{4})?"),
+        telephone in option::of("[2-9][0-9]{9}"),
+        fax in option::of("[2-9][0-9]{9}"),
+        country in option::of(Just(CountryCode::from_code("US"))),
+    ) -> Address {
+        Address {
+            line_1: Some(line_1),
+            line_2,
+            city: Some(city),
+            postal_code,
+            telephone,
+            fax,
+            state,
+            country,
+        }
+    }
+}
+
+prop_compose! {
+    /// Strategy for taxonomy entries drawn from common NUCC codes
+    pub fn arb_taxonomy_code()(
+        code in select(TAXONOMY_CODES),
+        license_number in option::of("[A-Z0-9]{4,10}"),
+        license_state_code in option::of(arb_state()),
+        primary_switch in option::of(prop_oneof![
+            Just(PrimaryTaxonomySwitch::Yes),
+            Just(PrimaryTaxonomySwitch::No),
+            Just(PrimaryTaxonomySwitch::NotAnswered),
+        ]),
+        group_taxonomy_code in option::of(prop_oneof![
+            Just(GroupTaxonomyCode::MultiSpecialtyGroup),
+            Just(GroupTaxonomyCode::SingleSpecialtyGroup),
+        ]),
+    ) -> TaxonomyCode {
+        TaxonomyCode {
+            code: code.to_string(),
+            license_number,
+            license_state: license_state_code.as_ref().map(|s| s.as_code().to_string()),
+            license_state_code,
+            is_primary: primary_switch == Some(PrimaryTaxonomySwitch::Yes),
+            taxonomy_group: group_taxonomy_code.as_ref().map(|g| g.as_code().to_string()),
+            group_taxonomy_code,
+            primary_switch,
+        }
+    }
+}
+
+prop_compose! {
+    /// Strategy for other provider identifiers
+    pub fn arb_other_identifier()(
+        identifier in "[A-Z0-9]{4,12}",
+        type_code in option::of("0[1-9]"),
+        state in option::of(arb_state()),
+        issuer in option::of(prop_oneof![
+            Just(OtherProviderIdentifierIssuerCode::Other),
+            Just(OtherProviderIdentifierIssuerCode::Medicaid),
+        ]),
+    ) -> OtherIdentifier {
+        OtherIdentifier { identifier, type_code, issuer, state }
+    }
+}
+
+/// Strategy for name prefixes
+pub fn arb_name_prefix() -> impl Strategy<Value = NamePrefixCode> {
+    prop_oneof![
+        Just(NamePrefixCode::Ms),
+        Just(NamePrefixCode::Mr),
+        Just(NamePrefixCode::Miss),
+        Just(NamePrefixCode::Mrs),
+        Just(NamePrefixCode::Dr),
+        Just(NamePrefixCode::Prof),
+    ]
+}
+
+/// Strategy for name suffixes
+pub fn arb_name_suffix() -> impl Strategy<Value = NameSuffixCode> {
+    prop_oneof![
+        Just(NameSuffixCode::Jr),
+        Just(NameSuffixCode::Sr),
+        Just(NameSuffixCode::II),
+        Just(NameSuffixCode::III),
+    ]
+}
+
+prop_compose! {
+    /// Strategy for individual provider names
+    pub fn arb_provider_name()(
+        prefix in option::of(arb_name_prefix()),
+        first in option::of("[A-Z]{2,12}"),
+        middle in option::of("[A-Z]{1,12}"),
+        last in "[A-Z]{2,12}",
+        suffix in option::of(arb_name_suffix()),
+        credential in option::of("(MD|DO|RN|NP|PA|DDS)"),
+    ) -> ProviderName {
+        ProviderName { prefix, first, middle, last: Some(last), suffix, credential }
+    }
+}
+
+prop_compose! {
+    /// Strategy for authorized officials of organizations
+    pub fn arb_authorized_official()(
+        prefix in option::of(arb_name_prefix()),
+        first_name in option::of("[A-Z]{2,12}"),
+        middle_name in option::of("[A-Z]{1,12}"),
+        last_name in option::of("[A-Z]{2,12}"),
+        suffix in option::of(arb_name_suffix()),
+        credential in option::of("(MD|DO|RN|MBA)"),
+        title in option::of("(CEO|PRESIDENT|OWNER|ADMINISTRATOR)"),
+        telephone in option::of("[2-9][0-9]{9}"),
+    ) -> AuthorizedOfficial {
+        AuthorizedOfficial {
+            prefix,
+            first_name,
+            middle_name,
+            last_name,
+            suffix,
+            credential,
+            title,
+            telephone,
+        }
+    }
+}
+
+prop_compose! {
+    /// Strategy for valid-ish main provider records
+    pub fn arb_nppes_record()(
+        npi in arb_npi(),
+        entity_type in option::weighted(0.95, prop_oneof![
+            Just(EntityType::Individual),
+            Just(EntityType::Organization),
+        ]),
+        replacement_npi in option::weighted(0.05, arb_npi()),
+        ein in option::weighted(0.1, "[0-9]{9}".prop_map(|digits| Ein::from_raw(&digits))),
+        legal_business_name in "[A-Z]{3,12} (CLINIC|HOSPITAL|PHARMACY|GROUP)",
+        // Name strategies are boxed to keep the combined value tree small enough
+        // for the default test thread stack
+        provider_name in arb_provider_name().boxed(),
+        provider_other_name in option::weighted(0.1, (
+            arb_provider_name(),
+            prop_oneof![
+                Just(OtherProviderNameTypeCode::FormerName),
+                Just(OtherProviderNameTypeCode::ProfessionalName),
+                Just(OtherProviderNameTypeCode::OtherName),
+            ],
+        )).boxed(),
+        (mailing_address, practice_address) in (arb_address(), arb_address()),
+        (enumeration_date, last_update_date, certification_date) in (
+            option::of(arb_date()), option::of(arb_date()), option::of(arb_date())
+        ),
+        (deactivation_date, reactivation_date, deactivation_reason) in (
+            option::weighted(0.1, arb_date()),
+            option::weighted(0.05, arb_date()),
+            option::weighted(0.1, prop_oneof![
+                Just(DeactivationReasonCode::Death),
+                Just(DeactivationReasonCode::Disbandment),
+                Just(DeactivationReasonCode::Fraud),
+                Just(DeactivationReasonCode::Other),
+            ]),
+        ),
+        provider_gender in option::of(prop_oneof![
+            Just(SexCode::Male),
+            Just(SexCode::Female),
+            Just(SexCode::Undisclosed),
+            Just(SexCode::Unspecified),
+        ]),
+        taxonomy_codes in prop::collection::vec(arb_taxonomy_code(), 0..4),
+        other_identifiers in prop::collection::vec(arb_other_identifier(), 0..3),
+        authorized_official in arb_authorized_official().boxed(),
+        (sole_proprietor, organization_subpart) in (
+            option::of(prop_oneof![
+                Just(SoleProprietorCode::Yes),
+                Just(SoleProprietorCode::No),
+                Just(SoleProprietorCode::NotAnswered),
+            ]),
+            option::of(prop_oneof![
+                Just(SubpartCode::Yes),
+                Just(SubpartCode::No),
+            ]),
+        ),
+    ) -> NppesRecord {
+        let is_organization = entity_type == Some(EntityType::Organization);
+        NppesRecord {
+            npi,
+            entity_type,
+            replacement_npi,
+            ein,
+            provider_name: if is_organization { ProviderName::default() } else { provider_name },
+            provider_other_name_type: provider_other_name.as_ref()
+                .filter(|_| !is_organization)
+                .map(|(_, type_code)| type_code.clone()),
+            provider_other_name: provider_other_name
+                .filter(|_| !is_organization)
+                .map(|(name, _)| name)
+                .unwrap_or_default(),
+            organization_name: OrganizationName {
+                legal_business_name: is_organization.then_some(legal_business_name),
+                ..Default::default()
+            },
+            mailing_address,
+            practice_address,
+            enumeration_date,
+            last_update_date,
+            deactivation_date,
+            reactivation_date,
+            certification_date,
+            deactivation_reason,
+            provider_gender: if is_organization { None } else { provider_gender },
+            authorized_official: is_organization.then_some(authorized_official)
+                .filter(|official| !official.is_empty()),
+            sole_proprietor: if is_organization { None } else { sole_proprietor },
+            organization_subpart: if is_organization { organization_subpart } else { None },
+            taxonomy_codes,
+            other_identifiers,
+            ..Default::default()
+        }
+    }
+}
+
+/// Write a record to a CSV line in the main file layout and parse it back
+///
+/// Returns the re-parsed record, or an error if parsing fails or the result
+/// differs from the original.
+pub fn roundtrip_csv(record: &NppesRecord) -> Result<NppesRecord> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record(denormalized_row(record))?;
+    let bytes = writer.into_inner().map_err(|e| NppesError::from(e.into_error()))?;
+
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .from_reader(bytes.as_slice());
+    let row = reader.records().next().transpose()?.ok_or_else(|| NppesError::Custom {
+        message: "Round-trip produced no CSV row".to_string(),
+        suggestion: None,
+        source: None,
+    })?;
+
+    let parsed = NppesReader::new().parse_main_record(&row, 1)?;
+    if &parsed != record {
+        return Err(NppesError::DataValidation {
+            message: format!(
+                "Round-tripped record differs from the original\n  original: {:?}\n  parsed:   {:?}",
+                record, parsed
+            ),
+            field: None,
+            value: Some(record.npi.to_string()),
+            context: Default::default(),
+        });
+    }
+
+    Ok(parsed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_npi_check_digit() {
+        // Example NPI from the CMS check digit documentation
+        assert_eq!(npi_check_digit("123456789"), 3);
+    }
+
+    proptest! {
+        #[test]
+        fn generated_records_roundtrip(record in arb_nppes_record()) {
+            let parsed = roundtrip_csv(&record);
+            prop_assert!(parsed.is_ok(), "{}", parsed.unwrap_err());
+        }
+    }
+}